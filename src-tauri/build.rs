@@ -1,3 +1,20 @@
+use std::process::Command;
+
 fn main() {
+    // Commit the app was built from, for support requests
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .filter(|hash| !hash.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    
+    println!("cargo:rustc-env=JTLSYNC_GIT_HASH={}", git_hash);
+    println!("cargo:rustc-env=JTLSYNC_TARGET={}", std::env::var("TARGET").unwrap_or_default());
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    
     tauri_build::build()
 }
@@ -1,16 +1,222 @@
-use log::info;
-use reqwest::{Client, header::{HeaderMap, HeaderValue}};
+use chrono::{DateTime, Utc};
+use flate2::{Compression, write::GzEncoder};
+use log::{info, warn};
+use reqwest::{Client, Proxy, RequestBuilder, Response, StatusCode, header::{HeaderMap, HeaderName, HeaderValue, CONTENT_ENCODING, RETRY_AFTER}};
 use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
+use crate::config::settings::AppSettings;
 use crate::error::{Result, Error};
 use crate::db::models::{JtlCustomer, JtlOrder, JtlOrderItem};
 
+mod workflow;
+
+pub use workflow::{WorkflowEvent, PAID_EVENT_ID, HOLD_EVENT_ID};
+
+// Maximum number of retries after a 429 response before giving up
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+// Wait time when JTL sends a 429 without a usable Retry-After header
+const DEFAULT_RETRY_AFTER_SECS: u64 = 5;
+
+// Upper bound for a single Retry-After wait
+const MAX_RETRY_AFTER_SECS: u64 = 120;
+
+// Page size when listing sales orders
+const ORDER_LIST_PAGE_SIZE: u32 = 100;
+
+// Oldest JTL-Wawi API version the request models are known to work with
+pub const MIN_SUPPORTED_JTL_VERSION: &str = "1.0.0";
+
+// First JTL-Wawi API version with a major schema change we don't support yet
+pub const MAX_SUPPORTED_JTL_VERSION: &str = "2.0.0";
+
+/// Parse a dotted version string into numeric components, ignoring suffixes like "-beta"
+fn parse_version(version: &str) -> Vec<u32> {
+    let mut parts: Vec<u32> = version.trim()
+        .trim_start_matches(['v', 'V'])
+        .split(['.', '-', '+'])
+        .map_while(|part| part.parse::<u32>().ok())
+        .collect();
+    
+    // "1.2" compares equal to "1.2.0"
+    if !parts.is_empty() && parts.len() < 3 {
+        parts.resize(3, 0);
+    }
+    parts
+}
+
+/// Check whether a JTL API version is within the supported range
+pub fn is_supported_version(version: &str) -> bool {
+    let parsed = parse_version(version);
+    if parsed.is_empty() {
+        return false;
+    }
+    
+    parsed >= parse_version(MIN_SUPPORTED_JTL_VERSION) && parsed < parse_version(MAX_SUPPORTED_JTL_VERSION)
+}
+
+// Bodies smaller than this are not worth compressing
+const COMPRESSION_THRESHOLD_BYTES: usize = 8 * 1024;
+
+/// Gzip a request body, returning None if compression fails
+fn gzip_body(body: &str) -> Option<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body.as_bytes()).ok()?;
+    encoder.finish().ok()
+}
+
+/// Parse a Retry-After header given either as seconds or as an HTTP date
+fn parse_retry_after(headers: &HeaderMap) -> Option<u64> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(secs);
+    }
+    
+    chrono::DateTime::parse_from_rfc2822(value).ok()
+        .map(|date| (date.with_timezone(&Utc) - Utc::now()).num_seconds().max(0) as u64)
+}
+
+// Keys JTL uses for the human-readable part of an error object
+const ERROR_MESSAGE_KEYS: [&str; 6] = ["ErrorMessage", "Message", "message", "detail", "Detail", "title"];
+
+// Keys JTL uses for the field an error refers to
+const ERROR_FIELD_KEYS: [&str; 3] = ["PropertyName", "Field", "field"];
+
+/// Extract readable messages from a JTL error body, falling back to the raw body
+///
+/// Handles validation problem details (`{"errors": {"Field": ["msg"]}}`),
+/// lists of error objects and plain `{"Message": "..."}` objects.
+pub fn parse_jtl_error(body: &str) -> String {
+    let raw = body.trim();
+    
+    let value: Value = match serde_json::from_str(raw) {
+        Ok(value) => value,
+        Err(_) => return raw.to_string(),
+    };
+    
+    let mut messages = Vec::new();
+    collect_error_messages(&value, &mut messages);
+    
+    if messages.is_empty() {
+        raw.to_string()
+    } else {
+        messages.join("; ")
+    }
+}
+
+/// Recursively collect error messages from a JTL error value
+fn collect_error_messages(value: &Value, messages: &mut Vec<String>) {
+    match value {
+        Value::String(message) if !message.trim().is_empty() => messages.push(message.trim().to_string()),
+        Value::Array(items) => {
+            for item in items {
+                collect_error_messages(item, messages);
+            }
+        },
+        Value::Object(map) => {
+            let errors = map.get("errors").or_else(|| map.get("Errors"));
+            
+            // Validation problem details: field -> list of messages
+            if let Some(Value::Object(fields)) = errors {
+                for (field, field_errors) in fields {
+                    let mut field_messages = Vec::new();
+                    collect_error_messages(field_errors, &mut field_messages);
+                    for message in field_messages {
+                        messages.push(format!("{}: {}", field, message));
+                    }
+                }
+                return;
+            }
+            
+            // List of error objects
+            if let Some(list @ Value::Array(_)) = errors {
+                collect_error_messages(list, messages);
+                return;
+            }
+            
+            let message = ERROR_MESSAGE_KEYS.iter()
+                .find_map(|key| map.get(*key).and_then(|v| v.as_str()))
+                .filter(|m| !m.trim().is_empty());
+            let field = ERROR_FIELD_KEYS.iter()
+                .find_map(|key| map.get(*key).and_then(|v| v.as_str()))
+                .filter(|f| !f.trim().is_empty());
+            
+            match (field, message) {
+                (Some(field), Some(message)) => messages.push(format!("{}: {}", field, message.trim())),
+                (None, Some(message)) => messages.push(message.trim().to_string()),
+                _ => {}
+            }
+        },
+        _ => {}
+    }
+}
+
+/// Pick the customer whose number matches exactly from keyword search results
+fn find_exact_customer(items: &[Value], customer_number: &str) -> Option<Value> {
+    items.iter()
+        .find(|item| item["Number"].as_str().is_some_and(|number| number.trim() == customer_number))
+        .cloned()
+}
+
+/// Read the JTL id of an order or customer, which may come as a number or a string
+pub fn jtl_id(object: &Value) -> Option<String> {
+    match &object["Id"] {
+        Value::String(id) => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+/// Pick the id of the order whose external number matches exactly from search results
+fn find_exact_order_id(items: &[Value], order_number: &str) -> Option<String> {
+    items.iter()
+        .filter(|item| item["ExternalNumber"].as_str().is_some_and(|number| number.trim() == order_number))
+        .find_map(jtl_id)
+}
+
+/// Split line items into request batches, or one batch if they fit the batch size
+fn line_item_batches<T>(items: &[T], batch_size: Option<usize>) -> Vec<&[T]> {
+    match batch_size {
+        Some(size) if items.len() > size => items.chunks(size).collect(),
+        _ => vec![items],
+    }
+}
+
+/// Build the HTTP client used for JTL requests
+fn build_http_client(max_idle_per_host: Option<usize>, idle_timeout_secs: Option<u64>, proxy: Option<&str>) -> Client {
+    let mut builder = Client::builder()
+        .timeout(Duration::from_secs(30));
+    
+    if let Some(url) = proxy {
+        match Proxy::all(url.trim()) {
+            Ok(proxy) => builder = builder.proxy(proxy),
+            Err(e) => warn!("Ignoring invalid proxy '{}': {}", url, e),
+        }
+    }
+    
+    if let Some(max_idle) = max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max_idle);
+    }
+    if let Some(secs) = idle_timeout_secs {
+        builder = builder.pool_idle_timeout(Duration::from_secs(secs));
+    }
+    
+    builder.build().expect("Failed to create HTTP client")
+}
+
 /// JTL API client for interacting with the JTL-Wawi API
 pub struct JtlApiClient {
     client: Client,
     base_url: String,
     api_key: String,
+    compress_requests: AtomicBool, // Cleared if the server rejects gzip bodies
+    line_item_batch_size: Option<usize>, // None sends all line items in one request
+    extra_headers: HeaderMap,            // Merged over the default headers of every request
 }
 
 impl JtlApiClient {
@@ -18,16 +224,60 @@ impl JtlApiClient {
     pub fn new(api_key: &str) -> Self {
         let base_url = "http://127.0.0.1:5883/api/eazybusiness/v1".to_string();
         
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
-            .expect("Failed to create HTTP client");
+        let client = build_http_client(None, None, None);
         
         JtlApiClient {
             client,
             base_url,
             api_key: api_key.to_string(),
+            compress_requests: AtomicBool::new(false),
+            line_item_batch_size: None,
+            extra_headers: HeaderMap::new(),
+        }
+    }
+    
+    /// Create a client configured from the application settings
+    ///
+    /// Use this for every client so compression, batching, pooling, proxy and headers apply everywhere
+    pub fn from_settings(api_key: &str, settings: &AppSettings) -> Self {
+        let mut client = JtlApiClient::new(api_key)
+            .with_request_compression(settings.compress_requests)
+            .with_line_item_batch_size(settings.line_item_batch_size)
+            .with_extra_headers(&settings.extra_headers);
+        client.client = build_http_client(
+            settings.pool_max_idle_per_host,
+            settings.pool_idle_timeout_secs,
+            settings.proxy.as_deref(),
+        );
+        client
+    }
+    
+    /// Enable gzip compression for large request bodies
+    pub fn with_request_compression(self, enabled: bool) -> Self {
+        self.compress_requests.store(enabled, Ordering::SeqCst);
+        self
+    }
+    
+    /// Limit the number of line items sent per request, for servers that reject large bodies
+    pub fn with_line_item_batch_size(mut self, batch_size: Option<usize>) -> Self {
+        self.line_item_batch_size = batch_size.filter(|size| *size > 0);
+        self
+    }
+    
+    /// Add static headers to every request, e.g. for JTL sandbox environments
+    ///
+    /// A configured header replaces the default one of the same name, so this also
+    /// overrides `X-AppId`, `X-AppVersion` or the authorization scheme.
+    pub fn with_extra_headers(mut self, headers: &HashMap<String, String>) -> Self {
+        for (name, value) in headers {
+            match (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) {
+                (Ok(name), Ok(value)) => {
+                    self.extra_headers.insert(name, value);
+                },
+                _ => warn!("Ignoring invalid extra header '{}'", name),
+            }
         }
+        self
     }
     
     /// Create HTTP headers for API requests
@@ -38,35 +288,113 @@ impl JtlApiClient {
         headers.insert("X-AppVersion", HeaderValue::from_static("2.0.0"));
         headers.insert("Content-Type", HeaderValue::from_static("application/json"));
         headers.insert("Accept", HeaderValue::from_static("application/json"));
+        headers.insert("Accept-Encoding", HeaderValue::from_static("gzip, deflate"));
+        for (name, value) in &self.extra_headers {
+            headers.insert(name.clone(), value.clone());
+        }
         headers
     }
     
+    /// Send a request, waiting and retrying when JTL responds with 429
+    async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        let mut attempt = 0;
+        
+        loop {
+            let current = request.try_clone()
+                .ok_or_else(|| Error::Api("Request body cannot be retried".to_string()))?;
+            
+            let response = current.send()
+                .await
+                .map_err(|e| Error::Api(format!("Request error: {}", e)))?;
+                
+            if response.status() != StatusCode::TOO_MANY_REQUESTS {
+                return Ok(response);
+            }
+            
+            let retry_after_secs = parse_retry_after(response.headers())
+                .unwrap_or(DEFAULT_RETRY_AFTER_SECS)
+                .min(MAX_RETRY_AFTER_SECS);
+            
+            if attempt >= MAX_RATE_LIMIT_RETRIES {
+                return Err(Error::RateLimited { retry_after_secs });
+            }
+            
+            attempt += 1;
+            warn!("JTL API rate limit reached, retrying in {}s (attempt {}/{})", 
+                  retry_after_secs, attempt, MAX_RATE_LIMIT_RETRIES);
+            tokio::time::sleep(Duration::from_secs(retry_after_secs)).await;
+        }
+    }
+    
+    /// Get the version reported by the JTL-Wawi API
+    pub async fn get_api_version(&self) -> Result<String> {
+        let url = format!("{}/info", self.base_url);
+        
+        let request = self.client.get(&url)
+            .headers(self.create_headers());
+        let response = self.send(request).await?;
+            
+        let status = response.status();
+        if status.is_success() {
+            let data = response.json::<Value>().await
+                .map_err(|e| Error::Api(format!("Response parsing error: {}", e)))?;
+                
+            ["Version", "ApiVersion", "version"].iter()
+                .find_map(|key| data[*key].as_str())
+                .map(|v| v.to_string())
+                .ok_or_else(|| Error::Api("Version missing in JTL info response".to_string()))
+        } else {
+            Err(Error::Api(format!("HTTP error: {}", status)))
+        }
+    }
+    
     /// Get a customer by their ID
     pub async fn get_customer_by_id(&self, customer_id: &str) -> Result<Option<Value>> {
         let url = format!("{}/customers?searchKeyWord={}", self.base_url, customer_id);
         
-        let response = self.client.get(&url)
-            .headers(self.create_headers())
-            .send()
-            .await
-            .map_err(|e| Error::Api(format!("Request error: {}", e)))?;
+        let request = self.client.get(&url)
+            .headers(self.create_headers());
+        let response = self.send(request).await?;
             
         let status = response.status();
         if status.is_success() {
             let data = response.json::<Value>().await
                 .map_err(|e| Error::Api(format!("Response parsing error: {}", e)))?;
                 
-            // Check if customers were found
-            if let Some(total_items) = data["TotalItems"].as_i64() {
-                if total_items > 0 {
-                    if let Some(items) = data["Items"].as_array() {
-                        if !items.is_empty() {
-                            return Ok(Some(items[0].clone()));
-                        }
-                    }
-                }
+            // The keyword search is fuzzy, so only an exact number match counts
+            let items = data["Items"].as_array().map(Vec::as_slice).unwrap_or_default();
+            if items.len() > 1 {
+                info!("Customer search for {} returned {} matches, looking for exact number", customer_id, items.len());
             }
-            Ok(None)
+            Ok(find_exact_customer(items, customer_id))
+        } else {
+            Err(Error::Api(format!("HTTP error: {}", status)))
+        }
+    }
+    
+    /// List the IDs of a JTL reference resource, e.g. `paymentMethods`
+    pub async fn list_reference_ids(&self, resource: &str) -> Result<Vec<i64>> {
+        let url = format!("{}/{}", self.base_url, resource);
+        
+        let request = self.client.get(&url)
+            .headers(self.create_headers());
+        let response = self.send(request).await?;
+            
+        let status = response.status();
+        if status.is_success() {
+            let data = response.json::<Value>().await
+                .map_err(|e| Error::Api(format!("Response parsing error: {}", e)))?;
+            
+            // Paged responses wrap the list in Items, others return it directly
+            let items = data["Items"].as_array().or_else(|| data.as_array()).cloned().unwrap_or_default();
+            
+            Ok(items.iter()
+                .filter_map(|item| match &item["Id"] {
+                    Value::Number(id) => id.as_i64(),
+                    Value::String(id) => id.parse().ok(),
+                    _ => None,
+                })
+                .collect())
         } else {
             Err(Error::Api(format!("HTTP error: {}", status)))
         }
@@ -77,11 +405,9 @@ impl JtlApiClient {
         let url = format!("{}/salesOrders?externalOrderNumber={}&customerId={}", 
                          self.base_url, order_number, customer_id);
         
-        let response = self.client.get(&url)
-            .headers(self.create_headers())
-            .send()
-            .await
-            .map_err(|e| Error::Api(format!("Request error: {}", e)))?;
+        let request = self.client.get(&url)
+            .headers(self.create_headers());
+        let response = self.send(request).await?;
             
         let status = response.status();
         if status.is_success() {
@@ -98,6 +424,148 @@ impl JtlApiClient {
         }
     }
     
+    /// Update the header of an existing order (addresses, dates, shipping details, comment)
+    ///
+    /// Line items are left unchanged
+    pub async fn update_order(&self, order_id: &str, order: &JtlOrder) -> Result<()> {
+        let url = format!("{}/salesOrders/{}", self.base_url, order_id);
+        
+        let order_json = serde_json::to_string(order)
+            .map_err(|e| Error::Api(format!("Serialization error: {}", e)))?;
+        
+        let request = self.client.patch(&url)
+            .headers(self.create_headers())
+            .body(order_json);
+        let response = self.send(request).await?;
+            
+        let status = response.status();
+        if status.is_success() {
+            info!("Order {} successfully updated", order_id);
+            Ok(())
+        } else {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            Err(Error::Api(format!("HTTP error {}: {}", status, parse_jtl_error(&error_text))))
+        }
+    }
+    
+    /// Find the JTL order ID for an external order number
+    pub async fn find_order_id(&self, order_number: &str) -> Result<Option<String>> {
+        let url = format!("{}/salesOrders?externalOrderNumber={}", self.base_url, order_number);
+        
+        let request = self.client.get(&url)
+            .headers(self.create_headers());
+        let response = self.send(request).await?;
+            
+        let status = response.status();
+        if status.is_success() {
+            let data = response.json::<Value>().await
+                .map_err(|e| Error::Api(format!("Response parsing error: {}", e)))?;
+                
+            // The filter may match loosely, only an identical external number counts
+            Ok(data["Items"].as_array()
+                .and_then(|items| find_exact_order_id(items, order_number)))
+        } else {
+            Err(Error::Api(format!("HTTP error: {}", status)))
+        }
+    }
+    
+    /// List the external order numbers of all sales orders created since a point in time
+    ///
+    /// Pages through the sales orders, so a sync needs a few list calls instead of one check per order
+    pub async fn list_external_order_numbers(&self, since: DateTime<Utc>) -> Result<HashSet<String>> {
+        let orders = self.list_orders_since(since).await?;
+        
+        Ok(orders.iter()
+            .filter_map(|order| order["ExternalNumber"].as_str())
+            .filter(|number| !number.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+    
+    /// List all sales orders created since a point in time, paging through the results
+    pub async fn list_orders_since(&self, since: DateTime<Utc>) -> Result<Vec<Value>> {
+        let mut orders = Vec::new();
+        let mut page_index = 1;
+        
+        loop {
+            let url = format!("{}/salesOrders?createdSince={}&pageIndex={}&pageSize={}", 
+                             self.base_url, since.format("%Y-%m-%dT%H:%M:%SZ"), page_index, ORDER_LIST_PAGE_SIZE);
+            
+            let request = self.client.get(&url)
+                .headers(self.create_headers());
+            let response = self.send(request).await?;
+            
+            let status = response.status();
+            if !status.is_success() {
+                return Err(Error::Api(format!("HTTP error: {}", status)));
+            }
+            
+            let data = response.json::<Value>().await
+                .map_err(|e| Error::Api(format!("Response parsing error: {}", e)))?;
+            
+            let items = data["Items"].as_array().cloned().unwrap_or_default();
+            let page_len = items.len();
+            orders.extend(items);
+            
+            let has_next_page = data["HasNextPage"].as_bool()
+                .unwrap_or(page_len as u32 >= ORDER_LIST_PAGE_SIZE);
+            if !has_next_page || page_len == 0 {
+                break;
+            }
+            page_index += 1;
+        }
+        
+        Ok(orders)
+    }
+    
+    /// Get an order by its JTL ID
+    pub async fn get_order(&self, order_id: &str) -> Result<Value> {
+        let url = format!("{}/salesOrders/{}", self.base_url, order_id);
+        
+        let request = self.client.get(&url)
+            .headers(self.create_headers());
+        let response = self.send(request).await?;
+            
+        let status = response.status();
+        if status.is_success() {
+            response.json::<Value>().await
+                .map_err(|e| Error::Api(format!("Response parsing error: {}", e)))
+        } else {
+            Err(Error::Api(format!("HTTP error: {}", status)))
+        }
+    }
+    
+    /// Get the line items of an order
+    pub async fn get_order_line_items(&self, order_id: &str) -> Result<Vec<Value>> {
+        let url = format!("{}/salesOrders/{}/lineitems", self.base_url, order_id);
+        
+        let request = self.client.get(&url)
+            .headers(self.create_headers());
+        let response = self.send(request).await?;
+            
+        let status = response.status();
+        if status.is_success() {
+            let data = response.json::<Value>().await
+                .map_err(|e| Error::Api(format!("Response parsing error: {}", e)))?;
+            
+            Ok(data["Items"].as_array().or_else(|| data.as_array()).cloned().unwrap_or_default())
+        } else {
+            Err(Error::Api(format!("HTTP error: {}", status)))
+        }
+    }
+    
+    /// Get the current status of an order
+    pub async fn get_order_status(&self, order_id: &str) -> Result<String> {
+        let data = self.get_order(order_id).await?;
+        
+        let order_status = match &data["Status"] {
+            Value::String(s) => s.clone(),
+            Value::Null => "Unknown".to_string(),
+            other => other.to_string(),
+        };
+        Ok(order_status)
+    }
+    
     /// Create a new customer
     pub async fn create_customer(&self, customer: &JtlCustomer) -> Result<Value> {
         let url = format!("{}/customers", self.base_url);
@@ -105,12 +573,10 @@ impl JtlApiClient {
         let customer_json = serde_json::to_string(customer)
             .map_err(|e| Error::Api(format!("Serialization error: {}", e)))?;
         
-        let response = self.client.post(&url)
+        let request = self.client.post(&url)
             .headers(self.create_headers())
-            .body(customer_json)
-            .send()
-            .await
-            .map_err(|e| Error::Api(format!("Request error: {}", e)))?;
+            .body(customer_json);
+        let response = self.send(request).await?;
             
         let status = response.status();
         if status.is_success() {
@@ -120,24 +586,43 @@ impl JtlApiClient {
             Ok(data)
         } else {
             let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            Err(Error::Api(format!("HTTP error {}: {}", status, error_text)))
+            Err(Error::Api(format!("HTTP error {}: {}", status, parse_jtl_error(&error_text))))
         }
     }
     
-    /// Create a new order with items
-    pub async fn create_order(&self, order: &JtlOrder, items: &[JtlOrderItem]) -> Result<Value> {
-        // First create the order
+    /// Replace the data of an existing customer, e.g. after an address change
+    pub async fn update_customer(&self, customer_id: &str, customer: &JtlCustomer) -> Result<()> {
+        let url = format!("{}/customers/{}", self.base_url, customer_id);
+        
+        let customer_json = serde_json::to_string(customer)
+            .map_err(|e| Error::Api(format!("Serialization error: {}", e)))?;
+        
+        let request = self.client.patch(&url)
+            .headers(self.create_headers())
+            .body(customer_json);
+        let response = self.send(request).await?;
+            
+        let status = response.status();
+        if status.is_success() {
+            info!("Customer {} successfully updated", customer_id);
+            Ok(())
+        } else {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            Err(Error::Api(format!("HTTP error {}: {}", status, parse_jtl_error(&error_text))))
+        }
+    }
+    
+    /// Create a new sales order without its line items
+    pub async fn create_sales_order(&self, order: &JtlOrder) -> Result<Value> {
         let url = format!("{}/salesOrders", self.base_url);
         
         let order_json = serde_json::to_string(order)
             .map_err(|e| Error::Api(format!("Serialization error: {}", e)))?;
         
-        let response = self.client.post(&url)
+        let request = self.client.post(&url)
             .headers(self.create_headers())
-            .body(order_json)
-            .send()
-            .await
-            .map_err(|e| Error::Api(format!("Request error: {}", e)))?;
+            .body(order_json);
+        let response = self.send(request).await?;
             
         let status = response.status();
         if status.is_success() {
@@ -145,35 +630,83 @@ impl JtlApiClient {
                 .map_err(|e| Error::Api(format!("Response parsing error: {}", e)))?;
 
             info!("Order: {}", data["Id"]);
-
-            let order_id = match data["Id"].as_i64() {
-                Some(id) => id as i32,
-                None => return Err(Error::Api("Invalid order ID".to_string()))
-            };
-            
-            // Add order items
-            self.add_order_items(&order_id, items).await?;
             
             Ok(data)
         } else {
             let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            Err(Error::Api(format!("HTTP error {}: {}", status, error_text)))
+            Err(Error::Api(format!("HTTP error {}: {}", status, parse_jtl_error(&error_text))))
         }
     }
     
-    /// Add items to an order
-    async fn add_order_items(&self, order_id: &i32, items: &[JtlOrderItem]) -> Result<()> {
+    /// Add items to an order, split into batches if a batch size is set
+    ///
+    /// All batches are attempted, failures are reported together
+    pub async fn add_order_items(&self, order_id: &i32, items: &[JtlOrderItem]) -> Result<()> {
+        let batches = line_item_batches(items, self.line_item_batch_size);
+        if batches.len() == 1 {
+            return self.post_line_items(order_id, items).await;
+        }
+        
+        let mut failures = Vec::new();
+        
+        for (index, batch) in batches.iter().enumerate() {
+            if let Err(e) = self.post_line_items(order_id, batch).await {
+                warn!("Line item batch {}/{} for order {} failed: {}", index + 1, batches.len(), order_id, e);
+                failures.push(format!("batch {}: {}", index + 1, e));
+            }
+        }
+        
+        if failures.is_empty() {
+            info!("{} order items added in {} batches", items.len(), batches.len());
+            Ok(())
+        } else {
+            Err(Error::Api(format!("{} of {} line item batches failed for order {}: {}", 
+                                   failures.len(), batches.len(), order_id, failures.join("; "))))
+        }
+    }
+    
+    /// Post line items to an order in a single request
+    async fn post_line_items(&self, order_id: &i32, items: &[JtlOrderItem]) -> Result<()> {
         let url = format!("{}/salesOrders/{}/lineitems", self.base_url, order_id);
         
         let items_json = serde_json::to_string(items)
             .map_err(|e| Error::Api(format!("Serialization error: {}", e)))?;
         
-        let response = self.client.post(&url)
+        // Large bodies are sent gzipped when enabled, falling back to plain JSON
+        let compressed = if self.compress_requests.load(Ordering::SeqCst) && items_json.len() >= COMPRESSION_THRESHOLD_BYTES {
+            gzip_body(&items_json)
+        } else {
+            None
+        };
+        
+        if let Some(body) = compressed {
+            let request = self.client.post(&url)
+                .headers(self.create_headers())
+                .header(CONTENT_ENCODING, "gzip")
+                .body(body);
+            let response = self.send(request).await?;
+            
+            let status = response.status();
+            if status.is_success() {
+                info!("{} order items successfully added (gzip)", items.len());
+                return Ok(());
+            }
+            
+            if status == StatusCode::UNSUPPORTED_MEDIA_TYPE {
+                warn!("JTL server does not accept compressed requests, disabling compression");
+                self.compress_requests.store(false, Ordering::SeqCst);
+            } else if status != StatusCode::BAD_REQUEST {
+                let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                return Err(Error::Api(format!("HTTP error {}: {}", status, parse_jtl_error(&error_text))));
+            }
+            
+            warn!("Compressed line item request for order {} failed with {}, retrying uncompressed", order_id, status);
+        }
+        
+        let request = self.client.post(&url)
             .headers(self.create_headers())
-            .body(items_json)
-            .send()
-            .await
-            .map_err(|e| Error::Api(format!("Request error: {}", e)))?;
+            .body(items_json);
+        let response = self.send(request).await?;
             
         let status = response.status();
         if status.is_success() {
@@ -181,55 +714,158 @@ impl JtlApiClient {
             Ok(())
         } else {
             let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            Err(Error::Api(format!("HTTP error {}: {}", status, error_text)))
+            Err(Error::Api(format!("HTTP error {}: {}", status, parse_jtl_error(&error_text))))
         }
     }
     
     /// Mark an order as paid
     pub async fn set_payment_paid(&self, order_id: &String) -> Result<()> {
-        let url = format!("{}/salesOrders/{}/workflowEvents", self.base_url, order_id);
-        
-        // Order status "Paid" (ID 15)
-        let payload = r#"{"Id": 15}"#;
-        
-        let response = self.client.post(&url)
-            .headers(self.create_headers())
-            .body(payload)
-            .send()
-            .await
-            .map_err(|e| Error::Api(format!("Request error: {}", e)))?;
-            
-        let status = response.status();
-        if status.is_success() {
-            info!("Order {} successfully marked as paid", order_id);
-            Ok(())
-        } else {
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            Err(Error::Api(format!("HTTP error {}: {}", status, error_text)))
-        }
+        self.trigger_workflow_event(order_id, WorkflowEvent::Paid).await
     }
 
     /// Set order on hold
     pub async fn set_order_hold(&self, order_id: &String) -> Result<()> {
-        let url = format!("{}/salesOrders/{}/workflowEvents", self.base_url, order_id);
+        self.trigger_workflow_event(order_id, WorkflowEvent::OnHold).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn parses_nested_error_list() {
+        let body = r#"{"Errors": [{"PropertyName": "Email", "ErrorMessage": "is invalid"}, {"Message": "Customer missing"}]}"#;
+        assert_eq!(parse_jtl_error(body), "Email: is invalid; Customer missing");
+    }
+    
+    #[test]
+    fn parses_validation_problem_details() {
+        let body = r#"{"title": "Validation failed", "errors": {"PostalCode": ["is required", "is too short"]}}"#;
+        assert_eq!(parse_jtl_error(body), "PostalCode: is required; PostalCode: is too short");
+    }
+    
+    #[test]
+    fn parses_plain_message() {
+        assert_eq!(parse_jtl_error(r#"{"Message": " Order not found "}"#), "Order not found");
+    }
+    
+    #[test]
+    fn returns_raw_body_when_not_json() {
+        assert_eq!(parse_jtl_error("  Internal Server Error \n"), "Internal Server Error");
+        assert_eq!(parse_jtl_error(""), "");
+    }
+    
+    #[test]
+    fn returns_raw_body_when_json_has_no_message() {
+        assert_eq!(parse_jtl_error(r#"{"status": 500}"#), r#"{"status": 500}"#);
+    }
+    
+    #[test]
+    fn parses_versions_with_prefix_and_suffix() {
+        assert_eq!(parse_version("v1.5.2-beta"), vec![1, 5, 2]);
+        assert_eq!(parse_version("1.2"), vec![1, 2, 0]);
+        assert!(parse_version("unknown").is_empty());
+    }
+    
+    #[test]
+    fn checks_supported_version_range() {
+        assert!(is_supported_version("1.0"));
+        assert!(is_supported_version("1.9.12"));
+        assert!(!is_supported_version("0.9.9"));
+        assert!(!is_supported_version("2.0.0"));
+        assert!(!is_supported_version(""));
+    }
+    
+    fn retry_after(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+    
+    #[test]
+    fn parses_retry_after_seconds() {
+        assert_eq!(parse_retry_after(&retry_after(" 120 ")), Some(120));
+        assert_eq!(parse_retry_after(&HeaderMap::new()), None);
+        assert_eq!(parse_retry_after(&retry_after("soon")), None);
+    }
+    
+    #[test]
+    fn parses_retry_after_http_date() {
+        let future = (Utc::now() + chrono::Duration::seconds(90)).to_rfc2822();
+        let secs = parse_retry_after(&retry_after(&future)).unwrap();
+        assert!((85..=90).contains(&secs));
         
-        // Order status "On Hold" (ID 16)
-        let payload = r#"{"Id": 16}"#;
+        assert_eq!(parse_retry_after(&retry_after("Wed, 21 Oct 2015 07:28:00 GMT")), Some(0));
+    }
+    
+    #[test]
+    fn reads_ids_given_as_number_or_string() {
+        assert_eq!(jtl_id(&serde_json::json!({"Id": 42})).as_deref(), Some("42"));
+        assert_eq!(jtl_id(&serde_json::json!({"Id": "42"})).as_deref(), Some("42"));
+        assert_eq!(jtl_id(&serde_json::json!({"Id": null})), None);
+    }
+    
+    #[test]
+    fn finds_only_exact_external_number_match() {
+        let items = vec![
+            serde_json::json!({"Id": 1, "ExternalNumber": "VM-SHOP1-420"}),
+            serde_json::json!({"Id": "2", "ExternalNumber": "VM-SHOP1-42"}),
+        ];
         
-        let response = self.client.post(&url)
-            .headers(self.create_headers())
-            .body(payload)
-            .send()
-            .await
-            .map_err(|e| Error::Api(format!("Request error: {}", e)))?;
-            
-        let status = response.status();
-        if status.is_success() {
-            info!("Order {} successfully put on hold", order_id);
-            Ok(())
-        } else {
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            Err(Error::Api(format!("HTTP error {}: {}", status, error_text)))
-        }
+        assert_eq!(find_exact_order_id(&items, "VM-SHOP1-42").as_deref(), Some("2"));
+        assert_eq!(find_exact_order_id(&items, "VM-SHOP1-4"), None);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn client_builds_with_custom_pool_settings() {
+        let mut settings = AppSettings::default();
+        settings.pool_max_idle_per_host = Some(2);
+        settings.pool_idle_timeout_secs = Some(30);
+        settings.proxy = Some("http://proxy.local:3128".to_string());
+        
+        let client = JtlApiClient::from_settings("key", &settings);
+        
+        assert_eq!(client.api_key, "key");
+        assert!(client.create_headers().contains_key("Authorization"));
+    }
+    
+    #[test]
+    fn finds_only_exact_customer_number_match() {
+        let items = vec![
+            serde_json::json!({"Id": 1, "Number": "K-1001"}),
+            serde_json::json!({"Id": 2, "Number": " K-100 "}),
+        ];
+        
+        assert_eq!(find_exact_customer(&items, "K-100").map(|customer| customer["Id"].clone()), Some(serde_json::json!(2)));
+        assert_eq!(find_exact_customer(&items, "K-10"), None);
+    }
+    
+    #[test]
+    fn gzipped_body_decompresses_to_original() {
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+        
+        let body = serde_json::json!([{"Sku": "A-1", "Quantity": 2}]).to_string().repeat(500);
+        let compressed = gzip_body(&body).unwrap();
+        
+        let mut decompressed = String::new();
+        GzDecoder::new(compressed.as_slice()).read_to_string(&mut decompressed).unwrap();
+        
+        assert!(compressed.len() < body.len());
+        assert_eq!(decompressed, body);
+    }
+    
+    #[test]
+    fn splits_line_items_into_batches() {
+        let items: Vec<u32> = (0..500).collect();
+        
+        let batches = line_item_batches(&items, Some(120));
+        assert_eq!(batches.len(), 5);
+        assert_eq!(batches[4].len(), 20);
+        assert_eq!(batches.iter().map(|batch| batch.len()).sum::<usize>(), 500);
+        
+        assert_eq!(line_item_batches(&items, Some(500)).len(), 1);
+        assert_eq!(line_item_batches(&items, None).len(), 1);
+    }
+}
@@ -0,0 +1,83 @@
+use log::info;
+use serde_json::json;
+
+use super::{parse_jtl_error, JtlApiClient};
+use crate::error::{Result, Error};
+
+/// JTL workflow event "Paid"
+pub const PAID_EVENT_ID: i32 = 15;
+
+/// JTL workflow event "On Hold"
+pub const HOLD_EVENT_ID: i32 = 16;
+
+/// Workflow events the sync triggers on orders
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkflowEvent {
+    Paid,
+    OnHold,
+}
+
+impl WorkflowEvent {
+    /// JTL id of the event
+    pub fn id(&self) -> i32 {
+        match self {
+            WorkflowEvent::Paid => PAID_EVENT_ID,
+            WorkflowEvent::OnHold => HOLD_EVENT_ID,
+        }
+    }
+    
+    /// Short description for logs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkflowEvent::Paid => "marked as paid",
+            WorkflowEvent::OnHold => "put on hold",
+        }
+    }
+}
+
+/// Request body that triggers a workflow event
+fn workflow_payload(event_id: i32) -> String {
+    json!({ "Id": event_id }).to_string()
+}
+
+impl JtlApiClient {
+    /// Trigger a workflow event on an order
+    ///
+    /// Callers log the outcome, they know what the event means
+    pub async fn trigger_workflow(&self, order_id: &str, event_id: i32) -> Result<()> {
+        let url = format!("{}/salesOrders/{}/workflowEvents", self.base_url, order_id);
+        
+        let payload = workflow_payload(event_id);
+        
+        let request = self.client.post(&url)
+            .headers(self.create_headers())
+            .body(payload);
+        let response = self.send(request).await?;
+        
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            Err(Error::Api(format!("HTTP error {}: {}", status, parse_jtl_error(&error_text))))
+        }
+    }
+    
+    /// Trigger one of the named workflow events on an order
+    pub async fn trigger_workflow_event(&self, order_id: &str, event: WorkflowEvent) -> Result<()> {
+        self.trigger_workflow(order_id, event.id()).await?;
+        info!("Order {} successfully {}", order_id, event.as_str());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn named_events_post_their_jtl_id() {
+        assert_eq!(workflow_payload(WorkflowEvent::Paid.id()), r#"{"Id":15}"#);
+        assert_eq!(workflow_payload(WorkflowEvent::OnHold.id()), r#"{"Id":16}"#);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod jtl;
+pub mod webhook;
 
 // Re-export commonly used types
 pub use jtl::JtlApiClient;
\ No newline at end of file
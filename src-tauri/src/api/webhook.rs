@@ -0,0 +1,105 @@
+use hmac::{Hmac, Mac};
+use log::{info, warn};
+use reqwest::Client;
+use sha2::Sha256;
+use std::time::Duration;
+
+use crate::config::app::AppConfig;
+use crate::error::{Result, Error};
+use crate::sync::stats::SyncStats;
+
+// Webhooks must not hold up the sync, so keep the timeout short
+const WEBHOOK_TIMEOUT_SECS: u64 = 5;
+
+// Header carrying the HMAC-SHA256 signature of the request body
+const SIGNATURE_HEADER: &str = "X-JtlSync-Signature";
+
+/// Sign a webhook payload with HMAC-SHA256 and return the hex digest
+pub fn sign_payload(secret: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Serialize the webhook body and its signature header value, if a secret is set
+fn webhook_payload(secret: Option<&str>, stats: &SyncStats) -> Result<(String, Option<String>)> {
+    let body = serde_json::to_string(stats)
+        .map_err(|e| Error::Api(format!("Serialization error: {}", e)))?;
+    let signature = secret.filter(|s| !s.is_empty())
+        .map(|secret| format!("sha256={}", sign_payload(secret, &body)));
+    Ok((body, signature))
+}
+
+/// POST the final sync stats to the configured completion webhook
+pub async fn post_completion_webhook(url: &str, secret: Option<&str>, stats: &SyncStats) -> Result<()> {
+    let (body, signature) = webhook_payload(secret, stats)?;
+    
+    let client = Client::builder()
+        .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| Error::Api(format!("Failed to create HTTP client: {}", e)))?;
+    
+    let mut request = client.post(url)
+        .header("Content-Type", "application/json");
+    
+    if let Some(signature) = signature {
+        request = request.header(SIGNATURE_HEADER, signature);
+    }
+    
+    let response = request.body(body)
+        .send()
+        .await
+        .map_err(|e| Error::Api(format!("Request error: {}", e)))?;
+        
+    let status = response.status();
+    if status.is_success() {
+        info!("Completion webhook delivered for shop {}", stats.shop_id);
+        Ok(())
+    } else {
+        Err(Error::Api(format!("Webhook HTTP error: {}", status)))
+    }
+}
+
+/// Fire-and-forget the completion webhook if one is configured
+pub fn send_completion_webhook(config: &AppConfig, stats: &SyncStats) {
+    let url = match config.completion_webhook_url.clone().filter(|u| !u.trim().is_empty()) {
+        Some(url) => url,
+        None => return,
+    };
+    let secret = config.webhook_secret.clone();
+    let stats = stats.clone();
+    
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = post_completion_webhook(&url, secret.as_deref(), &stats).await {
+            warn!("Completion webhook for shop {} failed: {}", stats.shop_id, e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn signs_payload_with_hmac_sha256() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign_payload("Jefe", "what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+    
+    #[test]
+    fn payload_is_signed_stats_json() {
+        let stats = SyncStats { shop_id: "shop1".to_string(), ..Default::default() };
+        
+        let (body, signature) = webhook_payload(Some("secret"), &stats).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(parsed["shop_id"], "shop1");
+        assert_eq!(signature, Some(format!("sha256={}", sign_payload("secret", &body))));
+        
+        assert_eq!(webhook_payload(Some(""), &stats).unwrap().1, None);
+        assert_eq!(webhook_payload(None, &stats).unwrap().1, None);
+    }
+}
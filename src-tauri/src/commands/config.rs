@@ -1,10 +1,14 @@
 use chrono::Utc;
-use tauri::{AppHandle, Runtime, Emitter};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Runtime};
 
-use crate::config::{load_config, save_config, add_shop, update_shop, remove_shop, set_current_shop};
+use crate::config::{load_config, save_config, add_shop, duplicate_shop, update_shop, remove_shop, unarchive_shop, set_current_shop, set_shop_enabled, set_shop_default_shipping_method, reorder_shops, update_settings};
 use crate::config::app::AppConfig;
+use crate::config::settings::AppSettings;
 use crate::config::shop::ShopConfig;
 use crate::models::LogEntry;
+use crate::utils::emit::emit_log;
+use crate::utils::tasks::{is_shop_syncing, running_sync_count};
 use crate::error::{Result, Error};
 use tauri::ipc::InvokeError;
 use anyhow::Context;
@@ -15,7 +19,7 @@ pub fn save_config_command<R: Runtime>(app_handle: AppHandle<R>, config: AppConf
     save_config(&config)?;
     
     // Send log event
-    let _ = app_handle.emit("log", LogEntry {
+    let _ = emit_log(&app_handle, LogEntry {
         timestamp: Utc::now(),
         message: "Configuration saved successfully".to_string(),
         level: "info".to_string(),
@@ -32,7 +36,40 @@ pub fn load_config_command<R: Runtime>(_app_handle: AppHandle<R>) -> Result<AppC
     let config = load_config()?;
     
     // Optional: Send log event
-    // let _ = app_handle.emit("log", LogEntry { ... });
+    // let _ = emit_log(&app_handle, LogEntry { ... });
+    
+    Ok(config)
+}
+
+/// Fail if a sync is running, which would keep working with the config it was started with
+fn ensure_no_running_sync() -> Result<()> {
+    if running_sync_count() > 0 {
+        return Err(Error::Sync("Cannot reload configuration while a synchronization is running".to_string()));
+    }
+    Ok(())
+}
+
+/// Reload configuration from disk, e.g. after editing config.json externally
+///
+/// There is no app-wide connection manager to clear: each sync creates its own
+/// pools and API clients, so the next sync uses the reloaded config
+#[tauri::command]
+pub async fn reload_config<R: Runtime>(app_handle: AppHandle<R>) -> Result<AppConfig> {
+    ensure_no_running_sync()?;
+    
+    let config = load_config()?;
+    
+    app_handle.emit("config-reloaded", config.clone())
+        .map_err(|e| Error::System(format!("Failed to emit config reload: {}", e)))?;
+    
+    // Send log event
+    let _ = emit_log(&app_handle, LogEntry {
+        timestamp: Utc::now(),
+        message: format!("Configuration reloaded with {} shops", config.shops.len()),
+        level: "info".to_string(),
+        category: "system".to_string(),
+        shop_id: None,
+    });
     
     Ok(config)
 }
@@ -45,7 +82,7 @@ pub fn add_shop_command<R: Runtime>(app_handle: AppHandle<R>, shop: ShopConfig)
     add_shop(&mut config, shop.clone())?;
     
     // Send log event
-    let _ = app_handle.emit("log", LogEntry {
+    let _ = emit_log(&app_handle, LogEntry {
         timestamp: Utc::now(),
         message: format!("New shop '{}' added successfully", shop.name),
         level: "info".to_string(),
@@ -56,6 +93,30 @@ pub fn add_shop_command<R: Runtime>(app_handle: AppHandle<R>, shop: ShopConfig)
     Ok(config)
 }
 
+/// Duplicate shop
+#[tauri::command]
+pub fn duplicate_shop_command<R: Runtime>(app_handle: AppHandle<R>, source_shop_id: String, new_name: String) -> Result<AppConfig> {
+    let mut config = load_config()?;
+    
+    let shop = duplicate_shop(&mut config, &source_shop_id, &new_name)?;
+    
+    let source_name = config.shops.iter()
+        .find(|s| s.id == source_shop_id)
+        .map(|s| s.name.clone())
+        .unwrap_or_else(|| "Unknown".to_string());
+    
+    // Send log event
+    let _ = emit_log(&app_handle, LogEntry {
+        timestamp: Utc::now(),
+        message: format!("Shop '{}' created as a copy of '{}'", shop.name, source_name),
+        level: "info".to_string(),
+        category: "system".to_string(),
+        shop_id: Some(shop.id),
+    });
+    
+    Ok(config)
+}
+
 /// Update shop
 #[tauri::command]
 pub fn update_shop_command<R: Runtime>(app_handle: AppHandle<R>, shop: ShopConfig) -> Result<AppConfig> {
@@ -64,7 +125,7 @@ pub fn update_shop_command<R: Runtime>(app_handle: AppHandle<R>, shop: ShopConfi
     update_shop(&mut config, shop.clone())?;
     
     // Send log event
-    let _ = app_handle.emit("log", LogEntry {
+    let _ = emit_log(&app_handle, LogEntry {
         timestamp: Utc::now(),
         message: format!("Shop '{}' updated successfully", shop.name),
         level: "info".to_string(),
@@ -75,9 +136,16 @@ pub fn update_shop_command<R: Runtime>(app_handle: AppHandle<R>, shop: ShopConfi
     Ok(config)
 }
 
-/// Remove shop
+/// Remove shop, or only archive it when `archive` is set
 #[tauri::command]
-pub fn remove_shop_command<R: Runtime>(app_handle: AppHandle<R>, shop_id: String) -> Result<AppConfig> {
+pub fn remove_shop_command<R: Runtime>(app_handle: AppHandle<R>, shop_id: String, archive: Option<bool>) -> Result<AppConfig> {
+    // There is no shared connection manager to call `remove_pool` on: pools only
+    // live in the manager of a running sync, so refusing to remove a syncing shop
+    // means no connections outlive the removed shop
+    if is_shop_syncing(&shop_id) {
+        return Err(Error::Sync("Cannot remove a shop while it is being synchronized".to_string()));
+    }
+    
     let mut config = load_config()?;
     
     // Find shop name for logging before removing
@@ -86,12 +154,13 @@ pub fn remove_shop_command<R: Runtime>(app_handle: AppHandle<R>, shop_id: String
         .map(|s| s.name.clone())
         .unwrap_or_else(|| "Unknown".to_string());
     
-    remove_shop(&mut config, &shop_id)?;
+    let archive = archive.unwrap_or(false);
+    remove_shop(&mut config, &shop_id, archive)?;
     
     // Send log event
-    let _ = app_handle.emit("log", LogEntry {
+    let _ = emit_log(&app_handle, LogEntry {
         timestamp: Utc::now(),
-        message: format!("Shop '{}' removed successfully", shop_name),
+        message: format!("Shop '{}' {} successfully", shop_name, if archive { "archived" } else { "removed" }),
         level: "info".to_string(),
         category: "system".to_string(),
         shop_id: None,
@@ -100,6 +169,30 @@ pub fn remove_shop_command<R: Runtime>(app_handle: AppHandle<R>, shop_id: String
     Ok(config)
 }
 
+/// Restore an archived shop
+#[tauri::command]
+pub fn unarchive_shop_command<R: Runtime>(app_handle: AppHandle<R>, shop_id: String) -> Result<AppConfig> {
+    let mut config = load_config()?;
+    
+    unarchive_shop(&mut config, &shop_id)?;
+    
+    let shop_name = config.shops.iter()
+        .find(|s| s.id == shop_id)
+        .map(|s| s.name.clone())
+        .unwrap_or_else(|| "Unknown".to_string());
+    
+    // Send log event
+    let _ = emit_log(&app_handle, LogEntry {
+        timestamp: Utc::now(),
+        message: format!("Shop '{}' restored from archive", shop_name),
+        level: "info".to_string(),
+        category: "system".to_string(),
+        shop_id: Some(shop_id),
+    });
+    
+    Ok(config)
+}
+
 /// Set current shop
 #[tauri::command]
 pub fn set_current_shop_command<R: Runtime>(app_handle: AppHandle<R>, shop_id: String) -> Result<AppConfig> {
@@ -114,7 +207,7 @@ pub fn set_current_shop_command<R: Runtime>(app_handle: AppHandle<R>, shop_id: S
         .unwrap_or_else(|| "Unknown".to_string());
     
     // Send log event
-    let _ = app_handle.emit("log", LogEntry {
+    let _ = emit_log(&app_handle, LogEntry {
         timestamp: Utc::now(),
         message: format!("Active shop changed to '{}'", shop_name),
         level: "info".to_string(),
@@ -123,4 +216,139 @@ pub fn set_current_shop_command<R: Runtime>(app_handle: AppHandle<R>, shop_id: S
     });
     
     Ok(config)
-}
\ No newline at end of file
+}
+
+/// Reorder shops
+#[tauri::command]
+pub fn reorder_shops_command<R: Runtime>(app_handle: AppHandle<R>, shop_ids: Vec<String>) -> Result<AppConfig> {
+    let mut config = load_config()?;
+    
+    reorder_shops(&mut config, &shop_ids)?;
+    
+    // Send log event
+    let _ = emit_log(&app_handle, LogEntry {
+        timestamp: Utc::now(),
+        message: "Shop order updated successfully".to_string(),
+        level: "info".to_string(),
+        category: "system".to_string(),
+        shop_id: None,
+    });
+    
+    Ok(config)
+}
+
+/// Short overview of a shop for listings
+#[derive(Serialize, Clone)]
+pub struct ShopSummary {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub archived: bool,
+}
+
+/// Get a summary of the configured shops, archived ones only if `include_archived` is set
+#[tauri::command]
+pub fn get_shops_summary(include_archived: Option<bool>) -> Result<Vec<ShopSummary>> {
+    let config = load_config()?;
+    let include_archived = include_archived.unwrap_or(false);
+    
+    Ok(config.shops.iter()
+        .filter(|s| include_archived || !s.archived)
+        .map(|s| ShopSummary {
+            id: s.id.clone(),
+            name: s.name.clone(),
+            enabled: s.enabled,
+            archived: s.archived,
+        })
+        .collect())
+}
+
+/// Enable or disable a shop
+#[tauri::command]
+pub fn set_shop_enabled_command<R: Runtime>(app_handle: AppHandle<R>, shop_id: String, enabled: bool) -> Result<AppConfig> {
+    let mut config = load_config()?;
+    
+    set_shop_enabled(&mut config, &shop_id, enabled)?;
+    
+    let shop_name = config.shops.iter()
+        .find(|s| s.id == shop_id)
+        .map(|s| s.name.clone())
+        .unwrap_or_else(|| "Unknown".to_string());
+    
+    // Send log event
+    let _ = emit_log(&app_handle, LogEntry {
+        timestamp: Utc::now(),
+        message: format!("Shop '{}' {}", shop_name, if enabled { "enabled" } else { "disabled" }),
+        level: "info".to_string(),
+        category: "system".to_string(),
+        shop_id: Some(shop_id),
+    });
+    
+    Ok(config)
+}
+
+/// Set the JTL shipping method used for a shop's orders
+#[tauri::command]
+pub fn set_default_shipping_method<R: Runtime>(app_handle: AppHandle<R>, shop_id: String, jtl_id: Option<i32>) -> Result<AppConfig> {
+    let mut config = load_config()?;
+    
+    set_shop_default_shipping_method(&mut config, &shop_id, jtl_id)?;
+    
+    let shop_name = config.shops.iter()
+        .find(|s| s.id == shop_id)
+        .map(|s| s.name.clone())
+        .unwrap_or_else(|| "Unknown".to_string());
+    
+    // Send log event
+    let _ = emit_log(&app_handle, LogEntry {
+        timestamp: Utc::now(),
+        message: match jtl_id {
+            Some(id) => format!("Shop '{}' now uses JTL shipping method {}", shop_name, id),
+            None => format!("Shop '{}' now uses the standard JTL shipping method", shop_name),
+        },
+        level: "info".to_string(),
+        category: "system".to_string(),
+        shop_id: Some(shop_id),
+    });
+    
+    Ok(config)
+}
+
+/// Get global application settings
+#[tauri::command]
+pub fn get_settings_command() -> Result<AppSettings> {
+    let config = load_config()?;
+    Ok(config.settings)
+}
+
+/// Update global application settings
+#[tauri::command]
+pub fn update_settings_command<R: Runtime>(app_handle: AppHandle<R>, settings: AppSettings) -> Result<AppSettings> {
+    let mut config = load_config()?;
+    
+    update_settings(&mut config, settings)?;
+    
+    // Send log event
+    let _ = emit_log(&app_handle, LogEntry {
+        timestamp: Utc::now(),
+        message: "Application settings updated successfully".to_string(),
+        level: "info".to_string(),
+        category: "system".to_string(),
+        shop_id: None,
+    });
+    
+    Ok(config.settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tasks::SyncTaskGuard;
+    
+    #[test]
+    fn reload_is_refused_while_a_sync_is_running() {
+        let _guard = SyncTaskGuard::acquire();
+        
+        assert!(matches!(ensure_no_running_sync(), Err(Error::Sync(_))));
+    }
+}
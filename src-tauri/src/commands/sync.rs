@@ -1,22 +1,55 @@
-use chrono::Utc;
-use log::{info, error};
+use chrono::{DateTime, Utc};
+use log::{info, error, warn};
 use tauri::{AppHandle, Emitter, Runtime};
+use tokio_util::sync::CancellationToken;
 use std::collections::HashMap;
 use std::sync::Mutex;
 use lazy_static::lazy_static;
+use serde_json::Value;
 
+use crate::api::jtl::JtlApiClient;
+use crate::api::webhook::send_completion_webhook;
 use crate::models::LogEntry;
-use crate::config::load_config;
-use crate::sync::{SyncEngine, SyncStats, get_shop_stats, update_shop_sync_hours, get_current_stats};
+use crate::notifications::notify;
+use crate::config::{load_config, save_config, set_shop_sync_hours};
+use crate::config::app::AppConfig;
+use crate::config::shop::ShopConfig;
+use crate::sync::diff::{compare_with_jtl, OrderDiff};
+use crate::sync::failures::{self, FailedOrder};
+use crate::sync::history::{estimate_duration, get_sync_history, EstimateResult};
+use crate::sync::incomplete::{self, IncompleteOrder};
+use crate::sync::polling::{self, PollingJob};
+use crate::sync::processor::{build_jtl_order, external_order_number};
+use crate::sync::reconciliation::{self, ReconciliationIssue};
+use crate::sync::responses::{get_jtl_response, JtlExchange};
+use crate::sync::scheduler::{self, ScheduledJob};
+use crate::sync::{SyncEngine, SyncStats, MultiSyncSummary, AggregateStats, aggregate_stats, get_all_shop_stats, get_shop_stats, update_shop_sync_hours, get_current_stats};
+use crate::db::connection::{ConnectionManager, run_blocking};
+use crate::db::joomla::{count_orders_within_timeframe, get_order_by_id, get_order_items, get_shipping_address};
 use crate::db::models::VirtueMartOrder;
 use crate::error::{Result, Error};
-use crate::utils::abort::{reset_abort_flag, set_abort_flag, should_abort};
+use crate::utils::abort::{reset_abort_flag, set_abort_flag, set_pause_flag, should_abort};
+use crate::utils::emit::emit_log;
+use crate::utils::lock::lock_or_recover;
+use crate::utils::tasks::{SyncTaskGuard, is_shop_syncing, running_sync_count};
 
 // Store synced orders in memory
 lazy_static! {
     static ref SYNCED_ORDERS: Mutex<HashMap<String, Vec<VirtueMartOrder>>> = Mutex::new(HashMap::new());
+    
+    // Last known JTL status per shop: shop_id -> (external_number -> status)
+    static ref ORDER_STATUSES: Mutex<HashMap<String, HashMap<String, String>>> = Mutex::new(HashMap::new());
 }
 
+// Delay between JTL status requests to avoid overwhelming the server
+const STATUS_REQUEST_DELAY_MS: u64 = 150;
+
+// Polls start this far before the previous poll so orders committed during it are not missed
+const POLLING_WATERMARK_OVERLAP_SECS: i64 = 60;
+
+// Number of upcoming runs returned when validating a cron expression
+const CRON_PREVIEW_RUNS: usize = 5;
+
 /// Command to abort the current synchronization
 #[tauri::command]
 pub async fn abort_sync_command<R: Runtime>(app_handle: AppHandle<R>) -> Result<()> {
@@ -26,7 +59,7 @@ pub async fn abort_sync_command<R: Runtime>(app_handle: AppHandle<R>) -> Result<
     set_abort_flag();
     
     // Log the abort
-    let _ = app_handle.emit("log", LogEntry {
+    let _ = emit_log(&app_handle, LogEntry {
         timestamp: Utc::now(),
         message: "Synchronization aborted by user".to_string(),
         level: "warn".to_string(),
@@ -37,6 +70,42 @@ pub async fn abort_sync_command<R: Runtime>(app_handle: AppHandle<R>) -> Result<
     Ok(())
 }
 
+/// Command to pause the current synchronization after the order in progress
+#[tauri::command]
+pub async fn pause_sync_command<R: Runtime>(app_handle: AppHandle<R>) -> Result<()> {
+    info!("Pausing synchronization...");
+    
+    set_pause_flag(true);
+    
+    let _ = emit_log(&app_handle, LogEntry {
+        timestamp: Utc::now(),
+        message: "Synchronization paused by user".to_string(),
+        level: "info".to_string(),
+        category: "sync".to_string(),
+        shop_id: None,
+    });
+    
+    Ok(())
+}
+
+/// Command to resume a paused synchronization
+#[tauri::command]
+pub async fn resume_sync_command<R: Runtime>(app_handle: AppHandle<R>) -> Result<()> {
+    info!("Resuming synchronization...");
+    
+    set_pause_flag(false);
+    
+    let _ = emit_log(&app_handle, LogEntry {
+        timestamp: Utc::now(),
+        message: "Synchronization resumed by user".to_string(),
+        level: "info".to_string(),
+        category: "sync".to_string(),
+        shop_id: None,
+    });
+    
+    Ok(())
+}
+
 /// Start scheduled synchronization
 #[tauri::command]
 pub async fn start_scheduled_sync<R: Runtime>(
@@ -51,8 +120,11 @@ pub async fn start_scheduled_sync<R: Runtime>(
         return Err(Error::ValidationError("No shops selected for synchronization".to_string()));
     }
     
+    // Respect the configured limit of parallel syncs
+    let task_guard = SyncTaskGuard::try_acquire(config.settings.max_concurrent_syncs)?;
+    
     // Log start of scheduled sync
-    let _ = app_handle.emit("log", LogEntry {
+    let _ = emit_log(&app_handle, LogEntry {
         timestamp: Utc::now(),
         message: format!("Starting scheduled synchronization for {} shops, job {}", shop_ids.len(), job_id),
         level: "info".to_string(),
@@ -69,21 +141,24 @@ pub async fn start_scheduled_sync<R: Runtime>(
     let shop_ids_clone = shop_ids.clone();
     
     tauri::async_runtime::spawn(async move {
+        let _task_guard = task_guard;
+        
         // Create sync engine
         let api_key = config_clone.get_api_key(); 
-        let mut engine = SyncEngine::new(&api_key);
+        let mut engine = SyncEngine::from_settings(&api_key, &config_clone.settings);
         
         match engine.sync_multiple_shops(&app_handle_clone, &config_clone, shop_ids_clone).await {
-            Ok(_) => {
+            Ok(summary) => {
                 // Send events
                 let _ = app_handle_clone.emit("multi-sync-complete", job_id.clone());
                 let _ = app_handle_clone.emit("scheduled-sync-completed", (job_id.clone(), shop_ids));
                 
-                // Log success
-                let _ = app_handle_clone.emit("log", LogEntry {
+                // Log the outcome, partial failures included
+                let (outcome, level) = log_multi_sync_outcomes(&app_handle_clone, &summary);
+                let _ = emit_log(&app_handle_clone, LogEntry {
                     timestamp: Utc::now(),
-                    message: format!("Scheduled synchronization completed for job {}", job_id),
-                    level: "info".to_string(),
+                    message: format!("Scheduled synchronization completed for job {}: {}", job_id, outcome),
+                    level: level.to_string(),
                     category: "sync".to_string(),
                     shop_id: None,
                 });
@@ -94,7 +169,7 @@ pub async fn start_scheduled_sync<R: Runtime>(
                 let _ = app_handle_clone.emit("sync-error", error_message.clone());
                 let _ = app_handle_clone.emit("scheduled-sync-error", (job_id.clone(), error_message.clone()));
                 
-                let _ = app_handle_clone.emit("log", LogEntry {
+                let _ = emit_log(&app_handle_clone, LogEntry {
                     timestamp: Utc::now(),
                     message: format!("Scheduled synchronization failed for job {}: {}", job_id, error_message),
                     level: "error".to_string(),
@@ -108,45 +183,74 @@ pub async fn start_scheduled_sync<R: Runtime>(
     Ok(())
 }
 
+/// Log the per-shop outcomes of a multi-shop sync and describe the overall result
+///
+/// The summary itself reaches the UI as the "multi-sync-summary" event.
+/// Returns the log message and level for the run as a whole
+fn log_multi_sync_outcomes<R: Runtime>(app_handle: &AppHandle<R>, summary: &MultiSyncSummary) -> (String, &'static str) {
+    for (shop_id, outcome) in &summary.per_shop {
+        let (message, level) = match outcome {
+            Ok(stats) => (format!("Shop {}: {} synced, {} skipped, {} errors",
+                                  shop_id, stats.synced_orders, stats.skipped_orders, stats.error_orders), "info"),
+            Err(e) => (format!("Shop {} failed: {}", shop_id, e), "error"),
+        };
+        let _ = emit_log(app_handle, LogEntry {
+            timestamp: Utc::now(),
+            message,
+            level: level.to_string(),
+            category: "sync".to_string(),
+            shop_id: Some(shop_id.clone()),
+        });
+    }
+    
+    let failed = summary.failed_shop_ids();
+    if failed.is_empty() {
+        (format!("{} shops synchronized successfully", summary.succeeded_count()), "info")
+    } else {
+        (format!("{} shops synchronized, {} failed: {}", summary.succeeded_count(), failed.len(), failed.join(", ")), "warn")
+    }
+}
+
+/// Insert an order, replacing an existing entry with the same VirtueMart order id
+fn upsert_order(orders: &mut Vec<VirtueMartOrder>, order: VirtueMartOrder) {
+    match orders.iter_mut().find(|o| o.virtuemart_order_id == order.virtuemart_order_id) {
+        Some(existing) => *existing = order,
+        None => orders.push(order),
+    }
+}
+
 /// Store synced orders for a specific shop
 pub fn store_synced_orders(shop_id: &str, orders: Vec<VirtueMartOrder>) {
-    let mut stored_orders = SYNCED_ORDERS.lock().unwrap();
+    let mut stored_orders = lock_or_recover(&SYNCED_ORDERS, "SYNCED_ORDERS");
     
-    // Add shop_id to each order
-    let orders_with_shop_id = orders.into_iter()
-        .map(|mut order| {
-            order.shop_id = Some(shop_id.to_string());
-            order
-        })
-        .collect();
+    // Add shop_id to each order, keeping only the latest entry per order id
+    let mut orders_with_shop_id = Vec::with_capacity(orders.len());
+    for mut order in orders {
+        order.shop_id = Some(shop_id.to_string());
+        upsert_order(&mut orders_with_shop_id, order);
+    }
     
     stored_orders.insert(shop_id.to_string(), orders_with_shop_id);
 }
 
-/// Add a synced order
+/// Add a synced order, replacing a previous entry for the same order
 pub fn add_synced_order<R: Runtime>(app_handle: &AppHandle<R>, shop_id: &str, order: VirtueMartOrder) {
-    let mut stored_orders = SYNCED_ORDERS.lock().unwrap();
-    
-    // Ensure there's an entry for this shop
-    if !stored_orders.contains_key(shop_id) {
-        stored_orders.insert(shop_id.to_string(), Vec::new());
-    }
+    let mut stored_orders = lock_or_recover(&SYNCED_ORDERS, "SYNCED_ORDERS");
     
     // Add shop_id to the order
-    let mut order_with_shop = order.clone();
+    let mut order_with_shop = order;
     order_with_shop.shop_id = Some(shop_id.to_string());
     
     // Add the order to the shop's list
-    if let Some(orders) = stored_orders.get_mut(shop_id) {
-        orders.push(order_with_shop.clone());
-        
-        // Add debug log
-        info!("Order added to SYNCED_ORDERS for shop {}. Current count: {}", shop_id, orders.len());
-        
-        // Send data to frontend
-        app_handle.emit("synced-orders", (shop_id.to_string(), orders.clone()))
-            .map_err(|e| format!("Failed to emit synced orders: {}", e)).ok();
-    }
+    let orders = stored_orders.entry(shop_id.to_string()).or_default();
+    upsert_order(orders, order_with_shop);
+    
+    // Add debug log
+    info!("Order added to SYNCED_ORDERS for shop {}. Current count: {}", shop_id, orders.len());
+    
+    // Send data to frontend
+    app_handle.emit("synced-orders", (shop_id.to_string(), orders.clone()))
+        .map_err(|e| format!("Failed to emit synced orders: {}", e)).ok();
 }
 
 #[tauri::command]
@@ -156,7 +260,7 @@ pub async fn get_synced_orders<R: Runtime>(
 ) -> Result<Vec<VirtueMartOrder>> {
     info!("Getting synced orders for shop: {:?}", shop_id);
     
-    let stored_orders = SYNCED_ORDERS.lock().map_err(|e| Error::System(e.to_string()))?;
+    let stored_orders = lock_or_recover(&SYNCED_ORDERS, "SYNCED_ORDERS");
     
     // If shop_id is provided, return orders for that shop only
     if let Some(id) = shop_id {
@@ -181,6 +285,261 @@ pub async fn get_synced_orders<R: Runtime>(
     }
 }
 
+/// Get the last known JTL status of a synced order
+pub fn get_cached_order_status(shop_id: &str, external_number: &str) -> Option<String> {
+    let statuses = lock_or_recover(&ORDER_STATUSES, "ORDER_STATUSES");
+    statuses.get(shop_id)?.get(external_number).cloned()
+}
+
+/// Query JTL for the current status of every synced order of a shop
+#[tauri::command]
+pub async fn refresh_order_statuses<R: Runtime>(
+    app_handle: AppHandle<R>,
+    shop_id: String
+) -> Result<Vec<(String, String)>> {
+    let orders = lock_or_recover(&SYNCED_ORDERS, "SYNCED_ORDERS")
+        .get(&shop_id)
+        .cloned()
+        .unwrap_or_default();
+    
+    info!("Refreshing JTL status for {} synced orders of shop {}", orders.len(), shop_id);
+    
+    let config = load_config()?;
+    let shop = config.shops.iter().find(|s| s.id == shop_id)
+        .ok_or_else(|| Error::NotFound(format!("No shop found with ID '{}'", shop_id)))?;
+    let client = JtlApiClient::from_settings(&config.get_api_key(), &config.settings);
+    
+    let mut statuses = Vec::with_capacity(orders.len());
+    for order in orders {
+        let external_number = external_order_number(&order, shop);
+        
+        let status = match client.find_order_id(&external_number).await {
+            Ok(Some(order_id)) => match client.get_order_status(&order_id).await {
+                Ok(status) => status,
+                Err(e) => {
+                    error!("Failed to get status for order {}: {}", external_number, e);
+                    "Error".to_string()
+                }
+            },
+            Ok(None) => "Not found".to_string(),
+            Err(e) => {
+                error!("Failed to look up order {}: {}", external_number, e);
+                "Error".to_string()
+            }
+        };
+        
+        statuses.push((external_number, status));
+        
+        // Rate-limit requests to the JTL API
+        tokio::time::sleep(tokio::time::Duration::from_millis(STATUS_REQUEST_DELAY_MS)).await;
+    }
+    
+    // Cache the latest statuses for this shop
+    lock_or_recover(&ORDER_STATUSES, "ORDER_STATUSES")
+        .insert(shop_id.clone(), statuses.iter().cloned().collect());
+    
+    app_handle.emit("order-statuses-updated", (shop_id.clone(), statuses.clone()))
+        .map_err(|e| Error::System(format!("Failed to emit order statuses: {}", e)))?;
+    
+    Ok(statuses)
+}
+
+/// Look up a customer in JTL by customer number, e.g. VM123, without syncing anything
+#[tauri::command]
+pub async fn lookup_jtl_customer(shop_id: String, customer_number: String) -> Result<Option<Value>> {
+    let config = load_config()?;
+    
+    let shop = config.shops.iter().find(|s| s.id == shop_id)
+        .ok_or_else(|| Error::NotFound(format!("No shop found with ID '{}'", shop_id)))?;
+    
+    let customer_number = customer_number.trim();
+    if customer_number.is_empty() {
+        return Err(Error::ValidationError("Customer number cannot be empty".to_string()));
+    }
+    
+    info!("Looking up JTL customer {} for shop '{}'", customer_number, shop.name);
+    
+    let client = JtlApiClient::from_settings(&config.get_api_key(), &config.settings);
+    client.get_customer_by_id(customer_number).await
+}
+
+/// Get the last JTL request and response recorded for an external order number
+#[tauri::command]
+pub async fn get_last_jtl_response(external_order_number: String) -> Result<Option<JtlExchange>> {
+    Ok(get_jtl_response(external_order_number.trim()))
+}
+
+/// Get the orders of a shop that failed to sync, with their errors
+#[tauri::command]
+pub async fn get_failed_orders(shop_id: String) -> Result<Vec<FailedOrder>> {
+    Ok(failures::get_failed_orders(&shop_id))
+}
+
+/// Get the orders of a shop whose line items didn't add up to the order total in its latest run
+#[tauri::command]
+pub async fn get_reconciliation_issues(shop_id: String) -> Result<Vec<ReconciliationIssue>> {
+    Ok(reconciliation::get_reconciliation_issues(&shop_id))
+}
+
+/// Estimate how long syncing `order_count` orders of a shop takes, based on its recent runs
+#[tauri::command]
+pub async fn estimate_sync_time(shop_id: String, order_count: usize) -> Result<EstimateResult> {
+    Ok(estimate_duration(&get_sync_history(&shop_id), order_count))
+}
+
+/// Find JTL orders of a shop without line items, e.g. left behind by a crash mid-order
+///
+/// Looks back `hours`, or the shop's sync timeframe if unset
+#[tauri::command]
+pub async fn find_incomplete_orders(shop_id: String, hours: Option<i32>) -> Result<Vec<IncompleteOrder>> {
+    let config = load_config()?;
+    let shop = config.shops.iter().find(|s| s.id == shop_id)
+        .ok_or_else(|| Error::NotFound(format!("No shop found with ID '{}'", shop_id)))?;
+    
+    let since = Utc::now() - chrono::Duration::hours(shop.resolve_sync_hours(hours) as i64);
+    let client = JtlApiClient::from_settings(&config.get_api_key(), &config.settings);
+    
+    incomplete::find_incomplete_orders(&client, shop, since).await
+}
+
+/// Reject workflow event ids JTL can't know
+fn validate_workflow_event_id(event_id: i32) -> Result<()> {
+    if event_id <= 0 {
+        return Err(Error::ValidationError("Workflow event id must be greater than zero".to_string()));
+    }
+    Ok(())
+}
+
+/// Trigger a JTL workflow event on a shop's order, e.g. to re-send "paid" after a failure
+#[tauri::command]
+pub async fn trigger_workflow_command<R: Runtime>(
+    app_handle: AppHandle<R>,
+    shop_id: String,
+    external_number: String,
+    event_id: i32
+) -> Result<()> {
+    validate_workflow_event_id(event_id)?;
+    
+    let config = load_config()?;
+    let shop = config.shops.iter().find(|s| s.id == shop_id)
+        .ok_or_else(|| Error::NotFound(format!("No shop found with ID '{}'", shop_id)))?;
+    
+    if !incomplete::is_shop_order_number(&external_number, shop) {
+        return Err(Error::ValidationError(format!("Order {} does not belong to shop '{}'", external_number, shop.name)));
+    }
+    
+    let client = JtlApiClient::from_settings(&config.get_api_key(), &config.settings);
+    
+    let order_id = client.find_order_id(&external_number).await?
+        .ok_or_else(|| Error::NotFound(format!("Order {} not found in JTL", external_number)))?;
+    
+    client.trigger_workflow(&order_id, event_id).await?;
+    
+    let _ = emit_log(&app_handle, LogEntry {
+        timestamp: Utc::now(),
+        message: format!("Workflow event {} triggered for order {} of shop '{}'", event_id, external_number, shop.name),
+        level: "info".to_string(),
+        category: "api".to_string(),
+        shop_id: Some(shop_id),
+    });
+    
+    Ok(())
+}
+
+/// Compare a VirtueMart order with the order JTL holds for it
+#[tauri::command]
+pub async fn diff_order(shop_id: String, order_id: i32) -> Result<OrderDiff> {
+    let config = load_config()?;
+    
+    let shop = config.shops.iter().find(|s| s.id == shop_id)
+        .ok_or_else(|| Error::NotFound(format!("No shop found with ID '{}'", shop_id)))?;
+    
+    let (order, items, shipping_address) = run_blocking({
+        let shop = shop.clone();
+        move || {
+            let mut conn_manager = ConnectionManager::new();
+            let pool = conn_manager.get_joomla_pool(&shop)?;
+            
+            let order = get_order_by_id(&pool, &shop, order_id)?
+                .ok_or_else(|| Error::NotFound(format!("Order {} not found in shop '{}'", order_id, shop.name)))?;
+            let items = get_order_items(&pool, &shop, order_id)?;
+            let shipping_address = get_shipping_address(&pool, &shop, order_id)?;
+            Ok((order, items, shipping_address))
+        }
+    }).await?;
+    
+    // The customer ID does not take part in the comparison
+    let (jtl_order, jtl_items) = build_jtl_order(&order, &items, shipping_address.as_ref(), shop, 0);
+    let external_number = jtl_order.ExternalNumber.clone();
+    
+    info!("Comparing order {} of shop '{}' with JTL", external_number, shop.name);
+    
+    let client = JtlApiClient::from_settings(&config.get_api_key(), &config.settings);
+    let Some(jtl_order_id) = client.find_order_id(&external_number).await? else {
+        return Ok(OrderDiff {
+            external_number,
+            jtl_order_id: None,
+            differences: Vec::new(),
+        });
+    };
+    
+    let remote_order = client.get_order(&jtl_order_id).await?;
+    let remote_items = client.get_order_line_items(&jtl_order_id).await?;
+    
+    Ok(OrderDiff {
+        external_number,
+        differences: compare_with_jtl(&jtl_order, &jtl_items, &remote_order, &remote_items),
+        jtl_order_id: Some(jtl_order_id),
+    })
+}
+
+/// Count the orders of each shop with `count`
+///
+/// A shop that can't be counted is logged and left out, so one unreachable
+/// database doesn't hide the counts of the other shops
+fn count_each_shop<'a, I, F>(shops: I, mut count: F) -> Vec<(String, usize)>
+where
+    I: IntoIterator<Item = &'a ShopConfig>,
+    F: FnMut(&ShopConfig) -> Result<usize>,
+{
+    shops.into_iter()
+        .filter_map(|shop| match count(shop) {
+            Ok(pending) => Some((shop.id.clone(), pending)),
+            Err(e) => {
+                warn!("Could not count pending orders for shop '{}': {}", shop.name, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Count the orders each enabled shop would sync for a timeframe
+///
+/// Shops whose database can't be reached are missing from the result
+#[tauri::command]
+pub async fn count_pending_orders(hours: i32) -> Result<Vec<(String, usize)>> {
+    if hours <= 0 {
+        return Err(Error::ValidationError("Sync timeframe must be greater than zero hours".to_string()));
+    }
+    
+    let config = load_config()?;
+    
+    let counts = run_blocking(move || {
+        let mut conn_manager = ConnectionManager::new();
+        
+        let shops = config.shops.iter().filter(|s| s.enabled && !s.archived);
+        Ok(count_each_shop(shops, |shop| {
+            let pool = conn_manager.get_joomla_pool(shop)?;
+            count_orders_within_timeframe(&pool, shop, hours)
+        }))
+    }).await?;
+    
+    info!("Pending orders within {}h: {} across {} shops", 
+          hours, counts.iter().map(|(_, c)| c).sum::<usize>(), counts.len());
+    
+    Ok(counts)
+}
+
 /// Start manual synchronization of multiple shops
 #[tauri::command]
 pub async fn start_multi_sync_command<R: Runtime>(
@@ -194,8 +553,11 @@ pub async fn start_multi_sync_command<R: Runtime>(
     // Load the configuration
     let config = load_config()?;
     
+    // Respect the configured limit of parallel syncs
+    let task_guard = SyncTaskGuard::try_acquire(config.settings.max_concurrent_syncs)?;
+    
     // Log start of synchronization
-    let _ = app_handle.emit("log", LogEntry {
+    let _ = emit_log(&app_handle, LogEntry {
         timestamp: Utc::now(),
         message: format!("Starting manual synchronization for {} shops...", shop_ids.len()),
         level: "info".to_string(),
@@ -213,20 +575,23 @@ pub async fn start_multi_sync_command<R: Runtime>(
     
     // Start background task
     tauri::async_runtime::spawn(async move {
+        let _task_guard = task_guard;
+        
         // Create sync engine
         let api_key = config_clone.get_api_key();
-        let mut engine = SyncEngine::new(&api_key);
+        let mut engine = SyncEngine::from_settings(&api_key, &config_clone.settings);
         
         match engine.sync_multiple_shops(&app_handle_clone, &config_clone, shop_ids_clone).await {
-            Ok(_) => {
+            Ok(summary) => {
                 // Send success event
                 let _ = app_handle_clone.emit("multi-sync-complete", ());
                 
-                // Log success
-                let _ = app_handle_clone.emit("log", LogEntry {
+                // Log the outcome, partial failures included
+                let (outcome, level) = log_multi_sync_outcomes(&app_handle_clone, &summary);
+                let _ = emit_log(&app_handle_clone, LogEntry {
                     timestamp: Utc::now(),
-                    message: "Multi-shop synchronization completed successfully".to_string(),
-                    level: "info".to_string(),
+                    message: format!("Multi-shop synchronization completed: {}", outcome),
+                    level: level.to_string(),
                     category: "sync".to_string(),
                     shop_id: None,
                 });
@@ -235,7 +600,7 @@ pub async fn start_multi_sync_command<R: Runtime>(
                 // Send error event
                 let error_message = e.to_string();
                 let _ = app_handle_clone.emit("sync-error", error_message.clone());
-                let _ = app_handle_clone.emit("log", LogEntry {
+                let _ = emit_log(&app_handle_clone, LogEntry {
                     timestamp: Utc::now(),
                     message: format!("Multi-shop synchronization failed: {}", error_message),
                     level: "error".to_string(),
@@ -249,12 +614,26 @@ pub async fn start_multi_sync_command<R: Runtime>(
     Ok(())
 }
 
+/// Start manual synchronization of all enabled shops
+#[tauri::command]
+pub async fn sync_all_shops<R: Runtime>(app_handle: AppHandle<R>) -> Result<()> {
+    let shop_ids = load_config()?.enabled_shop_ids();
+    
+    if shop_ids.is_empty() {
+        return Err(Error::ValidationError("No enabled shops to synchronize".to_string()));
+    }
+    
+    start_multi_sync_command(app_handle, shop_ids).await
+}
+
 /// Start manual synchronization of a single shop
 #[tauri::command]
 pub async fn start_sync_command<R: Runtime>(
     app_handle: AppHandle<R>, 
     shop_id: Option<String>,
-    hours: Option<i32>
+    hours: Option<i32>,
+    force: Option<bool>,
+    force_update: Option<bool>
 ) -> Result<()> {
     // Load the configuration
     let config = load_config()?;
@@ -271,16 +650,24 @@ pub async fn start_sync_command<R: Runtime>(
         config.get_current_shop()
     };
     
-    // Get the sync hours (default to 24 if not provided)
-    let sync_hours = hours.unwrap_or_else(|| get_shop_stats(&shop.id).sync_hours);
-    
-    // If hours was provided, update the shop's sync_hours
-    if let Some(h) = hours {
-        update_shop_sync_hours(&shop.id, h)?;
+    // Fail fast instead of starting a task that would be rejected
+    if is_shop_syncing(&shop.id) {
+        return Err(Error::Sync(format!("Synchronization for shop '{}' is already running", shop.name)));
     }
     
+    // Argument, then shop config, then 24 hours; stats only record the result
+    let sync_hours = shop.resolve_sync_hours(hours);
+    
+    // Guard against accidentally syncing years of history
+    config.check_sync_hours(sync_hours, force.unwrap_or(false))?;
+    
+    update_shop_sync_hours(&shop.id, sync_hours)?;
+    
+    // Respect the configured limit of parallel syncs
+    let task_guard = SyncTaskGuard::try_acquire(config.settings.max_concurrent_syncs)?;
+    
     // Log start of synchronization
-    let _ = app_handle.emit("log", LogEntry {
+    let _ = emit_log(&app_handle, LogEntry {
         timestamp: Utc::now(),
         message: format!("Starting manual synchronization for shop '{}' with {}h timeframe...", shop.name, sync_hours),
         level: "info".to_string(),
@@ -297,17 +684,27 @@ pub async fn start_sync_command<R: Runtime>(
     
     // Start background task
     tauri::async_runtime::spawn(async move {
+        let _task_guard = task_guard;
+        
         // Create sync engine
         let api_key = config.get_api_key();
-        let mut engine = SyncEngine::new(&api_key);
+        let mut engine = SyncEngine::from_settings(&api_key, &config.settings)
+            .with_force_update(force_update.unwrap_or(false));
         
         match engine.sync_shop(&app_handle_clone, &shop_clone, sync_hours).await {
             Ok(stats) => {
+                send_completion_webhook(&config, &stats);
+                
+                if config.settings.notify_on_complete {
+                    notify("Synchronization completed", &format!("Shop '{}': {} synced, {} errors", 
+                                                                  shop_clone.name, stats.synced_orders, stats.error_orders));
+                }
+                
                 // Send success event
                 let _ = app_handle_clone.emit("sync-complete", stats.clone());
                 
                 // Log success
-                let _ = app_handle_clone.emit("log", LogEntry {
+                let _ = emit_log(&app_handle_clone, LogEntry {
                     timestamp: Utc::now(),
                     message: format!("Synchronization completed for shop '{}': {} synced, {} skipped, {} errors", 
                                    shop_clone.name, stats.synced_orders, stats.skipped_orders, stats.error_orders),
@@ -320,7 +717,117 @@ pub async fn start_sync_command<R: Runtime>(
                 // Send error event
                 let error_message = e.to_string();
                 let _ = app_handle_clone.emit("sync-error", (error_message.clone(), shop_clone.id.clone()));
-                let _ = app_handle_clone.emit("log", LogEntry {
+                
+                if config.settings.notify_on_error {
+                    notify("Synchronization failed", &format!("Shop '{}': {}", shop_clone.name, error_message));
+                }
+                
+                let _ = emit_log(&app_handle_clone, LogEntry {
+                    timestamp: Utc::now(),
+                    message: format!("Synchronization failed for shop '{}': {}", shop_clone.name, error_message),
+                    level: "error".to_string(),
+                    category: "sync".to_string(),
+                    shop_id: Some(shop_clone.id),
+                });
+            }
+        }
+    });
+    
+    // Return immediately (actual stats will be updated via events)
+    Ok(())
+}
+
+/// Start manual synchronization of a single shop from an absolute timestamp
+#[tauri::command]
+pub async fn start_sync_from<R: Runtime>(
+    app_handle: AppHandle<R>,
+    shop_id: String,
+    since: DateTime<Utc>,
+    force: Option<bool>
+) -> Result<()> {
+    if since >= Utc::now() {
+        return Err(Error::ValidationError("Sync start time must be in the past".to_string()));
+    }
+    
+    // Load the configuration
+    let config = load_config()?;
+    
+    // Guard against accidentally syncing years of history, partial hours count as a full hour
+    let requested_hours = ((Utc::now() - since).num_minutes() as f64 / 60.0).ceil() as i32;
+    config.check_sync_hours(requested_hours, force.unwrap_or(false))?;
+    
+    let shop = config.shops.iter()
+        .find(|s| s.id == shop_id)
+        .ok_or_else(|| Error::NotFound(format!("Shop with ID '{}' not found", shop_id)))?
+        .clone();
+    
+    // Fail fast instead of starting a task that would be rejected
+    if is_shop_syncing(&shop.id) {
+        return Err(Error::Sync(format!("Synchronization for shop '{}' is already running", shop.name)));
+    }
+    
+    // Keep the shop's configured timeframe in the stats
+    let sync_hours = shop.resolve_sync_hours(None);
+    
+    // Respect the configured limit of parallel syncs
+    let task_guard = SyncTaskGuard::try_acquire(config.settings.max_concurrent_syncs)?;
+    
+    // Log start of synchronization
+    let _ = emit_log(&app_handle, LogEntry {
+        timestamp: Utc::now(),
+        message: format!("Starting manual synchronization for shop '{}' with orders since {}...", shop.name, since.format("%Y-%m-%d %H:%M:%S")),
+        level: "info".to_string(),
+        category: "sync".to_string(),
+        shop_id: Some(shop.id.clone()),
+    });
+    
+    // Reset abort flag
+    reset_abort_flag();
+    
+    // Create background task
+    let app_handle_clone = app_handle.clone();
+    let shop_clone = shop.clone();
+    
+    // Start background task
+    tauri::async_runtime::spawn(async move {
+        let _task_guard = task_guard;
+        
+        // Create sync engine
+        let api_key = config.get_api_key();
+        let mut engine = SyncEngine::from_settings(&api_key, &config.settings);
+        
+        match engine.sync_shop_since(&app_handle_clone, &shop_clone, since, sync_hours).await {
+            Ok(stats) => {
+                send_completion_webhook(&config, &stats);
+                
+                if config.settings.notify_on_complete {
+                    notify("Synchronization completed", &format!("Shop '{}': {} synced, {} errors", 
+                                                                  shop_clone.name, stats.synced_orders, stats.error_orders));
+                }
+                
+                // Send success event
+                let _ = app_handle_clone.emit("sync-complete", stats.clone());
+                
+                // Log success
+                let _ = emit_log(&app_handle_clone, LogEntry {
+                    timestamp: Utc::now(),
+                    message: format!("Synchronization completed for shop '{}': {} synced, {} skipped, {} errors", 
+                                   shop_clone.name, stats.synced_orders, stats.skipped_orders, stats.error_orders),
+                    level: "info".to_string(),
+                    category: "sync".to_string(),
+                    shop_id: Some(shop_clone.id),
+                });
+            },
+            Err(e) => {
+                // Send error event
+                let error_message = e.to_string();
+                let _ = app_handle_clone.emit("sync-error", (error_message.clone(), shop_clone.id.clone()));
+                
+                if config.settings.notify_on_error {
+                    notify("Synchronization failed", &format!("Shop '{}': {}", shop_clone.name, error_message));
+                }
+                
+                let _ = emit_log(&app_handle_clone, LogEntry {
                     timestamp: Utc::now(),
                     message: format!("Synchronization failed for shop '{}': {}", shop_clone.name, error_message),
                     level: "error".to_string(),
@@ -340,21 +847,26 @@ pub async fn start_sync_command<R: Runtime>(
 pub async fn set_sync_hours<R: Runtime>(
     app_handle: AppHandle<R>,
     shop_id: String,
-    hours: i32
+    hours: i32,
+    force: Option<bool>
 ) -> Result<SyncStats> {
     // Validate the hours parameter
     if hours <= 0 {
         return Err(Error::ValidationError("Sync timeframe must be greater than zero hours".to_string()));
     }
     
-    // Update the shop's sync hours
+    let mut config = load_config()?;
+    config.check_sync_hours(hours, force.unwrap_or(false))?;
+    
+    // Update the shop's sync hours, the config is the source for later syncs
+    set_shop_sync_hours(&mut config, &shop_id, hours)?;
     update_shop_sync_hours(&shop_id, hours)?;
     
     // Get updated stats
     let stats = get_shop_stats(&shop_id);
     
     // Log the change
-    let _ = app_handle.emit("log", LogEntry {
+    let _ = emit_log(&app_handle, LogEntry {
         timestamp: Utc::now(),
         message: format!("Sync timeframe for shop '{}' updated to {} hours", shop_id, hours),
         level: "info".to_string(),
@@ -365,6 +877,48 @@ pub async fn set_sync_hours<R: Runtime>(
     Ok(stats)
 }
 
+/// Set the sync timeframe of every shop in the config, leaving it untouched if the hours are rejected
+fn apply_sync_hours_to_all(config: &mut AppConfig, hours: i32, force: bool) -> Result<()> {
+    if hours <= 0 {
+        return Err(Error::ValidationError("Sync timeframe must be greater than zero hours".to_string()));
+    }
+    config.check_sync_hours(hours, force)?;
+    
+    for shop in config.shops.iter_mut() {
+        shop.sync_hours = Some(hours);
+    }
+    Ok(())
+}
+
+/// Set synchronization timeframe for all configured shops
+#[tauri::command]
+pub async fn set_sync_hours_all<R: Runtime>(
+    app_handle: AppHandle<R>,
+    hours: i32,
+    force: Option<bool>
+) -> Result<Vec<SyncStats>> {
+    let mut config = load_config()?;
+    apply_sync_hours_to_all(&mut config, hours, force.unwrap_or(false))?;
+    save_config(&config)?;
+    
+    let mut updated = Vec::with_capacity(config.shops.len());
+    for shop in &config.shops {
+        update_shop_sync_hours(&shop.id, hours)?;
+        updated.push(get_shop_stats(&shop.id));
+    }
+    
+    // Log the change
+    let _ = emit_log(&app_handle, LogEntry {
+        timestamp: Utc::now(),
+        message: format!("Sync timeframe for all {} shops updated to {} hours", updated.len(), hours),
+        level: "info".to_string(),
+        category: "sync".to_string(),
+        shop_id: None,
+    });
+    
+    Ok(updated)
+}
+
 /// Get current synchronization statistics
 #[tauri::command]
 pub async fn get_sync_stats(shop_id: Option<String>) -> Result<SyncStats> {
@@ -375,9 +929,29 @@ pub async fn get_sync_stats(shop_id: Option<String>) -> Result<SyncStats> {
     }
 }
 
+/// Get the stats of all shops added up, e.g. for a dashboard total
+#[tauri::command]
+pub async fn get_aggregate_stats() -> Result<AggregateStats> {
+    Ok(aggregate_stats(&get_all_shop_stats()))
+}
+
+/// Validate a cron expression without scheduling anything
+///
+/// Returns the next fire times so the UI can show a preview
+#[tauri::command]
+pub fn validate_cron(expr: String) -> Result<Vec<DateTime<Utc>>> {
+    if expr.trim().is_empty() {
+        return Err(Error::ValidationError("Cron expression cannot be empty".to_string()));
+    }
+    
+    scheduler::upcoming_runs(&expr, CRON_PREVIEW_RUNS)
+}
+
 /// Schedule synchronization
+///
+/// Returns the id of the registered job
 #[tauri::command]
-pub async fn schedule_sync(shop_ids: Vec<String>, cron_expression: String) -> Result<()> {
+pub async fn schedule_sync(shop_ids: Vec<String>, cron_expression: String) -> Result<String> {
     // Validate inputs
     if shop_ids.is_empty() {
         return Err(Error::ValidationError("No shops selected for scheduling".to_string()));
@@ -387,23 +961,243 @@ pub async fn schedule_sync(shop_ids: Vec<String>, cron_expression: String) -> Re
         return Err(Error::ValidationError("Invalid cron expression".to_string()));
     }
     
-    // In a real implementation, this would:
-    // 1. Validate the cron expression
-    // 2. Set up a persistent scheduler
-    // 3. Store the schedule in configuration
-    info!("Scheduling sync for {} shops with cron: {}", shop_ids.len(), cron_expression);
+    let job = scheduler::add_job(shop_ids, &cron_expression);
+    info!("Scheduled job {} for {} shops with cron: {}", job.job_id, job.shop_ids.len(), cron_expression);
     
-    Ok(())
+    Ok(job.job_id)
+}
+
+/// List all registered scheduled jobs
+#[tauri::command]
+pub async fn list_scheduled_jobs() -> Result<Vec<ScheduledJob>> {
+    Ok(scheduler::list_jobs())
 }
 
 /// Cancel scheduled synchronization jobs
+///
+/// Cancels a single job if `job_id` is given, otherwise all jobs of `shop_id`, otherwise all jobs
 #[tauri::command]
-pub async fn cancel_scheduled_sync(shop_id: Option<String>) -> Result<()> {
-    // In a real implementation, cancel scheduled jobs
-    if let Some(id) = shop_id {
-        info!("Canceled scheduled sync jobs for shop {}", id);
+pub async fn cancel_scheduled_sync(job_id: Option<String>, shop_id: Option<String>) -> Result<()> {
+    if let Some(id) = job_id {
+        scheduler::cancel_job(&id)?;
+        info!("Canceled scheduled sync job {}", id);
+    } else if let Some(id) = shop_id {
+        let count = scheduler::cancel_jobs_for_shop(&id);
+        info!("Canceled {} scheduled sync jobs for shop {}", count, id);
     } else {
-        info!("Canceled all scheduled sync jobs");
+        let count = scheduler::cancel_all_jobs();
+        info!("Canceled all {} scheduled sync jobs", count);
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Start polling shops for new orders, syncing them every `interval_secs`
+///
+/// Each poll only fetches orders since the previous successful poll of a shop,
+/// the first one uses the shop's sync timeframe.
+#[tauri::command]
+pub async fn start_polling_command<R: Runtime>(
+    app_handle: AppHandle<R>,
+    shop_ids: Vec<String>,
+    interval_secs: u64
+) -> Result<()> {
+    if shop_ids.is_empty() {
+        return Err(Error::ValidationError("No shops selected for polling".to_string()));
+    }
+    
+    let job = polling::start_polling(shop_ids.clone(), interval_secs)?;
+    let token = job.token();
+    
+    let _ = app_handle.emit("polling-started", job);
+    let _ = emit_log(&app_handle, LogEntry {
+        timestamp: Utc::now(),
+        message: format!("Polling {} shops for new orders every {} seconds", shop_ids.len(), interval_secs),
+        level: "info".to_string(),
+        category: "sync".to_string(),
+        shop_id: None,
+    });
+    
+    let app_handle_clone = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut watermarks: HashMap<String, DateTime<Utc>> = HashMap::new();
+        
+        while !token.is_cancelled() {
+            poll_shops(&app_handle_clone, &shop_ids, &mut watermarks, &token).await;
+            
+            tokio::select! {
+                _ = token.cancelled() => break,
+                _ = tokio::time::sleep(tokio::time::Duration::from_secs(interval_secs)) => {},
+            }
+        }
+        
+        info!("Polling loop for {} shops finished", shop_ids.len());
+    });
+    
+    Ok(())
+}
+
+/// Stop polling; a sync already running finishes its current shop first
+#[tauri::command]
+pub async fn stop_polling_command<R: Runtime>(app_handle: AppHandle<R>) -> Result<()> {
+    let job = polling::stop_polling()
+        .ok_or_else(|| Error::NotFound("Polling is not running".to_string()))?;
+    
+    let _ = app_handle.emit("polling-stopped", job.clone());
+    let _ = emit_log(&app_handle, LogEntry {
+        timestamp: Utc::now(),
+        message: format!("Polling for {} shops stopped", job.shop_ids.len()),
+        level: "info".to_string(),
+        category: "sync".to_string(),
+        shop_id: None,
+    });
+    
+    Ok(())
+}
+
+/// Get the active polling loop, if any
+#[tauri::command]
+pub async fn get_polling_status() -> Result<Option<PollingJob>> {
+    Ok(polling::polling_job())
+}
+
+/// Sync the polled shops once, advancing each shop's watermark after a successful run
+async fn poll_shops<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    shop_ids: &[String],
+    watermarks: &mut HashMap<String, DateTime<Utc>>,
+    token: &CancellationToken
+) {
+    // Reloaded every poll so removed or disabled shops drop out
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            error!("Polling could not load the configuration: {}", e);
+            return;
+        }
+    };
+    
+    // A leftover abort would cancel every poll, but an abort of another running sync must stand
+    if running_sync_count() == 0 {
+        reset_abort_flag();
+    }
+    
+    // Skip this round while manual syncs use up the configured limit
+    let _task_guard = match SyncTaskGuard::try_acquire(config.settings.max_concurrent_syncs) {
+        Ok(guard) => guard,
+        Err(e) => {
+            info!("Skipping poll: {}", e);
+            return;
+        }
+    };
+    let mut engine = SyncEngine::from_settings(&config.get_api_key(), &config.settings);
+    
+    for shop in config.shops.iter().filter(|s| s.enabled && !s.archived && shop_ids.contains(&s.id)) {
+        if token.is_cancelled() {
+            break;
+        }
+        
+        // Never overlap with a manual or scheduled sync of the same shop
+        if is_shop_syncing(&shop.id) {
+            info!("Shop '{}' is already syncing, skipping this poll", shop.name);
+            continue;
+        }
+        
+        let sync_hours = shop.resolve_sync_hours(None);
+        let since = watermarks.get(&shop.id).copied()
+            .unwrap_or_else(|| Utc::now() - chrono::Duration::hours(sync_hours as i64));
+        let poll_started = Utc::now();
+        
+        match engine.sync_shop_since(app_handle, shop, since, sync_hours).await {
+            Ok(stats) if !stats.aborted => {
+                watermarks.insert(shop.id.clone(), poll_started - chrono::Duration::seconds(POLLING_WATERMARK_OVERLAP_SECS));
+                
+                if stats.synced_orders > 0 {
+                    let _ = app_handle.emit("sync-complete", stats.clone());
+                }
+            },
+            // The watermark stays put, so the next poll covers the same orders again
+            Ok(_) => info!("Poll of shop '{}' was aborted", shop.name),
+            Err(e) => {
+                let _ = emit_log(app_handle, LogEntry {
+                    timestamp: Utc::now(),
+                    message: format!("Polling synchronization failed for shop '{}': {}", shop.name, e),
+                    level: "error".to_string(),
+                    category: "sync".to_string(),
+                    shop_id: Some(shop.id.clone()),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn order(id: i32, number: &str) -> VirtueMartOrder {
+        VirtueMartOrder {
+            virtuemart_order_id: id,
+            order_number: number.to_string(),
+            ..Default::default()
+        }
+    }
+    
+    #[test]
+    fn upsert_order_replaces_existing_and_appends_new() {
+        let mut orders = vec![order(1, "A"), order(2, "B")];
+        
+        upsert_order(&mut orders, order(1, "A-updated"));
+        upsert_order(&mut orders, order(3, "C"));
+        
+        let numbers: Vec<&str> = orders.iter().map(|o| o.order_number.as_str()).collect();
+        assert_eq!(numbers, vec!["A-updated", "B", "C"]);
+    }
+    
+    #[test]
+    fn validate_cron_previews_runs_and_rejects_empty_input() {
+        assert_eq!(validate_cron("0 * * * *".to_string()).unwrap().len(), CRON_PREVIEW_RUNS);
+        assert!(matches!(validate_cron("  ".to_string()), Err(Error::ValidationError(_))));
+    }
+    
+    #[test]
+    fn counting_continues_past_a_failing_shop() {
+        let shops: Vec<ShopConfig> = ["shop1", "shop2", "shop3"].iter().map(|id| {
+            let mut shop = ShopConfig::new("Test Shop");
+            shop.id = id.to_string();
+            shop
+        }).collect();
+        
+        let counts = count_each_shop(&shops, |shop| match shop.id.as_str() {
+            "shop2" => Err(Error::Database("Connection refused".to_string())),
+            _ => Ok(3),
+        });
+        
+        assert_eq!(counts, vec![("shop1".to_string(), 3), ("shop3".to_string(), 3)]);
+    }
+    
+    #[test]
+    fn workflow_event_ids_must_be_positive() {
+        assert!(matches!(validate_workflow_event_id(0), Err(Error::ValidationError(_))));
+        assert!(matches!(validate_workflow_event_id(-15), Err(Error::ValidationError(_))));
+        assert!(validate_workflow_event_id(15).is_ok());
+    }
+    
+    #[test]
+    fn sync_hours_are_set_on_every_shop() {
+        let mut config = AppConfig::default();
+        let mut second = ShopConfig::new("Second Shop");
+        second.id = "shop2".to_string();
+        second.sync_hours = Some(12);
+        config.shops.push(second);
+        
+        apply_sync_hours_to_all(&mut config, 48, false).unwrap();
+        assert!(config.shops.iter().all(|shop| shop.sync_hours == Some(48)));
+        
+        assert!(apply_sync_hours_to_all(&mut config, 0, false).is_err());
+        assert!(apply_sync_hours_to_all(&mut config, 10_000, false).is_err());
+        assert!(config.shops.iter().all(|shop| shop.sync_hours == Some(48)));
+        
+        apply_sync_hours_to_all(&mut config, 10_000, true).unwrap();
+        assert!(config.shops.iter().all(|shop| shop.sync_hours == Some(10_000)));
+    }
+}
@@ -1,12 +1,253 @@
-use std::time::SystemTime;
+use mysql::Pool;
+use serde::Serialize;
+use sysinfo::System;
+
+use crate::api::jtl::JtlApiClient;
+use crate::config::load_config;
+use crate::config::shop::ShopConfig;
+use crate::db::connection::{ConnectionManager, run_blocking};
+use crate::db::joomla::{get_latest_order, get_order_items, get_shipping_address};
+use crate::db::models::VirtueMartOrder;
+use crate::error::{Result, Error};
+use crate::sync::processor::{customer_number, external_order_number};
+use crate::utils::mapping::payment_method_name;
+
+/// System information for the frontend
+#[derive(Serialize, Clone)]
+pub struct SystemInfo {
+    pub platform: String,
+    pub arch: String,
+    pub app_version: String,
+    pub cpu_count: usize,
+    pub total_memory: u64,   // Bytes
+    pub used_memory: u64,    // Bytes
+    pub process_memory: u64, // Resident set size of this app in bytes
+    pub uptime: u64,         // System uptime in seconds
+}
 
 /// Get system information
 #[tauri::command]
-pub fn get_system_info() -> serde_json::Value {
-    serde_json::json!({
-        "platform": std::env::consts::OS,
-        "arch": std::env::consts::ARCH,
-        "memory": "N/A", // Would require additional crate
-        "uptime": SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs(),
-    })
-}
\ No newline at end of file
+pub fn get_system_info() -> SystemInfo {
+    let sys = System::new_all();
+    
+    // Resident memory of our own process, if it can be determined
+    let process_memory = sysinfo::get_current_pid()
+        .ok()
+        .and_then(|pid| sys.process(pid))
+        .map(|process| process.memory())
+        .unwrap_or(0);
+    
+    SystemInfo {
+        platform: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        cpu_count: sys.cpus().len(),
+        total_memory: sys.total_memory(),
+        used_memory: sys.used_memory(),
+        process_memory,
+        uptime: System::uptime(),
+    }
+}
+
+/// Build and dependency versions for support triage
+#[derive(Serialize, Clone)]
+pub struct VersionInfo {
+    pub app_version: String,
+    pub git_hash: String, // "unknown" if built outside a git checkout
+    pub target: String,   // Rust target triple
+    pub tauri_version: String,
+}
+
+/// Get version information of this build
+#[tauri::command]
+pub fn get_version_info() -> VersionInfo {
+    VersionInfo {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        git_hash: env!("JTLSYNC_GIT_HASH").to_string(),
+        target: env!("JTLSYNC_TARGET").to_string(),
+        tauri_version: tauri::VERSION.to_string(),
+    }
+}
+
+/// Outcome of a single smoke test stage
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SmokeTestStatus {
+    Passed,
+    Failed,
+    Skipped, // Not run because an earlier stage failed
+}
+
+/// Result of a single smoke test stage
+#[derive(Serialize, Clone)]
+pub struct SmokeTestStep {
+    pub name: String,
+    pub status: SmokeTestStatus,
+    pub message: String,
+}
+
+/// Step-by-step report of an end-to-end smoke test
+#[derive(Serialize, Clone)]
+pub struct SmokeTestReport {
+    pub shop_id: String,
+    pub passed: bool,
+    pub steps: Vec<SmokeTestStep>,
+}
+
+impl SmokeTestReport {
+    fn push(&mut self, name: &str, status: SmokeTestStatus, message: String) {
+        if status != SmokeTestStatus::Passed {
+            self.passed = false;
+        }
+        self.steps.push(SmokeTestStep { name: name.to_string(), status, message });
+    }
+    
+    fn skip(&mut self, name: &str) {
+        self.push(name, SmokeTestStatus::Skipped, "Skipped because an earlier step failed".to_string());
+    }
+}
+
+/// Test the whole pipeline for a shop without writing anything to JTL
+///
+/// Checks the Joomla connection, loads the most recent order, pings the JTL API
+/// and runs the read-only part of order processing for that order.
+#[tauri::command]
+pub async fn smoke_test(shop_id: String) -> Result<SmokeTestReport> {
+    let config = load_config()?;
+    let shop = config.shops.iter().find(|s| s.id == shop_id)
+        .ok_or_else(|| Error::NotFound(format!("No shop found with ID '{}'", shop_id)))?;
+    
+    let mut report = SmokeTestReport {
+        shop_id: shop.id.clone(),
+        passed: true,
+        steps: Vec::new(),
+    };
+    
+    // 1. Joomla database connection
+    let pool = match run_blocking({
+        let shop = shop.clone();
+        move || ConnectionManager::new().get_joomla_pool_checked(&shop)
+    }).await {
+        Ok(pool) => {
+            report.push("joomla_connection", SmokeTestStatus::Passed,
+                        format!("Connected to {}/{}", shop.joomla.host, shop.joomla.database));
+            Some(pool)
+        },
+        Err(e) => {
+            report.push("joomla_connection", SmokeTestStatus::Failed, e.to_string());
+            None
+        }
+    };
+    
+    // 2. Most recent order
+    let order: Option<VirtueMartOrder> = match &pool {
+        Some(pool) => match run_blocking({
+            let (pool, shop) = (pool.clone(), shop.clone());
+            move || get_latest_order(&pool, &shop)
+        }).await {
+            Ok(Some(order)) => {
+                report.push("latest_order", SmokeTestStatus::Passed,
+                            format!("Found order {} from {}", order.order_number, order.created_on));
+                Some(order)
+            },
+            Ok(None) => {
+                report.push("latest_order", SmokeTestStatus::Failed, "The shop has no orders".to_string());
+                None
+            },
+            Err(e) => {
+                report.push("latest_order", SmokeTestStatus::Failed, e.to_string());
+                None
+            }
+        },
+        None => {
+            report.skip("latest_order");
+            None
+        }
+    };
+    
+    // 3. JTL API
+    let client = JtlApiClient::from_settings(&config.get_api_key(), &config.settings);
+    let jtl_reachable = match client.get_api_version().await {
+        Ok(version) => {
+            report.push("jtl_api", SmokeTestStatus::Passed, format!("JTL API version {}", version));
+            true
+        },
+        Err(e) => {
+            report.push("jtl_api", SmokeTestStatus::Failed, e.to_string());
+            false
+        }
+    };
+    
+    // 4. Dry run of order processing (read-only)
+    match (&pool, &order, jtl_reachable) {
+        (Some(pool), Some(order), true) => {
+            match dry_run_order(&client, pool, shop, order).await {
+                Ok(message) => report.push("dry_run", SmokeTestStatus::Passed, message),
+                Err(e) => report.push("dry_run", SmokeTestStatus::Failed, e.to_string()),
+            }
+        },
+        _ => report.skip("dry_run"),
+    }
+    
+    Ok(report)
+}
+
+/// Run the read-only lookups `process_order` would perform for an order
+async fn dry_run_order(
+    client: &JtlApiClient,
+    pool: &Pool,
+    shop: &ShopConfig,
+    order: &VirtueMartOrder
+) -> Result<String> {
+    let order_id = order.virtuemart_order_id;
+    let (items, shipping_address) = run_blocking({
+        let (pool, shop) = (pool.clone(), shop.clone());
+        move || Ok((get_order_items(&pool, &shop, order_id)?, get_shipping_address(&pool, &shop, order_id)?))
+    }).await?;
+    
+    let customer_number = customer_number(order, shop);
+    let customer = client.get_customer_by_id(&customer_number).await?;
+    let existing_order = client.find_order_id(&external_order_number(order, shop)).await?;
+    
+    Ok(format!(
+        "Order {}: {} line items, {} shipping address, customer {} {}, order {}",
+        order.order_number,
+        items.len(),
+        if shipping_address.is_some() { "separate" } else { "no separate" },
+        customer_number,
+        if customer.is_some() { "exists in JTL" } else { "would be created" },
+        if existing_order.is_some() { "already exists in JTL" } else { "would be created" }
+    ))
+}
+
+/// Get the display name of a JTL payment method id
+#[tauri::command]
+pub fn get_payment_method_name(id: i32) -> Result<String> {
+    Ok(payment_method_name(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn version_info_is_filled_in() {
+        let info = get_version_info();
+        
+        assert_eq!(info.app_version, env!("CARGO_PKG_VERSION"));
+        assert!(!info.git_hash.is_empty());
+        assert!(!info.target.is_empty());
+        assert!(!info.tauri_version.is_empty());
+    }
+    
+    #[test]
+    fn system_info_has_plausible_values() {
+        let info = get_system_info();
+        
+        assert_eq!(info.platform, std::env::consts::OS);
+        assert_eq!(info.arch, std::env::consts::ARCH);
+        assert!(info.cpu_count > 0);
+        assert!(info.total_memory > 0);
+        assert!(info.used_memory <= info.total_memory);
+        assert!(info.process_memory <= info.total_memory);
+    }
+}
@@ -1,77 +1,70 @@
 use serde::{Serialize, Deserialize};
 
+use crate::config::settings::AppSettings;
 use crate::config::shop::ShopConfig;
 use crate::error::{Result, Error};
-use crate::db::models::{DatabaseConfig, TablesConfig};
+
+// Default safety cap for the sync timeframe (30 days)
+pub const DEFAULT_MAX_SYNC_HOURS: i32 = 720;
+
+/// The shop a fresh configuration starts with
+fn default_shop() -> ShopConfig {
+    let mut shop = ShopConfig::new("Default Shop");
+    shop.id = "shop1".to_string();
+    shop
+}
+
+fn default_max_sync_hours() -> Option<i32> {
+    Some(DEFAULT_MAX_SYNC_HOURS)
+}
 
 /// Application configuration
 #[derive(Serialize, Deserialize, Clone)]
 pub struct AppConfig {
     pub shops: Vec<ShopConfig>,
     pub current_shop_index: usize,
+    #[serde(default)]
     pub logFile: String,
+    #[serde(default)]
     pub jtlApiPath: String, // For backward compatibility
+    #[serde(default)]
+    pub settings: AppSettings,
+    #[serde(default)]
+    pub completion_webhook_url: Option<String>, // POSTed with the final SyncStats after each shop sync
+    #[serde(default)]
+    pub webhook_secret: Option<String>,         // Used to sign webhook payloads when set
+    #[serde(default = "default_max_sync_hours")]
+    pub max_sync_hours: Option<i32>,            // Safety cap for the sync timeframe; None disables it
 }
 
 impl AppConfig {
     /// Create a new default configuration
     pub fn default() -> Self {
-        // Create a default shop
-        let default_shop = ShopConfig {
-            id: "shop1".to_string(),
-            name: "Default Shop".to_string(),
-            joomla: DatabaseConfig {
-                host: "localhost".to_string(),
-                user: "root".to_string(),
-                password: "".to_string(),
-                database: "joomla".to_string(),
-            },
-            jtl: DatabaseConfig {
-                host: "localhost".to_string(),
-                user: "root".to_string(),
-                password: "".to_string(),
-                database: "jtl".to_string(),
-            },
-            tables: TablesConfig {
-                orders: "jos_virtuemart_orders".to_string(),
-                orderItems: "jos_virtuemart_order_items".to_string(),
-                customers: "jos_virtuemart_order_userinfos".to_string(),
-            },
-        };
-        
         AppConfig {
-            shops: vec![default_shop],
+            shops: vec![default_shop()],
             current_shop_index: 0,
             logFile: "sync_log.txt".to_string(),
             jtlApiPath: "C:\\Program Files (x86)\\JTL-Software\\JTL.Wawi.Rest.exe".to_string(),
+            settings: AppSettings::default(),
+            completion_webhook_url: None,
+            webhook_secret: None,
+            max_sync_hours: default_max_sync_hours(),
         }
     }
     
+    /// IDs of all enabled, non-archived shops, in configured order
+    pub fn enabled_shop_ids(&self) -> Vec<String> {
+        self.shops.iter()
+            .filter(|s| s.enabled && !s.archived)
+            .map(|s| s.id.clone())
+            .collect()
+    }
+    
     /// Get the current shop configuration
     pub fn get_current_shop(&self) -> ShopConfig {
         if self.shops.is_empty() {
             // Return a default shop if none exists
-            return ShopConfig {
-                id: "shop1".to_string(),
-                name: "Default Shop".to_string(),
-                joomla: DatabaseConfig {
-                    host: "localhost".to_string(),
-                    user: "root".to_string(),
-                    password: "".to_string(),
-                    database: "joomla".to_string(),
-                },
-                jtl: DatabaseConfig {
-                    host: "localhost".to_string(),
-                    user: "root".to_string(),
-                    password: "".to_string(),
-                    database: "jtl".to_string(),
-                },
-                tables: TablesConfig {
-                    orders: "jos_virtuemart_orders".to_string(),
-                    orderItems: "jos_virtuemart_order_items".to_string(),
-                    customers: "jos_virtuemart_order_userinfos".to_string(),
-                },
-            };
+            return default_shop();
         }
         
         let index = if self.current_shop_index < self.shops.len() {
@@ -90,6 +83,23 @@ impl AppConfig {
         "4fef6933-ae20-4cbc-bd97-a5cd584f244e".to_string()
     }
     
+    /// Check a requested sync timeframe against the configured cap
+    ///
+    /// `force` bypasses the cap for deliberate large backfills
+    pub fn check_sync_hours(&self, hours: i32, force: bool) -> Result<()> {
+        if force {
+            return Ok(());
+        }
+        
+        match self.max_sync_hours {
+            Some(max) if hours > max => Err(Error::ValidationError(format!(
+                "Sync timeframe of {} hours exceeds the maximum of {} hours; use force to sync anyway",
+                hours, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+    
     /// Validate the configuration
     pub fn validate(&self) -> Result<()> {
         if self.shops.is_empty() {
@@ -100,6 +110,8 @@ impl AppConfig {
             return Err(Error::ValidationError("Invalid current shop index".to_string()));
         }
         
+        self.settings.validate()?;
+        
         // Validate each shop
         for shop in &self.shops {
             shop.validate()?;
@@ -107,4 +119,47 @@ impl AppConfig {
         
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn check_sync_hours_enforces_cap_unless_forced() {
+        let mut config = AppConfig::default();
+        config.max_sync_hours = Some(48);
+        
+        assert!(config.check_sync_hours(48, false).is_ok());
+        assert!(matches!(config.check_sync_hours(49, false), Err(Error::ValidationError(_))));
+        assert!(config.check_sync_hours(10_000, true).is_ok());
+        
+        config.max_sync_hours = None;
+        assert!(config.check_sync_hours(10_000, false).is_ok());
+    }
+    
+    #[test]
+    fn default_shop_is_used_without_configured_shops() {
+        let mut config = AppConfig::default();
+        assert_eq!(config.shops[0].id, "shop1");
+        
+        config.shops.clear();
+        let shop = config.get_current_shop();
+        assert_eq!(shop.id, "shop1");
+        assert_eq!(shop.name, "Default Shop");
+    }
+    
+    #[test]
+    fn enabled_shop_ids_skip_disabled_and_archived_shops() {
+        let mut config = AppConfig::default();
+        for (id, enabled, archived) in [("shop2", false, false), ("shop3", true, true), ("shop4", true, false)] {
+            let mut shop = ShopConfig::new(id);
+            shop.id = id.to_string();
+            shop.enabled = enabled;
+            shop.archived = archived;
+            config.shops.push(shop);
+        }
+        
+        assert_eq!(config.enabled_shop_ids(), vec!["shop1".to_string(), "shop4".to_string()]);
+    }
+}
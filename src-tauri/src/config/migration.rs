@@ -0,0 +1,138 @@
+use log::info;
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+/// Upgrade a raw config document to the current structure
+///
+/// Handles the legacy single-shop layout (top-level `joomla`/`jtl`/`tables`),
+/// integer or missing shop ids and a missing or invalid shop index. Fields
+/// added later are filled by their serde defaults when the result is parsed.
+/// Returns true if anything was changed.
+pub fn migrate_config(config: &mut Value) -> bool {
+    let Some(root) = config.as_object_mut() else {
+        return false;
+    };
+    
+    let mut changed = false;
+    
+    if !root.contains_key("shops") && root.contains_key("joomla") {
+        info!("Migrating legacy single-shop configuration");
+        
+        let mut shop = Map::new();
+        shop.insert("name".to_string(), json!("Default Shop"));
+        for key in ["joomla", "jtl", "tables"] {
+            if let Some(value) = root.remove(key) {
+                shop.insert(key.to_string(), value);
+            }
+        }
+        
+        root.insert("shops".to_string(), Value::Array(vec![Value::Object(shop)]));
+        changed = true;
+    }
+    
+    let shop_count = match root.get_mut("shops").and_then(Value::as_array_mut) {
+        Some(shops) => {
+            for (index, shop) in shops.iter_mut().enumerate() {
+                if let Some(shop) = shop.as_object_mut() {
+                    changed |= migrate_shop(shop, index);
+                }
+            }
+            shops.len()
+        },
+        None => 0,
+    };
+    
+    let index_valid = root.get("current_shop_index")
+        .and_then(Value::as_u64)
+        .is_some_and(|index| (index as usize) < shop_count.max(1));
+    if !index_valid {
+        root.insert("current_shop_index".to_string(), json!(0));
+        changed = true;
+    }
+    
+    changed
+}
+
+/// Upgrade a single shop entry
+fn migrate_shop(shop: &mut Map<String, Value>, index: usize) -> bool {
+    let mut changed = false;
+    
+    // Early versions used integer ids; those are replaced by UUIDs
+    let id_valid = shop.get("id")
+        .and_then(Value::as_str)
+        .is_some_and(|id| !id.trim().is_empty());
+    if !id_valid {
+        let id = Uuid::new_v4().to_string();
+        info!("Assigning new ID {} to shop #{} (was {:?})", id, index + 1, shop.get("id"));
+        shop.insert("id".to_string(), json!(id));
+        changed = true;
+    }
+    
+    if !shop.get("name").is_some_and(Value::is_string) {
+        shop.insert("name".to_string(), json!(format!("Shop {}", index + 1)));
+        changed = true;
+    }
+    
+    // Shops synced before shop-tagged order numbers keep their numbers, otherwise every order would be created again
+    if !shop.contains_key("legacy_order_numbers") {
+        shop.insert("legacy_order_numbers".to_string(), json!(true));
+        changed = true;
+    }
+    
+    if !shop.contains_key("tables") {
+        shop.insert("tables".to_string(), json!({
+            "orders": "jos_virtuemart_orders",
+            "orderItems": "jos_virtuemart_order_items",
+            "customers": "jos_virtuemart_order_userinfos",
+        }));
+        changed = true;
+    }
+    
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn legacy_config() -> Value {
+        json!({
+            "joomla": {"host": "localhost", "user": "root", "password": "", "database": "joomla"},
+            "jtl": {"host": "localhost", "user": "root", "password": "", "database": "jtl"},
+            "logFile": "sync.log",
+        })
+    }
+    
+    #[test]
+    fn migrates_legacy_single_shop_config() {
+        let mut config = legacy_config();
+        
+        assert!(migrate_config(&mut config));
+        
+        let shops = config["shops"].as_array().unwrap();
+        assert_eq!(shops.len(), 1);
+        
+        let shop = &shops[0];
+        assert!(shop["id"].as_str().is_some_and(|id| Uuid::parse_str(id).is_ok()));
+        assert_eq!(shop["name"], "Default Shop");
+        assert_eq!(shop["legacy_order_numbers"], true);
+        assert_eq!(shop["joomla"]["database"], "joomla");
+        assert_eq!(shop["tables"]["orders"], "jos_virtuemart_orders");
+        assert_eq!(shop["tables"]["orderItems"], "jos_virtuemart_order_items");
+        assert_eq!(shop["tables"]["customers"], "jos_virtuemart_order_userinfos");
+        
+        assert!(config.get("joomla").is_none());
+        assert_eq!(config["current_shop_index"], 0);
+        assert_eq!(config["logFile"], "sync.log");
+    }
+    
+    #[test]
+    fn migration_is_idempotent() {
+        let mut config = legacy_config();
+        migrate_config(&mut config);
+        let migrated = config.clone();
+        
+        assert!(!migrate_config(&mut config));
+        assert_eq!(config, migrated);
+    }
+}
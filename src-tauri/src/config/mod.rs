@@ -1,25 +1,73 @@
 pub mod app;
+pub mod migration;
+pub mod settings;
 pub mod shop;
 
+use lazy_static::lazy_static;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Mutex;
+use uuid::Uuid;
 
 use crate::error::{Result, Error};
 use crate::config::app::AppConfig;
+use crate::config::migration::migrate_config;
+use crate::config::settings::AppSettings;
 use crate::config::shop::ShopConfig;
+use crate::utils::emit::set_ui_log_level;
+use crate::utils::lock::lock_or_recover;
 
-/// Determine configuration path
-pub fn get_config_path() -> PathBuf {
+// Environment variable that overrides the configuration file path
+pub const CONFIG_PATH_ENV: &str = "JTLSYNC_CONFIG_PATH";
+
+lazy_static! {
+    // App data directory resolved by Tauri on startup
+    static ref APP_DATA_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+/// Set the app data directory used for the configuration file
+pub fn set_app_data_dir(dir: PathBuf) {
+    *lock_or_recover(&APP_DATA_DIR, "APP_DATA_DIR") = Some(dir);
+}
+
+/// Configuration path relative to the working directory, used by earlier versions
+fn legacy_config_path() -> PathBuf {
     let mut app_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::new());
     app_dir.push("config");
     app_dir.push("config.json");
     app_dir
 }
 
+/// Determine configuration path
+///
+/// `JTLSYNC_CONFIG_PATH` wins, then the app data dir, then the working directory.
+/// An existing config in the working directory is kept until one exists in the app data dir.
+pub fn get_config_path() -> PathBuf {
+    if let Some(path) = std::env::var_os(CONFIG_PATH_ENV).filter(|path| !path.is_empty()) {
+        return PathBuf::from(path);
+    }
+    
+    let legacy_path = legacy_config_path();
+    
+    match lock_or_recover(&APP_DATA_DIR, "APP_DATA_DIR").clone() {
+        Some(dir) => {
+            let app_data_path = dir.join("config.json");
+            if !app_data_path.exists() && legacy_path.exists() {
+                legacy_path
+            } else {
+                app_data_path
+            }
+        }
+        None => legacy_path,
+    }
+}
+
 /// Save configuration
 pub fn save_config(config: &AppConfig) -> Result<()> {
     let config_path = get_config_path();
     
+    set_ui_log_level(&config.settings.log_level);
+    
     // Create directory if it doesn't exist
     if let Some(parent) = config_path.parent() {
         if !parent.exists() {
@@ -51,9 +99,21 @@ pub fn load_config() -> Result<AppConfig> {
     let config_str = fs::read_to_string(&config_path)
         .map_err(|e| Error::Config(format!("Failed to read config file: {}", e)))?;
     
-    let config: AppConfig = serde_json::from_str(&config_str)
+    let mut raw: serde_json::Value = serde_json::from_str(&config_str)
+        .map_err(|e| Error::Config(format!("Failed to parse config: {}", e)))?;
+    
+    // Upgrade older config files before parsing them into the current structure
+    let migrated = migrate_config(&mut raw);
+    
+    let config: AppConfig = serde_json::from_value(raw)
         .map_err(|e| Error::Config(format!("Failed to parse config: {}", e)))?;
     
+    if migrated {
+        save_config(&config)?;
+    }
+    
+    set_ui_log_level(&config.settings.log_level);
+    
     Ok(config)
 }
 
@@ -70,6 +130,22 @@ pub fn add_shop(config: &mut AppConfig, shop: ShopConfig) -> Result<()> {
     Ok(())
 }
 
+/// Duplicate an existing shop under a new name and a fresh ID
+pub fn duplicate_shop(config: &mut AppConfig, source_shop_id: &str, new_name: &str) -> Result<ShopConfig> {
+    let source = config.shops.iter().find(|s| s.id == source_shop_id)
+        .ok_or_else(|| Error::NotFound(format!("No shop found with ID '{}'", source_shop_id)))?;
+    
+    let mut shop = source.clone();
+    shop.id = Uuid::new_v4().to_string();
+    shop.name = new_name.to_string();
+    shop.validate()?;
+    
+    config.shops.push(shop.clone());
+    save_config(config)?;
+    
+    Ok(shop)
+}
+
 /// Update an existing shop
 pub fn update_shop(config: &mut AppConfig, shop: ShopConfig) -> Result<()> {
     let shop_index = config.shops.iter().position(|s| s.id == shop.id)
@@ -81,8 +157,12 @@ pub fn update_shop(config: &mut AppConfig, shop: ShopConfig) -> Result<()> {
     Ok(())
 }
 
-/// Remove a shop from the configuration
-pub fn remove_shop(config: &mut AppConfig, shop_id: &str) -> Result<()> {
+/// Remove a shop from the configuration, or only archive it when `archive` is set
+pub fn remove_shop(config: &mut AppConfig, shop_id: &str, archive: bool) -> Result<()> {
+    if archive {
+        return set_shop_archived(config, shop_id, true);
+    }
+    
     let initial_len = config.shops.len();
     
     // Don't allow removing the last shop
@@ -106,6 +186,33 @@ pub fn remove_shop(config: &mut AppConfig, shop_id: &str) -> Result<()> {
     }
 }
 
+/// Restore an archived shop
+pub fn unarchive_shop(config: &mut AppConfig, shop_id: &str) -> Result<()> {
+    set_shop_archived(config, shop_id, false)
+}
+
+/// Archive or restore a shop, keeping its config and stats
+fn set_shop_archived(config: &mut AppConfig, shop_id: &str, archived: bool) -> Result<()> {
+    let index = config.shops.iter().position(|s| s.id == shop_id)
+        .ok_or_else(|| Error::NotFound(format!("No shop found with ID '{}'", shop_id)))?;
+    
+    // Like removal, archiving must leave at least one usable shop
+    if archived && !config.shops.iter().any(|s| s.id != shop_id && !s.archived) {
+        return Err(Error::ValidationError("Cannot archive the last active shop".to_string()));
+    }
+    
+    config.shops[index].archived = archived;
+    
+    // An archived shop can't stay the current one
+    if archived && config.current_shop_index == index {
+        config.current_shop_index = config.shops.iter().position(|s| !s.archived).unwrap_or(0);
+    }
+    
+    save_config(config)?;
+    
+    Ok(())
+}
+
 /// Set the current active shop
 pub fn set_current_shop(config: &mut AppConfig, shop_id: &str) -> Result<()> {
     let shop_index = config.shops.iter().position(|s| s.id == shop_id)
@@ -115,4 +222,176 @@ pub fn set_current_shop(config: &mut AppConfig, shop_id: &str) -> Result<()> {
     save_config(config)?;
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Reorder shops to match the given id order, keeping the current shop selected
+pub fn reorder_shops(config: &mut AppConfig, shop_ids: &[String]) -> Result<()> {
+    apply_shop_order(config, shop_ids)?;
+    save_config(config)?;
+    
+    Ok(())
+}
+
+/// Sort the shops by the given ids without persisting the result
+fn apply_shop_order(config: &mut AppConfig, shop_ids: &[String]) -> Result<()> {
+    let mut unique_ids: Vec<&String> = shop_ids.iter().collect();
+    unique_ids.sort();
+    unique_ids.dedup();
+    
+    let same_set = unique_ids.len() == shop_ids.len()
+        && shop_ids.len() == config.shops.len()
+        && shop_ids.iter().all(|id| config.shops.iter().any(|s| &s.id == id));
+    
+    if !same_set {
+        return Err(Error::ValidationError("Shop order must contain every configured shop exactly once".to_string()));
+    }
+    
+    let current_id = config.shops.get(config.current_shop_index).map(|s| s.id.clone());
+    
+    config.shops.sort_by_key(|s| shop_ids.iter().position(|id| id == &s.id));
+    
+    config.current_shop_index = current_id
+        .and_then(|id| config.shops.iter().position(|s| s.id == id))
+        .unwrap_or(0);
+    
+    Ok(())
+}
+
+/// Enable or disable a shop for multi-shop synchronization
+pub fn set_shop_enabled(config: &mut AppConfig, shop_id: &str, enabled: bool) -> Result<()> {
+    let shop = config.shops.iter_mut().find(|s| s.id == shop_id)
+        .ok_or_else(|| Error::NotFound(format!("No shop found with ID '{}'", shop_id)))?;
+    
+    shop.enabled = enabled;
+    save_config(config)?;
+    
+    Ok(())
+}
+
+/// Set the default sync timeframe of a shop
+pub fn set_shop_sync_hours(config: &mut AppConfig, shop_id: &str, hours: i32) -> Result<()> {
+    let shop = config.shops.iter_mut().find(|s| s.id == shop_id)
+        .ok_or_else(|| Error::NotFound(format!("No shop found with ID '{}'", shop_id)))?;
+    
+    shop.sync_hours = Some(hours);
+    save_config(config)?;
+    
+    Ok(())
+}
+
+/// Set the JTL shipping method of a shop, `None` restores the standard method
+pub fn set_shop_default_shipping_method(config: &mut AppConfig, shop_id: &str, jtl_id: Option<i32>) -> Result<()> {
+    if jtl_id.is_some_and(|id| id <= 0) {
+        return Err(Error::ValidationError("Shipping method id must be greater than zero".to_string()));
+    }
+    
+    let shop = config.shops.iter_mut().find(|s| s.id == shop_id)
+        .ok_or_else(|| Error::NotFound(format!("No shop found with ID '{}'", shop_id)))?;
+    
+    shop.default_shipping_method_id = jtl_id;
+    save_config(config)?;
+    
+    Ok(())
+}
+
+/// Replace the global application settings
+pub fn update_settings(config: &mut AppConfig, settings: AppSettings) -> Result<()> {
+    settings.validate()?;
+    
+    config.settings = settings;
+    save_config(config)?;
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn config_with_shops(ids: &[&str]) -> AppConfig {
+        let mut config = AppConfig::default();
+        config.shops = ids.iter().map(|id| {
+            let mut shop = ShopConfig::new(id);
+            shop.id = id.to_string();
+            shop
+        }).collect();
+        config
+    }
+    
+    // Every test saves to the same file so parallel tests don't race on the variable
+    fn use_test_config_path() -> PathBuf {
+        let path = std::env::temp_dir().join("jtlsync-test-config.json");
+        std::env::set_var(CONFIG_PATH_ENV, &path);
+        path
+    }
+    
+    fn ids(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+    
+    #[test]
+    fn reorders_shops_and_keeps_current_selection() {
+        let mut config = config_with_shops(&["a", "b", "c"]);
+        config.current_shop_index = 1;
+        
+        apply_shop_order(&mut config, &ids(&["c", "b", "a"])).unwrap();
+        
+        let order: Vec<&str> = config.shops.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(order, vec!["c", "b", "a"]);
+        assert_eq!(config.shops[config.current_shop_index].id, "b");
+    }
+    
+    #[test]
+    fn rejects_incomplete_or_duplicate_order() {
+        let mut config = config_with_shops(&["a", "b", "c"]);
+        
+        assert!(apply_shop_order(&mut config, &ids(&["a", "b"])).is_err());
+        assert!(apply_shop_order(&mut config, &ids(&["a", "a", "b"])).is_err());
+        assert!(apply_shop_order(&mut config, &ids(&["a", "b", "x"])).is_err());
+        
+        let order: Vec<&str> = config.shops.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(order, vec!["a", "b", "c"]);
+    }
+    
+    #[test]
+    fn duplicated_shop_gets_new_id_and_same_databases() {
+        use_test_config_path();
+        let mut config = config_with_shops(&["a"]);
+        config.shops[0].joomla.database = "shop_a".to_string();
+        config.shops[0].tables.orders = "jos_orders".to_string();
+        
+        let copy = duplicate_shop(&mut config, "a", "Copy of A").unwrap();
+        
+        assert_ne!(copy.id, "a");
+        assert_eq!(copy.name, "Copy of A");
+        assert_eq!(copy.joomla.database, "shop_a");
+        assert_eq!(copy.tables.orders, "jos_orders");
+        assert_eq!(config.shops.len(), 2);
+        assert!(duplicate_shop(&mut config, "missing", "Copy").is_err());
+    }
+    
+    #[test]
+    fn archived_shop_is_hidden_but_kept() {
+        use_test_config_path();
+        let mut config = config_with_shops(&["a", "b"]);
+        config.current_shop_index = 1;
+        
+        set_shop_archived(&mut config, "b", true).unwrap();
+        
+        assert_eq!(config.shops.len(), 2);
+        assert!(config.shops[1].archived);
+        assert_eq!(config.enabled_shop_ids(), ids(&["a"]));
+        assert_eq!(config.current_shop_index, 0);
+        assert!(set_shop_archived(&mut config, "a", true).is_err());
+        
+        set_shop_archived(&mut config, "b", false).unwrap();
+        assert_eq!(config.enabled_shop_ids(), ids(&["a", "b"]));
+    }
+    
+    #[test]
+    fn config_path_can_be_overridden_by_env() {
+        let path = use_test_config_path();
+        
+        assert_eq!(get_config_path(), path);
+    }
+}
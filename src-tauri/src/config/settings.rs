@@ -0,0 +1,110 @@
+use reqwest::header::{HeaderName, HeaderValue};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+use crate::error::{Result, Error};
+
+/// Global application settings shared by all shops
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AppSettings {
+    pub inter_shop_delay_ms: u64,  // Pause between shops in a multi-shop sync
+    pub max_concurrent_syncs: usize,
+    pub notify_on_complete: bool,
+    pub notify_on_error: bool,
+    pub proxy: Option<String>,     // Optional HTTP proxy for JTL API requests
+    pub compress_requests: bool,   // Gzip large JTL request bodies
+    pub log_level: String,         // Minimum level of log entries shown in the UI (error, warn, info, debug)
+    pub strict_preflight: bool,    // Abort a shop sync if JTL reference data is missing
+    pub line_item_batch_size: Option<usize>, // Line items per request; all in one request if unset
+    pub pool_max_idle_per_host: Option<usize>, // Idle JTL connections kept open; reqwest default if unset
+    pub pool_idle_timeout_secs: Option<u64>,   // Close idle JTL connections after this; reqwest default if unset
+    pub extra_headers: HashMap<String, String>, // Added to every JTL request, replacing defaults like X-AppId
+    pub run_timeout_secs: Option<u64>, // Abort a shop sync that runs longer than this; no limit if unset
+    pub connection_retries: u32,   // Extra attempts to connect and fetch orders before a shop is skipped
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        AppSettings {
+            inter_shop_delay_ms: 500,
+            max_concurrent_syncs: 1,
+            notify_on_complete: true,
+            notify_on_error: true,
+            proxy: None,
+            compress_requests: false,
+            log_level: "info".to_string(),
+            strict_preflight: false,
+            line_item_batch_size: None,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout_secs: None,
+            extra_headers: HashMap::new(),
+            run_timeout_secs: None,
+            connection_retries: 2,
+        }
+    }
+}
+
+impl AppSettings {
+    /// Validate the settings
+    pub fn validate(&self) -> Result<()> {
+        if self.max_concurrent_syncs == 0 {
+            return Err(Error::ValidationError("Concurrent syncs must be at least 1".to_string()));
+        }
+        
+        if self.log_level.parse::<log::Level>().is_err() {
+            return Err(Error::ValidationError(format!("Unknown log level '{}'", self.log_level)));
+        }
+        
+        if self.run_timeout_secs == Some(0) {
+            return Err(Error::ValidationError("Run timeout must be at least 1 second".to_string()));
+        }
+        
+        if self.line_item_batch_size == Some(0) {
+            return Err(Error::ValidationError("Line item batch size must be at least 1".to_string()));
+        }
+        
+        for (name, value) in &self.extra_headers {
+            if HeaderName::from_bytes(name.as_bytes()).is_err() {
+                return Err(Error::ValidationError(format!("Invalid header name '{}'", name)));
+            }
+            if HeaderValue::from_str(value).is_err() {
+                return Err(Error::ValidationError(format!("Invalid value for header '{}'", name)));
+            }
+        }
+        
+        if let Some(proxy) = &self.proxy {
+            if proxy.trim().is_empty() {
+                return Err(Error::ValidationError("Proxy URL cannot be empty".to_string()));
+            }
+            if reqwest::Proxy::all(proxy.trim()).is_err() {
+                return Err(Error::ValidationError(format!("Invalid proxy URL '{}'", proxy)));
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::app::AppConfig;
+    
+    #[test]
+    fn config_without_settings_loads_defaults() {
+        let config: AppConfig = serde_json::from_value(serde_json::json!({
+            "shops": [],
+            "current_shop_index": 0
+        })).unwrap();
+        
+        assert_eq!(config.settings.max_concurrent_syncs, 1);
+        assert_eq!(config.settings.connection_retries, 2);
+        assert_eq!(config.settings.log_level, "info");
+        assert!(config.settings.validate().is_ok());
+        
+        let settings: AppSettings = serde_json::from_value(serde_json::json!({"inter_shop_delay_ms": 0})).unwrap();
+        assert_eq!(settings.inter_shop_delay_ms, 0);
+        assert!(settings.notify_on_error);
+    }
+}
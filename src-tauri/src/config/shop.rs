@@ -1,9 +1,171 @@
+use chrono_tz::Tz;
 use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::db::models::{DatabaseConfig, TablesConfig};
+use crate::db::models::{DatabaseConfig, TablesConfig, VirtueMartOrder};
+use crate::utils::mapping::is_eu_country;
 use crate::error::{Result, Error};
 
+/// Controls when synced orders are put on hold in JTL
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum HoldPolicy {
+    Always,
+    #[default]
+    OnlyUnpaid,
+    Never,
+}
+
+impl HoldPolicy {
+    /// Whether the hold workflow should be applied to an order
+    pub fn should_hold(&self, paid: bool) -> bool {
+        match self {
+            HoldPolicy::Always => true,
+            HoldPolicy::OnlyUnpaid => !paid,
+            HoldPolicy::Never => false,
+        }
+    }
+}
+
+/// Which VirtueMart date is used as the JTL order and shipping date
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum OrderDateSource {
+    #[default]
+    Created,
+    Paid,
+    Modified,
+}
+
+impl OrderDateSource {
+    /// Pick the configured date from an order, falling back to `created_on`
+    pub fn select<'a>(&self, order: &'a VirtueMartOrder) -> &'a str {
+        let date = match self {
+            OrderDateSource::Created => None,
+            OrderDateSource::Paid => order.paid_on.as_deref(),
+            OrderDateSource::Modified => order.modified_on.as_deref(),
+        };
+        
+        date.filter(|d| !d.is_empty()).unwrap_or(&order.created_on)
+    }
+}
+
+/// JTL company a shop's orders and customers are booked under
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct JtlCompanyProfile {
+    pub company_id: i32,             // CompanyId of the sales order
+    pub internal_company_id: i32,    // InternalCompanyId of the customer
+    pub departure_country_iso: String,
+    pub currency: String,            // ISO 4217, used for the order and its payment
+}
+
+impl Default for JtlCompanyProfile {
+    fn default() -> Self {
+        JtlCompanyProfile {
+            company_id: 1,
+            internal_company_id: 1,
+            departure_country_iso: "DE".to_string(),
+            currency: "EUR".to_string(),
+        }
+    }
+}
+
+/// Which VirtueMart timestamp decides whether an order falls into the sync timeframe
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum OrderTimeColumn {
+    #[default]
+    CreatedOn,
+    ModifiedOn,
+    Either, // Catches orders edited after creation
+}
+
+impl OrderTimeColumn {
+    /// SQL condition on the orders table alias `o`, one `?` per timestamp
+    pub fn filter_sql(&self, tables: &TablesConfig) -> String {
+        let created = tables.column("created_on");
+        let modified = tables.column("modified_on");
+        match self {
+            OrderTimeColumn::CreatedOn => format!("o.{} >= ?", created),
+            OrderTimeColumn::ModifiedOn => format!("o.{} >= ?", modified),
+            OrderTimeColumn::Either => format!("(o.{} >= ? OR o.{} >= ?)", created, modified),
+        }
+    }
+    
+    /// Number of timestamp parameters in `filter_sql`
+    pub fn param_count(&self) -> usize {
+        match self {
+            OrderTimeColumn::Either => 2,
+            _ => 1,
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+// Keeps the historic "[Shop] Item" naming for configs without the field
+pub const DEFAULT_LINE_ITEM_NAME_PREFIX: &str = "[{shop_name}] ";
+
+fn default_line_item_name_prefix() -> Option<String> {
+    Some(DEFAULT_LINE_ITEM_NAME_PREFIX.to_string())
+}
+
+// Keeps the shop name that used to be part of the order comment
+pub const DEFAULT_INTERNAL_NOTE_TEMPLATE: &str = "Shop: {shop_name}";
+
+fn default_internal_note_template() -> Option<String> {
+    Some(DEFAULT_INTERNAL_NOTE_TEMPLATE.to_string())
+}
+
+fn default_skip_orders_without_billing() -> bool {
+    true
+}
+
+/// Prefix used for JTL customer and order numbers unless configured otherwise
+pub const DEFAULT_NUMBER_PREFIX: &str = "VM";
+
+fn default_number_prefix() -> String {
+    DEFAULT_NUMBER_PREFIX.to_string()
+}
+
+/// Standard German VAT rate in percent
+pub const DEFAULT_TAX_RATE: f64 = 19.0;
+
+/// Decimal places of prices sent to JTL
+pub const DEFAULT_PRICE_DECIMALS: u8 = 2;
+
+// More decimals than JTL stores would only hide rounding differences
+const MAX_PRICE_DECIMALS: u8 = 4;
+
+fn default_price_decimals() -> u8 {
+    DEFAULT_PRICE_DECIMALS
+}
+
+/// Longest order comment JTL accepts, longer ones are rejected with a 422
+pub const DEFAULT_MAX_COMMENT_LENGTH: usize = 4000;
+
+fn default_max_comment_length() -> usize {
+    DEFAULT_MAX_COMMENT_LENGTH
+}
+
+/// Sync timeframe in hours for shops without a configured one
+pub const DEFAULT_SYNC_HOURS: i32 = 24;
+
+/// Timezone of VirtueMart dates for shops without a configured one
+pub const DEFAULT_TIMEZONE: &str = "UTC";
+
+fn default_timezone() -> String {
+    DEFAULT_TIMEZONE.to_string()
+}
+
+/// Country used for addresses without a known country
+pub const DEFAULT_COUNTRY_ISO: &str = "DE";
+
+fn default_country_iso() -> String {
+    DEFAULT_COUNTRY_ISO.to_string()
+}
+
 /// Shop configuration
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ShopConfig {
@@ -12,9 +174,78 @@ pub struct ShopConfig {
     pub joomla: DatabaseConfig,
     pub jtl: DatabaseConfig,
     pub tables: TablesConfig,
+    #[serde(default)]
+    pub placeholder_email_domain: Option<String>, // Domain used for placeholder emails when an order has none
+    #[serde(default)]
+    pub hold_policy: HoldPolicy,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool, // Disabled shops are skipped by multi-shop syncs
+    #[serde(default = "default_line_item_name_prefix")]
+    pub line_item_name_prefix: Option<String>, // Supports the {shop_name} placeholder; None means no prefix
+    #[serde(default)]
+    pub order_date_source: OrderDateSource,
+    #[serde(default = "default_skip_orders_without_billing")]
+    pub skip_orders_without_billing: bool, // Otherwise such orders are synced with minimal customer data
+    #[serde(default)]
+    pub min_order_total: Option<f64>, // Orders below this total are skipped (e.g. test orders)
+    #[serde(default)]
+    pub allow_non_positive_totals: bool, // Otherwise free or refunded orders with a total <= 0 are skipped
+    #[serde(default)]
+    pub shipping_tax_rate: Option<f64>, // Percent; defaults to the standard rate when unset
+    #[serde(default)]
+    pub zero_rate_exports: bool, // Shipping to non-EU countries is taxed at 0%
+    #[serde(default)]
+    pub emit_explicit_tax_line: bool, // Add a zero-priced line per tax rate stating the tax amount, for net-price shops
+    #[serde(default)]
+    pub allow_empty_orders: bool, // Otherwise orders without line items are skipped
+    #[serde(default)]
+    pub allow_service_only_orders: bool, // Sync orders with only shipping/coupon charges, without allowing fully empty ones
+    #[serde(default = "default_number_prefix")]
+    pub customer_number_prefix: String, // e.g. "VM" -> VM123
+    #[serde(default = "default_number_prefix")]
+    pub order_number_prefix: String,
+    #[serde(default)]
+    pub order_time_column: OrderTimeColumn,
+    #[serde(default)]
+    pub legacy_order_numbers: bool, // "VM123" instead of "VM-1A2B3C4D-123"; kept for shops synced before
+    #[serde(default = "default_country_iso")]
+    pub default_country_iso: String, // Used when an address has no country id or code
+    #[serde(default = "default_internal_note_template")]
+    pub internal_note_template: Option<String>, // Supports {shop_name}, {order_number} and {order_status}
+    #[serde(default)]
+    pub sync_hours: Option<i32>, // Default sync timeframe; 24 hours when unset
+    #[serde(default = "default_timezone")]
+    pub timezone: String, // IANA zone of the VirtueMart server time, e.g. "Europe/Berlin"
+    #[serde(default)]
+    pub excluded_email_domains: Vec<String>, // Orders from these domains are test orders, e.g. "example.com"
+    #[serde(default)]
+    pub excluded_user_ids: Vec<i32>, // VirtueMart user ids of test accounts
+    #[serde(default)]
+    pub tax_rate_by_sku_prefix: Vec<(String, f64)>, // e.g. ("BK-", 7.0) for reduced-rate books
+    #[serde(default = "default_price_decimals")]
+    pub price_decimals: u8, // Line item prices are rounded half-up to this many places
+    #[serde(default)]
+    pub jtl_company: JtlCompanyProfile,
+    #[serde(default = "default_max_comment_length")]
+    pub max_comment_length: usize, // Longer customer notes are cut off with an ellipsis
+    #[serde(default)]
+    pub default_shipping_method_id: Option<i32>, // JTL shipping method for all orders; the standard method if unset
+    #[serde(default)]
+    pub update_existing_customers: bool, // Update returning customers in JTL when their address changed
+    #[serde(default)]
+    pub archived: bool, // Archived shops keep their config but are hidden from listings and syncs
 }
 
 impl ShopConfig {
+    /// Short tag derived from the shop id that keeps order numbers unique across shops
+    pub fn order_number_tag(&self) -> String {
+        self.id.chars()
+            .filter(char::is_ascii_alphanumeric)
+            .take(8)
+            .collect::<String>()
+            .to_uppercase()
+    }
+    
     /// Create a new shop configuration with default values
     pub fn new(name: &str) -> Self {
         ShopConfig {
@@ -25,19 +256,134 @@ impl ShopConfig {
                 user: "root".to_string(),
                 password: "".to_string(),
                 database: "joomla".to_string(),
+                port: None,
             },
             jtl: DatabaseConfig {
                 host: "localhost".to_string(),
                 user: "root".to_string(),
                 password: "".to_string(),
                 database: "jtl".to_string(),
+                port: None,
             },
             tables: TablesConfig {
                 orders: "jos_virtuemart_orders".to_string(),
                 orderItems: "jos_virtuemart_order_items".to_string(),
                 customers: "jos_virtuemart_order_userinfos".to_string(),
+                country_code_column: None,
+                tracking_number_column: None,
+                carrier_column: None,
+                currency_code_column: None,
+                currency_factor_column: None,
+                column_map: HashMap::new(),
             },
+            placeholder_email_domain: None,
+            hold_policy: HoldPolicy::default(),
+            enabled: true,
+            line_item_name_prefix: default_line_item_name_prefix(),
+            order_date_source: OrderDateSource::default(),
+            skip_orders_without_billing: true,
+            min_order_total: None,
+            allow_non_positive_totals: false,
+            shipping_tax_rate: None,
+            zero_rate_exports: false,
+            emit_explicit_tax_line: false,
+            allow_empty_orders: false,
+            allow_service_only_orders: false,
+            customer_number_prefix: default_number_prefix(),
+            order_number_prefix: default_number_prefix(),
+            order_time_column: OrderTimeColumn::default(),
+            legacy_order_numbers: false,
+            default_country_iso: default_country_iso(),
+            internal_note_template: default_internal_note_template(),
+            sync_hours: None,
+            timezone: default_timezone(),
+            excluded_email_domains: Vec::new(),
+            excluded_user_ids: Vec::new(),
+            tax_rate_by_sku_prefix: Vec::new(),
+            price_decimals: default_price_decimals(),
+            jtl_company: JtlCompanyProfile::default(),
+            max_comment_length: default_max_comment_length(),
+            default_shipping_method_id: None,
+            update_existing_customers: false,
+            archived: false,
+        }
+    }
+    
+    /// Whether an order is skipped for a zero or negative total
+    pub fn is_non_positive_total(&self, order: &VirtueMartOrder) -> bool {
+        !self.allow_non_positive_totals && order.order_total <= 0.0
+    }
+    
+    /// Whether an order falls below the configured minimum total
+    pub fn is_below_min_total(&self, order: &VirtueMartOrder) -> bool {
+        self.min_order_total.is_some_and(|min| order.order_total < min)
+    }
+    
+    /// Tax rate in percent for the shipping line of an order shipped to `country_iso`
+    pub fn shipping_tax_rate_for(&self, country_iso: &str) -> f64 {
+        if self.zero_rate_exports && !is_eu_country(country_iso) {
+            return 0.0;
+        }
+        self.shipping_tax_rate.unwrap_or(DEFAULT_TAX_RATE)
+    }
+    
+    /// Tax rate in percent for a product line, from the longest matching SKU prefix
+    ///
+    /// Prefixes match case-insensitively, SKUs are often typed inconsistently
+    pub fn product_tax_rate_for(&self, sku: Option<&str>) -> f64 {
+        let Some(sku) = sku.map(str::trim).filter(|sku| !sku.is_empty()) else {
+            return DEFAULT_TAX_RATE;
+        };
+        self.tax_rate_by_sku_prefix.iter()
+            .filter(|(prefix, _)| !prefix.is_empty()
+                && sku.get(..prefix.len()).is_some_and(|head| head.eq_ignore_ascii_case(prefix)))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, rate)| *rate)
+            .unwrap_or(DEFAULT_TAX_RATE)
+    }
+    
+    /// Build a JTL line item name, applying the configured prefix template
+    pub fn line_item_name(&self, name: &str) -> String {
+        match &self.line_item_name_prefix {
+            Some(prefix) => format!("{}{}", prefix.replace("{shop_name}", &self.name), name),
+            None => name.to_string(),
+        }
+    }
+    
+    /// Sync timeframe in hours: the requested value, then the configured one, then 24
+    pub fn resolve_sync_hours(&self, requested: Option<i32>) -> i32 {
+        requested.or(self.sync_hours).unwrap_or(DEFAULT_SYNC_HOURS)
+    }
+    
+    /// Check whether an order was placed by a configured test customer
+    pub fn is_test_order(&self, order: &VirtueMartOrder) -> bool {
+        if order.virtuemart_user_id.is_some_and(|id| self.excluded_user_ids.contains(&id)) {
+            return true;
         }
+        
+        let Some(domain) = order.email.as_deref().and_then(|email| email.trim().rsplit_once('@')).map(|(_, domain)| domain) else {
+            return false;
+        };
+        self.excluded_email_domains.iter()
+            .any(|excluded| excluded.trim().trim_start_matches('@').eq_ignore_ascii_case(domain))
+    }
+    
+    /// Timezone of the shop's dates, UTC if the configured zone is unknown
+    pub fn tz(&self) -> Tz {
+        self.timezone.parse().unwrap_or(Tz::UTC)
+    }
+    
+    /// Build the internal JTL note for an order from the configured template
+    ///
+    /// Returns None if no template is set or it renders empty
+    pub fn internal_note(&self, order: &VirtueMartOrder) -> Option<String> {
+        let note = self.internal_note_template.as_ref()?
+            .replace("{shop_name}", &self.name)
+            .replace("{order_number}", &order.order_number)
+            .replace("{order_status}", order.order_status.as_deref().unwrap_or_default());
+        
+        let note = note.trim();
+        (!note.is_empty()).then(|| note.to_string())
     }
     
     /// Validate shop configuration
@@ -63,6 +409,82 @@ impl ShopConfig {
             return Err(Error::ValidationError("Joomla database name cannot be empty".to_string()));
         }
         
+        if self.joomla.port == Some(0) {
+            return Err(Error::ValidationError("Joomla database port must be between 1 and 65535".to_string()));
+        }
+        
+        if let Some(min) = self.min_order_total {
+            if !min.is_finite() || min < 0.0 {
+                return Err(Error::ValidationError("Minimum order total must be a non-negative number".to_string()));
+            }
+        }
+        
+        if let Some(rate) = self.shipping_tax_rate {
+            if !rate.is_finite() || !(0.0..=100.0).contains(&rate) {
+                return Err(Error::ValidationError("Shipping tax rate must be between 0 and 100".to_string()));
+            }
+        }
+        
+        for (prefix, rate) in &self.tax_rate_by_sku_prefix {
+            if prefix.trim().is_empty() {
+                return Err(Error::ValidationError("SKU prefix for a tax rate cannot be empty".to_string()));
+            }
+            if !rate.is_finite() || !(0.0..=100.0).contains(rate) {
+                return Err(Error::ValidationError(format!("Tax rate for SKU prefix '{}' must be between 0 and 100", prefix)));
+            }
+        }
+        
+        for (label, prefix) in [("Customer", &self.customer_number_prefix), ("Order", &self.order_number_prefix)] {
+            if prefix.is_empty() || !prefix.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(Error::ValidationError(format!("{} number prefix must be alphanumeric", label)));
+            }
+        }
+        
+        if self.jtl_company.company_id <= 0 || self.jtl_company.internal_company_id <= 0 {
+            return Err(Error::ValidationError("JTL company IDs must be positive".to_string()));
+        }
+        
+        let departure = &self.jtl_company.departure_country_iso;
+        if departure.len() != 2 || !departure.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(Error::ValidationError("Departure country must be a two-letter ISO code".to_string()));
+        }
+        
+        let currency = &self.jtl_company.currency;
+        if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(Error::ValidationError("Currency must be a three-letter ISO code".to_string()));
+        }
+        
+        if self.price_decimals > MAX_PRICE_DECIMALS {
+            return Err(Error::ValidationError(format!("Prices can have at most {} decimal places", MAX_PRICE_DECIMALS)));
+        }
+        
+        if self.default_shipping_method_id.is_some_and(|id| id <= 0) {
+            return Err(Error::ValidationError("Shipping method id must be greater than zero".to_string()));
+        }
+        
+        if self.max_comment_length == 0 {
+            return Err(Error::ValidationError("Maximum comment length must be greater than zero".to_string()));
+        }
+        
+        if self.sync_hours.is_some_and(|hours| hours <= 0) {
+            return Err(Error::ValidationError("Sync timeframe must be greater than zero hours".to_string()));
+        }
+        
+        // Mapped columns end up in the SQL text, only plain identifiers are allowed
+        for (field, column) in &self.tables.column_map {
+            if column.is_empty() || !column.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(Error::ValidationError(format!("Invalid column name '{}' for field '{}'", column, field)));
+            }
+        }
+        
+        if self.timezone.parse::<Tz>().is_err() {
+            return Err(Error::ValidationError(format!("Unknown timezone '{}'", self.timezone)));
+        }
+        
+        if self.default_country_iso.len() != 2 || !self.default_country_iso.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(Error::ValidationError("Default country must be a two-letter ISO code".to_string()));
+        }
+        
         // Validate table names
         if self.tables.orders.is_empty() {
             return Err(Error::ValidationError("Orders table name cannot be empty".to_string()));
@@ -78,4 +500,182 @@ impl ShopConfig {
         
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn shop_with_prefixes(prefixes: &[(&str, f64)]) -> ShopConfig {
+        let mut shop = ShopConfig::new("Test Shop");
+        shop.tax_rate_by_sku_prefix = prefixes.iter()
+            .map(|(prefix, rate)| (prefix.to_string(), *rate))
+            .collect();
+        shop
+    }
+    
+    #[test]
+    fn longest_sku_prefix_wins() {
+        let shop = shop_with_prefixes(&[("AB", 7.0), ("ABC", 0.0)]);
+        
+        assert_eq!(shop.product_tax_rate_for(Some("ABC-1")), 0.0);
+        assert_eq!(shop.product_tax_rate_for(Some("ABX-1")), 7.0);
+    }
+    
+    #[test]
+    fn unmatched_sku_uses_default_rate() {
+        let shop = shop_with_prefixes(&[("AB", 7.0)]);
+        
+        assert_eq!(shop.product_tax_rate_for(Some("XY-1")), DEFAULT_TAX_RATE);
+        assert_eq!(shop.product_tax_rate_for(Some("A")), DEFAULT_TAX_RATE);
+        assert_eq!(shop.product_tax_rate_for(Some("  ")), DEFAULT_TAX_RATE);
+        assert_eq!(shop.product_tax_rate_for(None), DEFAULT_TAX_RATE);
+    }
+    
+    #[test]
+    fn sku_prefix_ignores_case_and_whitespace() {
+        let shop = shop_with_prefixes(&[("bk-", 7.0)]);
+        
+        assert_eq!(shop.product_tax_rate_for(Some(" BK-123")), 7.0);
+        assert_eq!(shop.product_tax_rate_for(Some("Bk-9")), 7.0);
+    }
+    
+    fn order_from(user_id: Option<i32>, email: Option<&str>) -> VirtueMartOrder {
+        VirtueMartOrder {
+            virtuemart_user_id: user_id,
+            email: email.map(str::to_string),
+            ..Default::default()
+        }
+    }
+    
+    #[test]
+    fn detects_test_orders_by_user_id_and_email_domain() {
+        let mut shop = ShopConfig::new("Test Shop");
+        shop.excluded_user_ids = vec![99];
+        shop.excluded_email_domains = vec!["@Example.com".to_string()];
+        
+        assert!(shop.is_test_order(&order_from(Some(99), None)));
+        assert!(shop.is_test_order(&order_from(None, Some(" tester@example.COM "))));
+        assert!(!shop.is_test_order(&order_from(Some(1), Some("buyer@example.org"))));
+        assert!(!shop.is_test_order(&order_from(None, Some("not-an-email"))));
+        assert!(!shop.is_test_order(&order_from(None, None)));
+    }
+    
+    #[test]
+    fn hold_policy_decides_by_payment_state() {
+        assert!(HoldPolicy::Always.should_hold(true));
+        assert!(HoldPolicy::Always.should_hold(false));
+        assert!(HoldPolicy::OnlyUnpaid.should_hold(false));
+        assert!(!HoldPolicy::OnlyUnpaid.should_hold(true));
+        assert!(!HoldPolicy::Never.should_hold(false));
+        assert_eq!(HoldPolicy::default(), HoldPolicy::OnlyUnpaid);
+    }
+    
+    #[test]
+    fn order_time_filter_uses_mapped_columns() {
+        let mut tables = ShopConfig::new("Test Shop").tables;
+        assert_eq!(OrderTimeColumn::CreatedOn.filter_sql(&tables), "o.created_on >= ?");
+        
+        tables.column_map.insert("created_on".to_string(), "order_created".to_string());
+        tables.column_map.insert("modified_on".to_string(), "order_changed".to_string());
+        
+        assert_eq!(OrderTimeColumn::ModifiedOn.filter_sql(&tables), "o.order_changed >= ?");
+        assert_eq!(OrderTimeColumn::Either.filter_sql(&tables), "(o.order_created >= ? OR o.order_changed >= ?)");
+    }
+    
+    #[test]
+    fn shipping_tax_rate_follows_config_and_destination() {
+        let mut shop = ShopConfig::new("Test Shop");
+        assert_eq!(shop.shipping_tax_rate_for("DE"), 19.0);
+        
+        shop.shipping_tax_rate = Some(7.0);
+        assert_eq!(shop.shipping_tax_rate_for("DE"), 7.0);
+        assert_eq!(shop.shipping_tax_rate_for("US"), 7.0);
+        
+        shop.zero_rate_exports = true;
+        assert_eq!(shop.shipping_tax_rate_for("US"), 0.0);
+        assert_eq!(shop.shipping_tax_rate_for("at"), 7.0);
+    }
+    
+    #[test]
+    fn zero_and_negative_totals_are_skipped_unless_allowed() {
+        let mut shop = ShopConfig::new("Test Shop");
+        
+        assert!(shop.is_non_positive_total(&VirtueMartOrder { order_total: 0.0, ..Default::default() }));
+        assert!(shop.is_non_positive_total(&VirtueMartOrder { order_total: -12.5, ..Default::default() }));
+        assert!(!shop.is_non_positive_total(&VirtueMartOrder { order_total: 0.01, ..Default::default() }));
+        
+        shop.allow_non_positive_totals = true;
+        assert!(!shop.is_non_positive_total(&VirtueMartOrder { order_total: -12.5, ..Default::default() }));
+    }
+    
+    #[test]
+    fn minimum_total_skips_only_orders_below_it() {
+        let mut shop = ShopConfig::new("Test Shop");
+        assert!(!shop.is_below_min_total(&VirtueMartOrder { order_total: 1.0, ..Default::default() }));
+        
+        shop.min_order_total = Some(10.0);
+        assert!(shop.is_below_min_total(&VirtueMartOrder { order_total: 9.99, ..Default::default() }));
+        assert!(!shop.is_below_min_total(&VirtueMartOrder { order_total: 10.0, ..Default::default() }));
+        assert!(!shop.is_below_min_total(&VirtueMartOrder { order_total: 10.01, ..Default::default() }));
+    }
+    
+    #[test]
+    fn line_item_name_applies_the_prefix_template() {
+        let mut shop = ShopConfig::new("Test Shop");
+        assert_eq!(shop.line_item_name("Mug"), "[Test Shop] Mug");
+        
+        shop.line_item_name_prefix = Some("{shop_name}: ".to_string());
+        assert_eq!(shop.line_item_name("Mug"), "Test Shop: Mug");
+        
+        shop.line_item_name_prefix = None;
+        assert_eq!(shop.line_item_name("Mug"), "Mug");
+    }
+    
+    #[test]
+    fn internal_note_renders_the_template_apart_from_the_comment() {
+        let mut shop = ShopConfig::new("Test Shop");
+        let order = VirtueMartOrder {
+            order_number: "ORD1".to_string(),
+            order_status: Some("C".to_string()),
+            customer_note: Some("Please ring twice".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(shop.internal_note(&order).as_deref(), Some("Shop: Test Shop"));
+        
+        shop.internal_note_template = Some("{order_number} ({order_status}) from {shop_name}".to_string());
+        let note = shop.internal_note(&order).unwrap();
+        assert_eq!(note, "ORD1 (C) from Test Shop");
+        assert!(!note.contains("ring twice"));
+        
+        shop.internal_note_template = Some("  ".to_string());
+        assert_eq!(shop.internal_note(&order), None);
+    }
+    
+    #[test]
+    fn order_date_source_picks_the_configured_date() {
+        let order = VirtueMartOrder {
+            created_on: "2024-03-01 10:00:00".to_string(),
+            paid_on: Some("2024-03-02 10:00:00".to_string()),
+            modified_on: Some("2024-03-03 10:00:00".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(OrderDateSource::Created.select(&order), "2024-03-01 10:00:00");
+        assert_eq!(OrderDateSource::Paid.select(&order), "2024-03-02 10:00:00");
+        assert_eq!(OrderDateSource::Modified.select(&order), "2024-03-03 10:00:00");
+        
+        let unpaid = VirtueMartOrder { paid_on: None, modified_on: Some(String::new()), ..order };
+        assert_eq!(OrderDateSource::Paid.select(&unpaid), "2024-03-01 10:00:00");
+        assert_eq!(OrderDateSource::Modified.select(&unpaid), "2024-03-01 10:00:00");
+    }
+    
+    #[test]
+    fn sync_hours_prefer_argument_then_config() {
+        let mut shop = ShopConfig::new("Test Shop");
+        assert_eq!(shop.resolve_sync_hours(None), 24);
+        
+        shop.sync_hours = Some(48);
+        assert_eq!(shop.resolve_sync_hours(None), 48);
+        assert_eq!(shop.resolve_sync_hours(Some(6)), 6);
+    }
+}
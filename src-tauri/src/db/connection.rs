@@ -1,9 +1,36 @@
+use log::warn;
 use mysql::{OptsBuilder, Pool, Error as MySqlError};
 use std::sync::Arc;
 
 use crate::config::shop::ShopConfig;
 use crate::error::{Result, Error};
 
+// Run on every new connection so umlauts and other non-ASCII text arrive intact
+const CONNECTION_INIT: &str = "SET NAMES utf8mb4";
+
+/// Run blocking database work on Tokio's blocking thread pool
+///
+/// The `mysql` crate is synchronous, calling it directly from async code stalls the runtime
+pub async fn run_blocking<T, F>(work: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(work).await
+        .map_err(|e| Error::System(format!("Database task failed: {}", e)))?
+}
+
+/// Build connection options for a shop's Joomla database
+fn joomla_opts(shop: &ShopConfig) -> OptsBuilder {
+    OptsBuilder::new()
+        .ip_or_hostname(Some(&shop.joomla.host))
+        .tcp_port(shop.joomla.port())
+        .user(Some(&shop.joomla.user))
+        .pass(Some(&shop.joomla.password))
+        .db_name(Some(&shop.joomla.database))
+        .init(vec![CONNECTION_INIT])
+}
+
 /// Connection pool manager for database connections
 pub struct ConnectionManager {
     pools: std::collections::HashMap<String, Arc<Pool>>,
@@ -25,13 +52,7 @@ impl ConnectionManager {
         }
         
         // Create a new pool
-        let opts = OptsBuilder::new()
-            .ip_or_hostname(Some(&shop.joomla.host))
-            .user(Some(&shop.joomla.user))
-            .pass(Some(&shop.joomla.password))
-            .db_name(Some(&shop.joomla.database));
-        
-        let pool = Pool::new(opts)
+        let pool = Pool::new(joomla_opts(shop))
             .map_err(|e| Error::Database(format!("Failed to create connection pool: {}", e)))?;
         
         // Store the pool
@@ -41,6 +62,26 @@ impl ConnectionManager {
         Ok(pool_arc)
     }
     
+    /// Get a connection pool for a shop, rebuilding a stale cached pool once
+    ///
+    /// A cached pool can go bad after a database restart or a network blip,
+    /// so a failing pool is dropped and recreated before giving up.
+    pub fn get_joomla_pool_checked(&mut self, shop: &ShopConfig) -> Result<Arc<Pool>> {
+        let pool = self.get_joomla_pool(shop)?;
+        
+        if let Err(e) = pool.get_conn() {
+            warn!("Connection pool for shop '{}' is unusable ({}), rebuilding", shop.name, e);
+            self.remove_pool(&shop.id);
+            
+            let pool = self.get_joomla_pool(shop)?;
+            pool.get_conn()
+                .map_err(|e| Error::Database(format!("Connection failed after rebuilding pool: {}", e)))?;
+            return Ok(pool);
+        }
+        
+        Ok(pool)
+    }
+    
     /// Test connection to verify credentials
     pub fn test_connection(&mut self, shop: &ShopConfig) -> Result<()> {
         let pool = self.get_joomla_pool(shop)?;
@@ -50,19 +91,57 @@ impl ConnectionManager {
         Ok(())
     }
     
-    /// Clear connection pools
-    pub fn clear_pools(&mut self) {
-        self.pools.clear();
+    /// Drop the cached pool of a shop; returns whether one was cached
+    ///
+    /// Connections close once the last clone of the pool is dropped
+    pub fn remove_pool(&mut self, shop_id: &str) -> bool {
+        self.pools.remove(shop_id).is_some()
     }
 }
 
 /// Connect to Joomla database - legacy function for compatibility
 pub fn connect_to_joomla(shop: &ShopConfig) -> std::result::Result<Pool, MySqlError> {
-    let opts = OptsBuilder::new()
-        .ip_or_hostname(Some(&shop.joomla.host))
-        .user(Some(&shop.joomla.user))
-        .pass(Some(&shop.joomla.password))
-        .db_name(Some(&shop.joomla.database));
+    Pool::new(joomla_opts(shop))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mysql::{Opts, PoolConstraints, PoolOpts};
+    
+    #[test]
+    fn removing_a_pool_drops_only_that_shop() {
+        let mut shop = ShopConfig::new("Test Shop");
+        shop.id = "shop1".to_string();
+        
+        // Without idle connections the pool does not connect on creation
+        let opts = joomla_opts(&shop)
+            .pool_opts(PoolOpts::default().with_constraints(PoolConstraints::new(0, 1).unwrap()));
+        let mut manager = ConnectionManager::new();
+        manager.pools.insert(shop.id.clone(), Arc::new(Pool::new(opts).unwrap()));
+        
+        assert!(!manager.remove_pool("shop2"));
+        assert!(manager.remove_pool("shop1"));
+        assert!(!manager.remove_pool("shop1"));
+    }
     
-    Pool::new(opts)
-}
\ No newline at end of file
+    #[test]
+    fn joomla_opts_use_configured_port() {
+        let mut shop = ShopConfig::new("Test Shop");
+        shop.joomla.port = Some(3307);
+        
+        let opts = Opts::from(joomla_opts(&shop));
+        assert_eq!(opts.get_tcp_port(), 3307);
+        assert_eq!(opts.get_db_name(), Some("joomla"));
+        
+        shop.joomla.port = None;
+        assert_eq!(Opts::from(joomla_opts(&shop)).get_tcp_port(), crate::db::models::DEFAULT_MYSQL_PORT);
+    }
+    
+    #[test]
+    fn connections_are_initialized_with_utf8mb4() {
+        let opts = Opts::from(joomla_opts(&ShopConfig::new("Test Shop")));
+        
+        assert!(opts.get_init().iter().any(|statement| statement == "SET NAMES utf8mb4"));
+    }
+}
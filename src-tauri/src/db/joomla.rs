@@ -1,5 +1,6 @@
-use chrono::{Utc, Duration};
-use log::{info, error};
+use chrono::{DateTime, Utc, Duration};
+use chrono_tz::Tz;
+use log::{info, warn, error};
 use mysql::{prelude::Queryable, Row, Value, Pool};
 
 use crate::config::shop::ShopConfig;
@@ -16,89 +17,244 @@ fn mysql_date_to_string(value: Value) -> String {
     }
 }
 
+/// Read an optional date column, treating NULL, zero dates and missing columns as None
+fn read_optional_date(row: &Row, column: &str) -> Option<String> {
+    match row.get_opt::<Value, _>(column) {
+        Some(Ok(Value::Date(0, _, _, _, _, _, _))) => None,
+        Some(Ok(value @ Value::Date(..))) => Some(mysql_date_to_string(value)),
+        Some(Ok(Value::Bytes(bytes))) => {
+            let text = String::from_utf8_lossy(&bytes).to_string();
+            if text.is_empty() || text.starts_with("0000") { None } else { Some(text) }
+        },
+        _ => None
+    }
+}
+
+/// Read a trimmed string from an optionally configured column
+fn read_optional_column(row: &Row, column: Option<&str>) -> Option<String> {
+    match row.get_opt::<String, _>(column?) {
+        Some(Ok(value)) if !value.trim().is_empty() => Some(value.trim().to_string()),
+        _ => None // Column missing, NULL or empty
+    }
+}
+
+/// Read the direct ISO country code if the shop has a column configured for it
+fn read_country_code(row: &Row, shop: &ShopConfig) -> Option<String> {
+    read_optional_column(row, shop.tables.country_code_column.as_deref())
+        .map(|code| code.to_uppercase())
+}
+
+/// Read the order currency if the shop has a column configured for it
+///
+/// Anything but a 3-letter code is ignored, so the shop currency is used
+fn read_currency_iso(row: &Row, shop: &ShopConfig) -> Option<String> {
+    read_optional_column(row, shop.tables.currency_code_column.as_deref())
+        .filter(|code| code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic()))
+        .map(|code| code.to_uppercase())
+}
+
+/// Read the currency conversion factor if the shop has a column configured for it
+fn read_currency_factor(row: &Row, shop: &ShopConfig) -> Option<f64> {
+    read_optional_column(row, shop.tables.currency_factor_column.as_deref())
+        .and_then(|factor| factor.parse::<f64>().ok())
+        .filter(|factor| factor.is_finite() && *factor > 0.0)
+}
+
+/// Convert a joined order/billing row to a VirtueMartOrder
+fn order_from_row(row: Row, shop: &ShopConfig) -> VirtueMartOrder {
+    let order_id: i32 = row.get(shop.tables.column("virtuemart_order_id")).unwrap_or(0);
+    let order_number: String = row.get(shop.tables.column("order_number")).unwrap_or_else(|| format!("VM{}", order_id));
+    
+    // Get formatted date as string
+    let created_on: String = row.get("created_on_str").unwrap_or_else(|| {
+        // Fallback: If formatted date not available, try raw date
+        let raw_date: Value = row.get(shop.tables.column("created_on")).unwrap_or(Value::NULL);
+        mysql_date_to_string(raw_date)
+    });
+
+    let phone_1: Option<String> = match row.get_opt::<String, _>(shop.tables.column("phone_1")) {
+        Some(Ok(value)) => Some(value),
+        _ => None // Field doesn't exist or is NULL or has wrong type
+    };
+    
+    let phone_2: Option<String> = match row.get_opt::<String, _>(shop.tables.column("phone_2")) {
+        Some(Ok(value)) => Some(value),
+        _ => None // Field doesn't exist or is NULL or has wrong type
+    };
+                    
+    VirtueMartOrder {
+        virtuemart_order_id: order_id,
+        order_number,
+        created_on,
+        modified_on: read_optional_date(&row, "modified_on_str"),
+        paid_on: read_optional_date(&row, shop.tables.column("paid_on")),
+        order_total: row.get(shop.tables.column("order_total")).unwrap_or(0.0),
+        virtuemart_user_id: row.get(shop.tables.column("virtuemart_user_id")),
+        order_status: row.get(shop.tables.column("order_status")),
+        first_name: row.get(shop.tables.column("first_name")),
+        last_name: row.get(shop.tables.column("last_name")),
+        phone_1,
+        phone_2,
+        address_1: row.get(shop.tables.column("address_1")),
+        address_2: row.get(shop.tables.column("address_2")),
+        zip: row.get(shop.tables.column("zip")),
+        city: row.get(shop.tables.column("city")),
+        virtuemart_country_id: row.get(shop.tables.column("virtuemart_country_id")).unwrap_or(None),
+        country_code: read_country_code(&row, shop),
+        tracking_number: read_optional_column(&row, shop.tables.tracking_number_column.as_deref()),
+        carrier_name: read_optional_column(&row, shop.tables.carrier_column.as_deref()),
+        currency_iso: read_currency_iso(&row, shop),
+        currency_factor: read_currency_factor(&row, shop),
+        email: row.get(shop.tables.column("email")),
+        virtuemart_paymentmethod_id: row.get(shop.tables.column("virtuemart_paymentmethod_id")),
+        virtuemart_shipmentmethod_id: row.get(shop.tables.column("virtuemart_shipmentmethod_id")),
+        virtuemart_order_userinfo_id: row.get(shop.tables.column("virtuemart_order_userinfo_id")),
+        customer_note: row.get(shop.tables.column("customer_note")).unwrap_or(Some(String::new())), 
+        order_shipment: row.get(shop.tables.column("order_shipment")),
+        coupon_code: row.get(shop.tables.column("coupon_code")).unwrap_or(Some(String::new())),
+        coupon_discount: row.get(shop.tables.column("coupon_discount")).unwrap_or(Some(0.0)),
+        company: row.get(shop.tables.column("company")).unwrap_or(Some(String::new())),
+        shop_id: Some(shop.id.clone()),
+    }
+}
+
+/// Format a point in time as a VirtueMart timestamp in the shop's local time
+///
+/// `created_on`/`modified_on` are stored without offset in the server zone,
+/// so comparing them with a UTC string shifts the window by the UTC offset.
+fn shop_local_time(time: DateTime<Utc>, timezone: Tz) -> String {
+    time.with_timezone(&timezone).format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// SELECT and FROM clause joining orders with their billing address (BT), using the shop's columns
+fn order_select_sql(shop: &ShopConfig) -> String {
+    let columns = &shop.tables;
+    format!(
+        "SELECT o.*, c.*, 
+         DATE_FORMAT(o.{created_on}, '%Y-%m-%d %H:%M:%S') as created_on_str,
+         DATE_FORMAT(o.{modified_on}, '%Y-%m-%d %H:%M:%S') as modified_on_str 
+         FROM {orders} o
+         LEFT JOIN {customers} c ON o.{order_id} = c.{order_id} AND c.{address_type} = 'BT'",
+        created_on = columns.column("created_on"),
+        modified_on = columns.column("modified_on"),
+        orders = columns.orders,
+        customers = columns.customers,
+        order_id = columns.column("virtuemart_order_id"),
+        address_type = columns.column("address_type"),
+    )
+}
+
+/// Positional parameters for the shop's order time filter
+fn time_params(shop: &ShopConfig, formatted_time: &str) -> Vec<String> {
+    vec![formatted_time.to_string(); shop.order_time_column.param_count()]
+}
+
 /// Get orders within a configurable timeframe
 pub fn get_orders_within_timeframe(pool: &Pool, shop: &ShopConfig, hours: i32) -> Result<Vec<VirtueMartOrder>> {
-    let now = Utc::now();
-    let past_time = now - Duration::hours(hours as i64);
-    let formatted_time = past_time.format("%Y-%m-%d %H:%M:%S").to_string();
+    let past_time = Utc::now() - Duration::hours(hours as i64);
     
-    info!("Searching orders since: {} ({}h timeframe) for Shop '{}'", formatted_time, hours, shop.name);
+    info!("Searching orders within {}h timeframe for Shop '{}'", hours, shop.name);
+    get_orders_since(pool, shop, past_time)
+}
+
+/// Get orders created at or after an absolute timestamp
+pub fn get_orders_since(pool: &Pool, shop: &ShopConfig, since: DateTime<Utc>) -> Result<Vec<VirtueMartOrder>> {
+    let formatted_time = shop_local_time(since, shop.tz());
+    
+    info!("Searching orders since: {} for Shop '{}'", formatted_time, shop.name);
     
     let query = format!(
-        "SELECT o.*, c.*, 
-         DATE_FORMAT(o.created_on, '%Y-%m-%d %H:%M:%S') as created_on_str 
-         FROM {} o
-         JOIN {} c ON o.virtuemart_order_id = c.virtuemart_order_id
-         WHERE o.created_on >= ? AND c.address_type = 'BT'
-         ORDER BY o.created_on DESC",
-        shop.tables.orders, shop.tables.customers
+        "{}
+         WHERE {}
+         ORDER BY o.{} DESC",
+        order_select_sql(shop), shop.order_time_column.filter_sql(&shop.tables), shop.tables.column("created_on")
     );
     
     let mut conn = pool.get_conn()
         .map_err(|e| Error::Database(format!("Error connecting to database for shop '{}': {}", shop.name, e)))?;
     
-    let results = conn.exec_map(query, (formatted_time,), |row: Row| {
-        // Converting MySQL Row to VirtueMartOrder
-        let order_id: i32 = row.get("virtuemart_order_id").unwrap_or(0);
-        let order_number: String = row.get("order_number").unwrap_or_else(|| format!("VM{}", order_id));
-        
-        // Get formatted date as string
-        let created_on: String = row.get("created_on_str").unwrap_or_else(|| {
-            // Fallback: If formatted date not available, try raw date
-            let raw_date: Value = row.get("created_on").unwrap_or(Value::NULL);
-            mysql_date_to_string(raw_date)
-        });
-
-        let phone_1: Option<String> = match row.get_opt::<String, _>("phone_1") {
-            Some(Ok(value)) => Some(value),
-            _ => None // Field doesn't exist or is NULL or has wrong type
-        };
-        
-        let phone_2: Option<String> = match row.get_opt::<String, _>("phone_2") {
-            Some(Ok(value)) => Some(value),
-            _ => None // Field doesn't exist or is NULL or has wrong type
-        };
-                        
-        VirtueMartOrder {
-            virtuemart_order_id: order_id,
-            order_number,
-            created_on,
-            order_total: row.get("order_total").unwrap_or(0.0),
-            virtuemart_user_id: row.get("virtuemart_user_id"),
-            order_status: row.get("order_status"),
-            first_name: row.get("first_name"),
-            last_name: row.get("last_name"),
-            phone_1,
-            phone_2,
-            address_1: row.get("address_1"),
-            address_2: row.get("address_2"),
-            zip: row.get("zip"),
-            city: row.get("city"),
-            virtuemart_country_id: row.get("virtuemart_country_id").unwrap_or(Some(81)),
-            email: row.get("email"),
-            virtuemart_paymentmethod_id: row.get("virtuemart_paymentmethod_id"),
-            virtuemart_shipmentmethod_id: row.get("virtuemart_shipmentmethod_id"),
-            virtuemart_order_userinfo_id: row.get("virtuemart_order_userinfo_id"),
-            customer_note: row.get("customer_note").unwrap_or(Some(String::new())), 
-            order_shipment: row.get("order_shipment"),
-            coupon_code: row.get("coupon_code").unwrap_or(Some(String::new())),
-            coupon_discount: row.get("coupon_discount").unwrap_or(Some(0.0)),
-            company: row.get("company").unwrap_or(Some(String::new())),
-            shop_id: Some(shop.id.clone()),
-        }
-    }).map_err(|e| Error::Database(format!("Error fetching orders for shop '{}': {}", shop.name, e)))?;
+    let results = conn.exec_map(query, time_params(shop, &formatted_time), |row: Row| order_from_row(row, shop))
+        .map_err(|e| Error::Database(format!("Error fetching orders for shop '{}': {}", shop.name, e)))?;
+    
+    let missing_billing = results.iter().filter(|o| !o.has_billing_address()).count();
+    if missing_billing > 0 {
+        warn!("{} orders without billing address (BT) found for shop '{}'", missing_billing, shop.name);
+    }
     
     info!("Found {} orders for shop '{}'", results.len(), shop.name);
     Ok(results)
 }
 
+/// Get a single order by its VirtueMart ID
+pub fn get_order_by_id(pool: &Pool, shop: &ShopConfig, order_id: i32) -> Result<Option<VirtueMartOrder>> {
+    let query = format!(
+        "{}
+         WHERE o.{} = ?",
+        order_select_sql(shop), shop.tables.column("virtuemart_order_id")
+    );
+    
+    let mut conn = pool.get_conn()
+        .map_err(|e| Error::Database(format!("Error connecting to database for shop '{}': {}", shop.name, e)))?;
+    
+    let row: Option<Row> = conn.exec_first(query, (order_id,))
+        .map_err(|e| Error::Database(format!("Error fetching order {} for shop '{}': {}", order_id, shop.name, e)))?;
+    
+    Ok(row.map(|row| order_from_row(row, shop)))
+}
+
+/// Get the most recently created order of a shop
+pub fn get_latest_order(pool: &Pool, shop: &ShopConfig) -> Result<Option<VirtueMartOrder>> {
+    let query = format!(
+        "{}
+         ORDER BY o.{} DESC
+         LIMIT 1",
+        order_select_sql(shop), shop.tables.column("created_on")
+    );
+    
+    let mut conn = pool.get_conn()
+        .map_err(|e| Error::Database(format!("Error connecting to database for shop '{}': {}", shop.name, e)))?;
+    
+    let row: Option<Row> = conn.query_first(query)
+        .map_err(|e| Error::Database(format!("Error fetching latest order for shop '{}': {}", shop.name, e)))?;
+    
+    Ok(row.map(|row| order_from_row(row, shop)))
+}
+
+/// Count orders within a timeframe without fetching the rows
+pub fn count_orders_within_timeframe(pool: &Pool, shop: &ShopConfig, hours: i32) -> Result<usize> {
+    let past_time = Utc::now() - Duration::hours(hours as i64);
+    let formatted_time = shop_local_time(past_time, shop.tz());
+    
+    let query = format!(
+        "SELECT COUNT(*) 
+         FROM {orders} o
+         LEFT JOIN {customers} c ON o.{order_id} = c.{order_id} AND c.{address_type} = 'BT'
+         WHERE {filter}",
+        orders = shop.tables.orders,
+        customers = shop.tables.customers,
+        order_id = shop.tables.column("virtuemart_order_id"),
+        address_type = shop.tables.column("address_type"),
+        filter = shop.order_time_column.filter_sql(&shop.tables),
+    );
+    
+    let mut conn = pool.get_conn()
+        .map_err(|e| Error::Database(format!("Error connecting to database for shop '{}': {}", shop.name, e)))?;
+    
+    let count: Option<u64> = conn.exec_first(query, time_params(shop, &formatted_time))
+        .map_err(|e| Error::Database(format!("Error counting orders for shop '{}': {}", shop.name, e)))?;
+    
+    let count = count.unwrap_or(0) as usize;
+    info!("Counted {} orders within {}h for shop '{}'", count, hours, shop.name);
+    Ok(count)
+}
+
 /// Get order items for an order
 pub fn get_order_items(pool: &Pool, shop: &ShopConfig, order_id: i32) -> Result<Vec<VirtueMartOrderItem>> {
     info!("Fetching order items for order {} in Shop '{}'", order_id, shop.name);
     
     let query = format!(
-        "SELECT * FROM {} WHERE virtuemart_order_id = ?",
-        shop.tables.orderItems
+        "SELECT * FROM {} WHERE {} = ?",
+        shop.tables.orderItems, shop.tables.column("virtuemart_order_id")
     );
     
     let mut conn = pool.get_conn()
@@ -106,14 +262,14 @@ pub fn get_order_items(pool: &Pool, shop: &ShopConfig, order_id: i32) -> Result<
     
     let results = conn.exec_map(query, (order_id,), |row: Row| {
         VirtueMartOrderItem {
-            virtuemart_order_item_id: row.get("virtuemart_order_item_id").unwrap_or(0),
-            virtuemart_order_id: row.get("virtuemart_order_id").unwrap_or(0),
-            order_item_sku: row.get("order_item_sku"),
-            order_item_name: row.get("order_item_name").unwrap_or_else(|| "Unknown Product".to_string()),
-            product_quantity: row.get("product_quantity").unwrap_or(1),
-            product_final_price: row.get("product_final_price").unwrap_or(0.0),
-            product_tax: row.get("product_tax"),
-            product_priceWithoutTax: row.get("product_priceWithoutTax"),
+            virtuemart_order_item_id: row.get(shop.tables.column("virtuemart_order_item_id")).unwrap_or(0),
+            virtuemart_order_id: row.get(shop.tables.column("virtuemart_order_id")).unwrap_or(0),
+            order_item_sku: row.get(shop.tables.column("order_item_sku")),
+            order_item_name: row.get(shop.tables.column("order_item_name")).unwrap_or_else(|| "Unknown Product".to_string()),
+            product_quantity: row.get(shop.tables.column("product_quantity")).unwrap_or(1),
+            product_final_price: row.get(shop.tables.column("product_final_price")).unwrap_or(0.0),
+            product_tax: row.get(shop.tables.column("product_tax")),
+            product_priceWithoutTax: row.get(shop.tables.column("product_priceWithoutTax")),
         }
     }).map_err(|e| Error::Database(format!("Error fetching order items for shop '{}': {}", shop.name, e)))?;
     
@@ -127,8 +283,8 @@ pub fn get_shipping_address(pool: &Pool, shop: &ShopConfig, order_id: i32) -> Re
     info!("Checking shipping address for order {} in Shop '{}'", order_id, shop.name);
     
     let query = format!(
-        "SELECT * FROM {} WHERE virtuemart_order_id = ? AND address_type = 'ST'",
-        shop.tables.customers
+        "SELECT * FROM {} WHERE {} = ? AND {} = 'ST'",
+        shop.tables.customers, shop.tables.column("virtuemart_order_id"), shop.tables.column("address_type")
     );
     
     let mut conn = pool.get_conn()
@@ -136,60 +292,62 @@ pub fn get_shipping_address(pool: &Pool, shop: &ShopConfig, order_id: i32) -> Re
     
     let results: Vec<VirtueMartOrder> = conn.exec_map(query, (order_id,), |row: Row| {
         // Handle all optional fields properly
-        let phone_1: Option<String> = match row.get_opt::<String, _>("phone_1") {
+        let phone_1: Option<String> = match row.get_opt::<String, _>(shop.tables.column("phone_1")) {
             Some(Ok(value)) => Some(value),
             _ => None // Field doesn't exist or is NULL or has wrong type
         };
         
-        let phone_2: Option<String> = match row.get_opt::<String, _>("phone_2") {
+        let phone_2: Option<String> = match row.get_opt::<String, _>(shop.tables.column("phone_2")) {
             Some(Ok(value)) => Some(value),
             _ => None
         };
         
-        let first_name: Option<String> = match row.get_opt::<String, _>("first_name") {
+        let first_name: Option<String> = match row.get_opt::<String, _>(shop.tables.column("first_name")) {
             Some(Ok(value)) => Some(value),
             _ => None
         };
         
-        let last_name: Option<String> = match row.get_opt::<String, _>("last_name") {
+        let last_name: Option<String> = match row.get_opt::<String, _>(shop.tables.column("last_name")) {
             Some(Ok(value)) => Some(value),
             _ => None
         };
         
-        let address_1: Option<String> = match row.get_opt::<String, _>("address_1") {
+        let address_1: Option<String> = match row.get_opt::<String, _>(shop.tables.column("address_1")) {
             Some(Ok(value)) => Some(value),
             _ => None
         };
         
-        let address_2: Option<String> = match row.get_opt::<String, _>("address_2") {
+        let address_2: Option<String> = match row.get_opt::<String, _>(shop.tables.column("address_2")) {
             Some(Ok(value)) => Some(value),
             _ => None
         };
         
-        let zip: Option<String> = match row.get_opt::<String, _>("zip") {
+        let zip: Option<String> = match row.get_opt::<String, _>(shop.tables.column("zip")) {
             Some(Ok(value)) => Some(value),
             _ => None
         };
         
-        let city: Option<String> = match row.get_opt::<String, _>("city") {
+        let city: Option<String> = match row.get_opt::<String, _>(shop.tables.column("city")) {
             Some(Ok(value)) => Some(value),
             _ => None
         };
         
-        let email: Option<String> = match row.get_opt::<String, _>("email") {
+        let email: Option<String> = match row.get_opt::<String, _>(shop.tables.column("email")) {
             Some(Ok(value)) => Some(value),
             _ => None
         };
         
-        let company: Option<String> = match row.get_opt::<String, _>("company") {
+        let company: Option<String> = match row.get_opt::<String, _>(shop.tables.column("company")) {
             Some(Ok(value)) => Some(value),
             _ => None
         };
         
         VirtueMartOrder {
-            virtuemart_order_id: row.get("virtuemart_order_id").unwrap_or(0),
+            virtuemart_order_id: row.get(shop.tables.column("virtuemart_order_id")).unwrap_or(0),
             order_number: "".to_string(), // Not needed for shipping address
             created_on: "".to_string(),   // Not needed for shipping address
+            modified_on: None,
+            paid_on: None,
             order_total: 0.0,             // Not needed for shipping address
             virtuemart_user_id: None,     // Not needed for shipping address
             order_status: None,           // Not needed for shipping address
@@ -201,11 +359,16 @@ pub fn get_shipping_address(pool: &Pool, shop: &ShopConfig, order_id: i32) -> Re
             address_2,
             zip,
             city,
-            virtuemart_country_id: row.get("virtuemart_country_id").unwrap_or(Some(81)),
+            virtuemart_country_id: row.get(shop.tables.column("virtuemart_country_id")).unwrap_or(None),
+            country_code: read_country_code(&row, shop),
+            tracking_number: None,
+            carrier_name: None,
+            currency_iso: None,
+            currency_factor: None,
             email,
             virtuemart_paymentmethod_id: None, // Not needed for shipping address
             virtuemart_shipmentmethod_id: None, // Not needed for shipping address
-            virtuemart_order_userinfo_id: row.get("virtuemart_order_userinfo_id"),
+            virtuemart_order_userinfo_id: row.get(shop.tables.column("virtuemart_order_userinfo_id")),
             customer_note: None, // Not needed for shipping address
             order_shipment: None,
             coupon_code: None,
@@ -222,4 +385,42 @@ pub fn get_shipping_address(pool: &Pool, shop: &ShopConfig, order_id: i32) -> Re
         info!("Separate shipping address (ST) found for order {} in shop '{}'", order_id, shop.name);
         Ok(Some(results[0].clone()))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    
+    #[test]
+    fn formats_window_start_in_shop_time() {
+        let winter = Utc.with_ymd_and_hms(2024, 1, 15, 11, 0, 0).unwrap();
+        let summer = Utc.with_ymd_and_hms(2024, 7, 15, 10, 0, 0).unwrap();
+        
+        assert_eq!(shop_local_time(winter, Tz::Europe__Berlin), "2024-01-15 12:00:00");
+        assert_eq!(shop_local_time(summer, Tz::Europe__Berlin), "2024-07-15 12:00:00");
+        assert_eq!(shop_local_time(summer, Tz::UTC), "2024-07-15 10:00:00");
+    }
+    
+    #[test]
+    fn order_query_uses_mapped_columns() {
+        let mut shop = ShopConfig::new("Test Shop");
+        shop.tables.column_map.insert("virtuemart_order_id".to_string(), "order_id".to_string());
+        shop.tables.column_map.insert("created_on".to_string(), "created".to_string());
+        shop.tables.column_map.insert("address_type".to_string(), "kind".to_string());
+        shop.tables.column_map.insert("first_name".to_string(), "fname".to_string());
+        
+        let sql = order_select_sql(&shop);
+        
+        assert!(sql.contains("DATE_FORMAT(o.created, "));
+        assert!(sql.contains("DATE_FORMAT(o.modified_on, "));
+        assert!(sql.contains("ON o.order_id = c.order_id AND c.kind = 'BT'"));
+        assert!(!sql.contains("virtuemart_order_id"));
+        
+        // Billing columns come in through c.*, order_from_row reads them under their mapped name
+        assert!(sql.contains("SELECT o.*, c.*"));
+        assert!(!sql.contains("first_name"));
+        assert_eq!(shop.tables.column("first_name"), "fname");
+        assert_eq!(shop.tables.column("last_name"), "last_name");
+    }
+}
@@ -3,5 +3,5 @@ pub mod models;
 pub mod joomla;
 
 // Re-export commonly used types and functions
-pub use connection::ConnectionManager;
+pub use connection::{ConnectionManager, run_blocking};
 pub use models::{VirtueMartOrder, VirtueMartOrderItem, JtlOrder, JtlCustomer};
\ No newline at end of file
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 // Database configuration
 #[derive(Serialize, Deserialize, Clone)]
@@ -7,6 +8,18 @@ pub struct DatabaseConfig {
     pub user: String,
     pub password: String,
     pub database: String,
+    #[serde(default)]
+    pub port: Option<u16>, // Defaults to the standard MySQL port when unset
+}
+
+/// Standard MySQL port
+pub const DEFAULT_MYSQL_PORT: u16 = 3306;
+
+impl DatabaseConfig {
+    /// Configured port, falling back to the MySQL default
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(DEFAULT_MYSQL_PORT)
+    }
 }
 
 // Table configuration
@@ -15,14 +28,37 @@ pub struct TablesConfig {
     pub orders: String,
     pub orderItems: String,
     pub customers: String,
+    #[serde(default)]
+    pub country_code_column: Option<String>, // Column holding the 2-letter ISO code, if the install has one
+    #[serde(default)]
+    pub tracking_number_column: Option<String>, // Order column with the shipment tracking number
+    #[serde(default)]
+    pub carrier_column: Option<String>,         // Order column with the carrier name
+    #[serde(default)]
+    pub currency_code_column: Option<String>,   // Order column with the 3-letter ISO currency of a multi-currency shop
+    #[serde(default)]
+    pub currency_factor_column: Option<String>, // Order column with the conversion factor to the shop currency
+    #[serde(default)]
+    pub column_map: HashMap<String, String>,    // Logical field -> actual column, e.g. "first_name" -> "fname"
+}
+
+impl TablesConfig {
+    /// Actual column name of a logical field, the field name itself unless remapped
+    pub fn column<'a>(&'a self, field: &'a str) -> &'a str {
+        self.column_map.get(field).map(String::as_str).unwrap_or(field)
+    }
 }
 
 // VirtueMart order structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct VirtueMartOrder {
     pub virtuemart_order_id: i32,
     pub order_number: String,
     pub created_on: String,
+    #[serde(default)]
+    pub modified_on: Option<String>,
+    #[serde(default)]
+    pub paid_on: Option<String>, // Only present on VirtueMart versions with a paid_on column
     pub order_total: f64,
     pub company: Option<String>,
     pub virtuemart_user_id: Option<i32>,
@@ -44,11 +80,28 @@ pub struct VirtueMartOrder {
     pub coupon_code: Option<String>,
     pub coupon_discount: Option<f64>,
     pub virtuemart_country_id: Option<i32>,
+    #[serde(default)]
+    pub country_code: Option<String>, // Direct ISO code read from `country_code_column`
+    #[serde(default)]
+    pub tracking_number: Option<String>,
+    #[serde(default)]
+    pub carrier_name: Option<String>,
+    #[serde(default)]
+    pub currency_iso: Option<String>,   // Read from `currency_code_column`; the shop currency if unset
+    #[serde(default)]
+    pub currency_factor: Option<f64>,   // Read from `currency_factor_column`
     pub shop_id: Option<String>, // Added shop_id to track which shop this order belongs to
 }
 
+impl VirtueMartOrder {
+    /// Whether a billing (BT) userinfo row was joined for this order
+    pub fn has_billing_address(&self) -> bool {
+        self.virtuemart_order_userinfo_id.is_some()
+    }
+}
+
 // VirtueMart order item structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct VirtueMartOrderItem {
     pub virtuemart_order_item_id: i32,
     pub virtuemart_order_id: i32,
@@ -61,6 +114,7 @@ pub struct VirtueMartOrderItem {
 }
 
 // JTL API models
+// Optional fields are omitted instead of sent as null, which some JTL endpoints reject
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JtlAddress {
     pub City: String,
@@ -83,12 +137,17 @@ pub struct JtlAddress {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JtlOrderItem {
     pub Quantity: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub SalesPriceGross: Option<f64>,
     pub TaxRate: f64,
     pub Name: String,
     pub SalesUnit: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub SalesPriceNet: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub PurchasePriceNet: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub Sku: Option<String>, // Lets JTL link the line to an existing article
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -102,7 +161,9 @@ pub struct JtlOrder {
     pub SalesOrderDate: String,
     pub SalesOrderPaymentDetails: JtlPaymentDetails,
     pub SalesOrderShippingDetail: JtlShippingDetails,
-    pub Comment: String,
+    pub Comment: String, // Customer note from the shop
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub InternalNote: Option<String>,
     pub LanguageIso: String,
 }
 
@@ -124,6 +185,10 @@ pub struct JtlPaymentDetails {
 pub struct JtlShippingDetails {
     pub ShippingMethodId: i32,
     pub ShippingDate: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub TrackingId: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub CarrierName: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -135,4 +200,58 @@ pub struct JtlCustomer {
     pub Shipmentaddress: JtlAddress,
     pub CustomerSince: String,
     pub Number: String,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn order_item_leaves_out_unset_fields() {
+        let item = JtlOrderItem {
+            Quantity: 1,
+            SalesPriceGross: None,
+            TaxRate: 19.0,
+            Name: "Widget".to_string(),
+            SalesUnit: "stk".to_string(),
+            SalesPriceNet: None,
+            PurchasePriceNet: None,
+            Sku: None,
+        };
+        
+        let json = serde_json::to_value(&item).unwrap();
+        
+        assert_eq!(json, serde_json::json!({ "Quantity": 1, "TaxRate": 19.0, "Name": "Widget", "SalesUnit": "stk" }));
+    }
+    
+    #[test]
+    fn shipping_details_carry_tracking_and_carrier() {
+        let details = JtlShippingDetails {
+            ShippingMethodId: 7,
+            ShippingDate: "2024-03-01T10:00:00+00:00".to_string(),
+            TrackingId: Some("00340434161234567890".to_string()),
+            CarrierName: Some("DHL".to_string()),
+        };
+        let json = serde_json::to_value(&details).unwrap();
+        assert_eq!(json["TrackingId"], "00340434161234567890");
+        assert_eq!(json["CarrierName"], "DHL");
+        
+        let untracked = JtlShippingDetails { TrackingId: None, CarrierName: None, ..details };
+        let json = serde_json::to_value(&untracked).unwrap();
+        assert!(json.get("TrackingId").is_none());
+        assert!(json.get("CarrierName").is_none());
+    }
+    
+    #[test]
+    fn orders_without_a_bt_row_are_detected() {
+        let orders = vec![
+            VirtueMartOrder { virtuemart_order_userinfo_id: Some(7), ..Default::default() },
+            VirtueMartOrder { virtuemart_order_userinfo_id: None, ..Default::default() },
+            VirtueMartOrder { virtuemart_order_userinfo_id: None, ..Default::default() },
+        ];
+        
+        assert!(orders[0].has_billing_address());
+        assert!(!orders[1].has_billing_address());
+        assert_eq!(orders.iter().filter(|o| !o.has_billing_address()).count(), 2);
+    }
+}
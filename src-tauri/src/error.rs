@@ -12,6 +12,7 @@ pub enum Error {
     System(String),
     NotFound(String),
     ValidationError(String),
+    RateLimited { retry_after_secs: u64 },
 }
 
 impl fmt::Display for Error {
@@ -24,6 +25,7 @@ impl fmt::Display for Error {
             Error::System(msg) => write!(f, "System error: {}", msg),
             Error::NotFound(msg) => write!(f, "Not found: {}", msg),
             Error::ValidationError(msg) => write!(f, "Validation error: {}", msg),
+            Error::RateLimited { retry_after_secs } => write!(f, "API rate limit exceeded, retry after {} seconds", retry_after_secs),
         }
     }
 }
@@ -64,5 +66,64 @@ impl From<serde_json::Error> for Error {
     }
 }
 
+/// Order-level sync failure, categorized by the step that failed
+#[derive(Debug)]
+pub enum SyncError {
+    LoadOrder(String),        // Reading line items or the shipping address from Joomla
+    CustomerCreate(String),   // Looking up or creating the JTL customer
+    OrderExistsCheck(String),
+    OrderCreate(String),      // Creating or updating the JTL order
+    ItemsCreate(String),      // The order was created, its line items were not
+    Workflow(String),         // The order was created, payment status or hold failed
+}
+
+impl SyncError {
+    /// Stable category name for counting and grouping failures
+    pub fn category(&self) -> &'static str {
+        match self {
+            SyncError::LoadOrder(_) => "load_order",
+            SyncError::CustomerCreate(_) => "customer_create",
+            SyncError::OrderExistsCheck(_) => "order_exists_check",
+            SyncError::OrderCreate(_) => "order_create",
+            SyncError::ItemsCreate(_) => "items_create",
+            SyncError::Workflow(_) => "workflow",
+        }
+    }
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncError::LoadOrder(msg) => write!(f, "Loading order data failed: {}", msg),
+            SyncError::CustomerCreate(msg) => write!(f, "Customer creation failed: {}", msg),
+            SyncError::OrderExistsCheck(msg) => write!(f, "Order existence check failed: {}", msg),
+            SyncError::OrderCreate(msg) => write!(f, "Order creation failed: {}", msg),
+            SyncError::ItemsCreate(msg) => write!(f, "Line item creation failed: {}", msg),
+            SyncError::Workflow(msg) => write!(f, "Order workflow failed: {}", msg),
+        }
+    }
+}
+
+impl StdError for SyncError {}
+
 // Convenience type alias
-pub type Result<T> = std::result::Result<T, Error>;
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn sync_errors_map_to_their_categories() {
+        let categories: Vec<&str> = [
+            SyncError::LoadOrder(String::new()),
+            SyncError::CustomerCreate(String::new()),
+            SyncError::OrderExistsCheck(String::new()),
+            SyncError::OrderCreate(String::new()),
+            SyncError::ItemsCreate(String::new()),
+            SyncError::Workflow(String::new()),
+        ].iter().map(SyncError::category).collect();
+        
+        assert_eq!(categories, vec!["load_order", "customer_create", "order_exists_check", "order_create", "items_create", "workflow"]);
+    }
+}
@@ -4,8 +4,10 @@
 )]
 
 use chrono::Utc;
-use tauri::{Emitter, Manager};
+use log::{info, warn};
+use tauri::{Manager, RunEvent};
 use std::error::Error;
+use std::time::Duration;
 
 
 use jtlsync_lib::{
@@ -16,10 +18,22 @@ use jtlsync_lib::{
     // Models
     models::LogEntry,
     
+    // Graceful shutdown
+    sync::polling::stop_polling,
+    utils::abort::set_abort_flag,
+    utils::emit::emit_log,
+    utils::tasks::{request_shutdown, running_sync_count, wait_for_running_syncs},
+    
+    // Configuration
+    config::set_app_data_dir,
+    
     // Initialization
     init,
 };
 
+// Maximum time to wait for running syncs to finish their current order on exit
+const SHUTDOWN_TIMEOUT_SECS: u64 = 30;
+
 fn main() -> Result<(), Box<dyn Error>> {
     // Initialize the application
     init()?;
@@ -32,25 +46,64 @@ fn main() -> Result<(), Box<dyn Error>> {
             // Config commands
             jtlsync_lib::commands::config::load_config_command,
             jtlsync_lib::commands::config::save_config_command,
+            jtlsync_lib::commands::config::reload_config,
             jtlsync_lib::commands::config::add_shop_command,
             jtlsync_lib::commands::config::update_shop_command,
             jtlsync_lib::commands::config::remove_shop_command,
+            jtlsync_lib::commands::config::unarchive_shop_command,
             jtlsync_lib::commands::config::set_current_shop_command,
+            jtlsync_lib::commands::config::duplicate_shop_command,
+            jtlsync_lib::commands::config::reorder_shops_command,
+            jtlsync_lib::commands::config::get_shops_summary,
+            jtlsync_lib::commands::config::set_shop_enabled_command,
+            jtlsync_lib::commands::config::set_default_shipping_method,
+            jtlsync_lib::commands::config::get_settings_command,
+            jtlsync_lib::commands::config::update_settings_command,
 
             jtlsync_lib::commands::sync::start_sync_command,
             jtlsync_lib::commands::sync::start_multi_sync_command,
+            jtlsync_lib::commands::sync::sync_all_shops,
+            jtlsync_lib::commands::sync::start_sync_from,
             jtlsync_lib::commands::sync::get_sync_stats,
+            jtlsync_lib::commands::sync::get_aggregate_stats,
             jtlsync_lib::commands::sync::set_sync_hours,
+            jtlsync_lib::commands::sync::set_sync_hours_all,
             jtlsync_lib::commands::sync::schedule_sync,
+            jtlsync_lib::commands::sync::validate_cron,
             jtlsync_lib::commands::sync::cancel_scheduled_sync,
+            jtlsync_lib::commands::sync::start_polling_command,
+            jtlsync_lib::commands::sync::stop_polling_command,
+            jtlsync_lib::commands::sync::get_polling_status,
+            jtlsync_lib::commands::sync::list_scheduled_jobs,
             jtlsync_lib::commands::sync::abort_sync_command,
+            jtlsync_lib::commands::sync::pause_sync_command,
+            jtlsync_lib::commands::sync::resume_sync_command,
             jtlsync_lib::commands::sync::start_scheduled_sync,
             jtlsync_lib::commands::sync::get_synced_orders,
+            jtlsync_lib::commands::sync::refresh_order_statuses,
+            jtlsync_lib::commands::sync::lookup_jtl_customer,
+            jtlsync_lib::commands::sync::get_last_jtl_response,
+            jtlsync_lib::commands::sync::diff_order,
+            jtlsync_lib::commands::sync::find_incomplete_orders,
+            jtlsync_lib::commands::sync::trigger_workflow_command,
+            jtlsync_lib::commands::sync::get_failed_orders,
+            jtlsync_lib::commands::sync::estimate_sync_time,
+            jtlsync_lib::commands::sync::get_reconciliation_issues,
+            jtlsync_lib::commands::sync::count_pending_orders,
 
             jtlsync_lib::commands::system::get_system_info,
+            jtlsync_lib::commands::system::get_version_info,
+            jtlsync_lib::commands::system::get_payment_method_name,
+            jtlsync_lib::commands::system::smoke_test,
             
         ])
         .setup(|app| {
+            // Store the config in the app data dir instead of the working directory
+            match app.path().app_data_dir() {
+                Ok(dir) => set_app_data_dir(dir),
+                Err(e) => warn!("Could not resolve app data dir, using working directory for config: {}", e),
+            }
+            
             // Set up the notification handler
             setup_notification_handler(app)?;
             
@@ -58,7 +111,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             let app_handle = app.app_handle();
             
             // Log application start
-            let _ = app_handle.emit("log", LogEntry {
+            let _ = emit_log(app_handle, LogEntry {
                 timestamp: Utc::now(),
                 message: "Application started".to_string(),
                 level: "info".to_string(),
@@ -68,7 +121,32 @@ fn main() -> Result<(), Box<dyn Error>> {
             
             Ok(())
         })
-        .run(tauri::generate_context!())?;
+        .build(tauri::generate_context!())?
+        .run(|app_handle, event| {
+            if let RunEvent::ExitRequested { api, .. } = event {
+                // No new polls may start while shutting down
+                stop_polling();
+                
+                // Nothing to wait for, or we are already past the graceful path
+                if running_sync_count() == 0 || !request_shutdown() {
+                    return;
+                }
+                
+                api.prevent_exit();
+                info!("Exit requested with {} sync(s) running, aborting after current order", running_sync_count());
+                set_abort_flag();
+                
+                let app_handle = app_handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    if wait_for_running_syncs(Duration::from_secs(SHUTDOWN_TIMEOUT_SECS)).await {
+                        info!("All running syncs finished, shutting down");
+                    } else {
+                        warn!("Timed out waiting for {} running sync(s), shutting down anyway", running_sync_count());
+                    }
+                    app_handle.exit(0);
+                });
+            }
+        });
     
     Ok(())
 }
\ No newline at end of file
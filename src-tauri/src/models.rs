@@ -20,4 +20,12 @@ pub struct LogEntry {
     pub level: String,
     pub category: String,
     pub shop_id: Option<String>, // Optional shop_id to identify which shop this log belongs to
+}
+
+// Payload of the "order-created" event
+#[derive(Serialize, Clone)]
+pub struct OrderCreatedEvent {
+    pub shop_id: String,
+    pub external_number: String,
+    pub jtl_order_id: String,
 }
\ No newline at end of file
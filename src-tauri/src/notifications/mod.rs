@@ -1,13 +1,25 @@
 mod platform;
 
-use log::{info, error};
-use serde::{Deserialize, Serialize};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use serde::Deserialize;
 use tauri::Manager;
 
-use crate::error::{Result, Error};
+use crate::error::Result;
 
 pub use platform::show_notification;
 
+lazy_static! {
+    // Probed once, notifications are disabled for the session if the backend is missing
+    static ref BACKEND_AVAILABLE: bool = {
+        let available = platform::backend_available();
+        if !available {
+            warn!("Notification backend not available, notifications are disabled");
+        }
+        available
+    };
+}
+
 #[derive(Deserialize)]
 pub struct NotificationPayload {
     pub title: String,
@@ -21,6 +33,19 @@ pub fn show_notification_command(notification: NotificationPayload) -> Result<()
     show_notification(&notification.title, &notification.body)
 }
 
+/// Show a notification from inside the app
+///
+/// Notifications are non-critical, so failures are logged instead of returned
+pub fn notify(title: &str, message: &str) {
+    if !*BACKEND_AVAILABLE {
+        return;
+    }
+    
+    if let Err(e) = show_notification(title, message) {
+        warn!("Failed to show notification '{}': {}", title, e);
+    }
+}
+
 /// Setup notification handler for the app
 pub fn setup_notification_handler(app: &mut tauri::App) -> Result<()> {
     // Get the app handle and use that instead of the mutable app reference
@@ -3,6 +3,27 @@ use std::process::Command;
 
 use crate::error::{Result, Error};
 
+/// Check whether the command used to show notifications can be started
+#[cfg(target_os = "windows")]
+pub fn backend_available() -> bool {
+    Command::new("powershell").args(&["-Command", "exit"]).output().is_ok()
+}
+
+#[cfg(target_os = "linux")]
+pub fn backend_available() -> bool {
+    Command::new("notify-send").arg("--version").output().is_ok()
+}
+
+#[cfg(target_os = "macos")]
+pub fn backend_available() -> bool {
+    Command::new("osascript").args(&["-e", "return"]).output().is_ok()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+pub fn backend_available() -> bool {
+    false
+}
+
 #[cfg(target_os = "windows")]
 pub fn show_notification(title: &str, message: &str) -> Result<()> {
     info!("Showing Windows notification: {} - {}", title, message);
@@ -0,0 +1,135 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::db::models::{JtlAddress, JtlOrder, JtlOrderItem};
+
+// Totals closer than this are considered equal
+const TOTAL_TOLERANCE: f64 = 0.005;
+
+/// A single field that differs between VirtueMart and JTL
+#[derive(Serialize, Clone, Debug)]
+pub struct FieldDiff {
+    pub field: String,
+    pub virtuemart: String, // Value the sync would send
+    pub jtl: String,        // Value JTL currently holds
+}
+
+/// Differences between a VirtueMart order and its JTL counterpart
+#[derive(Serialize, Clone, Debug)]
+pub struct OrderDiff {
+    pub external_number: String,
+    pub jtl_order_id: Option<String>, // None if the order is not in JTL yet
+    pub differences: Vec<FieldDiff>,
+}
+
+/// Gross total of a list of line items
+fn gross_total<'a>(items: impl Iterator<Item = (f64, f64)> + 'a) -> f64 {
+    items.map(|(quantity, price)| quantity * price).sum()
+}
+
+/// Read a JSON value as text for comparison, treating null as empty
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.trim().to_string(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Compare the fields of an address with the JTL address object
+fn diff_address(prefix: &str, ours: &JtlAddress, theirs: &Value, differences: &mut Vec<FieldDiff>) {
+    let Ok(Value::Object(ours)) = serde_json::to_value(ours) else {
+        return;
+    };
+    
+    for (key, value) in ours {
+        // Only compare fields JTL actually returns
+        let Some(their_value) = theirs.get(&key) else {
+            continue;
+        };
+        
+        let (ours, theirs) = (value_text(&value), value_text(their_value));
+        if ours != theirs {
+            differences.push(FieldDiff {
+                field: format!("{}.{}", prefix, key),
+                virtuemart: ours,
+                jtl: theirs,
+            });
+        }
+    }
+}
+
+/// Compare a built JTL order with the order and line items JTL holds
+pub fn compare_with_jtl(
+    order: &JtlOrder,
+    items: &[JtlOrderItem],
+    jtl_order: &Value,
+    jtl_items: &[Value]
+) -> Vec<FieldDiff> {
+    let mut differences = Vec::new();
+    
+    let our_total = gross_total(items.iter()
+        .map(|item| (item.Quantity as f64, item.SalesPriceGross.unwrap_or_default())));
+    let their_total = gross_total(jtl_items.iter()
+        .map(|item| (item["Quantity"].as_f64().unwrap_or_default(), item["SalesPriceGross"].as_f64().unwrap_or_default())));
+    
+    if (our_total - their_total).abs() > TOTAL_TOLERANCE {
+        differences.push(FieldDiff {
+            field: "TotalGross".to_string(),
+            virtuemart: format!("{:.2}", our_total),
+            jtl: format!("{:.2}", their_total),
+        });
+    }
+    
+    if items.len() != jtl_items.len() {
+        differences.push(FieldDiff {
+            field: "ItemCount".to_string(),
+            virtuemart: items.len().to_string(),
+            jtl: jtl_items.len().to_string(),
+        });
+    }
+    
+    diff_address("BillingAddress", &order.BillingAddress, &jtl_order["BillingAddress"], &mut differences);
+    diff_address("Shipmentaddress", &order.Shipmentaddress, &jtl_order["Shipmentaddress"], &mut differences);
+    
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::shop::ShopConfig;
+    use crate::db::models::VirtueMartOrder;
+    use crate::sync::processor::build_jtl_order;
+    
+    fn item(gross: f64) -> JtlOrderItem {
+        JtlOrderItem {
+            Quantity: 2,
+            SalesPriceGross: Some(gross),
+            TaxRate: 19.0,
+            Name: "Item".to_string(),
+            SalesUnit: "stk".to_string(),
+            SalesPriceNet: None,
+            PurchasePriceNet: None,
+            Sku: None,
+        }
+    }
+    
+    #[test]
+    fn reports_mismatched_total() {
+        let (order, _) = build_jtl_order(&VirtueMartOrder::default(), &[], None, &ShopConfig::new("Test Shop"), 1);
+        let jtl_order = serde_json::to_value(&order).unwrap();
+        let items = vec![item(10.0)];
+        
+        let matching = vec![serde_json::json!({"Quantity": 2, "SalesPriceGross": 10.0})];
+        assert!(compare_with_jtl(&order, &items, &jtl_order, &matching).is_empty());
+        
+        let mismatched = vec![serde_json::json!({"Quantity": 2, "SalesPriceGross": 9.5})];
+        let differences = compare_with_jtl(&order, &items, &jtl_order, &mismatched);
+        
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].field, "TotalGross");
+        assert_eq!(differences[0].virtuemart, "20.00");
+        assert_eq!(differences[0].jtl, "19.00");
+    }
+}
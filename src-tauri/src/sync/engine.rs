@@ -1,26 +1,157 @@
-use chrono::Utc;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use log::{info, error, warn};
+use mysql::Pool;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::sync::Arc;
 use tokio::time::sleep;
 use tokio::time::Duration as TokioDuration;
 use tauri::{AppHandle, Runtime, Emitter, Manager};
+use tokio_util::sync::CancellationToken;
 
 
-use crate::api::jtl::JtlApiClient;
+use crate::api::jtl::{JtlApiClient, is_supported_version, MIN_SUPPORTED_JTL_VERSION, MAX_SUPPORTED_JTL_VERSION};
+use crate::api::webhook::send_completion_webhook;
 use crate::config::app::AppConfig;
+use crate::config::settings::AppSettings;
 use crate::config::shop::ShopConfig;
-use crate::db::connection::ConnectionManager;
-use crate::db::joomla::{get_orders_within_timeframe, get_order_items, get_shipping_address};
+use crate::db::connection::{ConnectionManager, run_blocking};
+use crate::db::joomla::{get_orders_since, get_order_items, get_shipping_address};
+use crate::db::models::VirtueMartOrder;
 use crate::error::{Result, Error};
-use crate::models::LogEntry;
-use crate::sync::processor::process_order;
-use crate::sync::stats::{SyncStats, update_sync_stats, get_shop_stats};
-use crate::utils::abort::{should_abort, reset_abort_flag};
+use crate::models::{LogEntry, OrderCreatedEvent};
+use crate::sync::failures::{clear_failed_order, record_failed_order};
+use crate::sync::history::{record_sync_run, SyncRun};
+use crate::sync::processor::{process_order, external_order_number, OrderResult, SkipReason, CustomerCache, JTL_CUSTOMER_GROUP_ID, shipping_method_id};
+use crate::sync::reconciliation::clear_reconciliation_issues;
+use crate::sync::stats::{SyncStats, MultiSyncSummary, update_sync_stats, set_last_error};
+use crate::utils::abort::{should_abort, should_pause, reset_abort_flag, abort_token, Interruption, RunInterrupt};
+use crate::utils::emit::emit_log;
+use crate::utils::mapping::jtl_payment_method_ids;
+use crate::utils::tasks::ShopSyncGuard;
+
+// How often a paused sync checks whether it was resumed
+const PAUSE_CHECK_INTERVAL_MS: u64 = 500;
+
+// Wait before the first connection retry, doubled for every further attempt
+const CONNECTION_RETRY_BASE_DELAY_SECS: u64 = 2;
+
+// Stats reason for runs stopped by the user
+const USER_ABORT_REASON: &str = "Aborted on user request";
+
+/// Why a shop listed for a multi-shop sync is skipped, if it is
+///
+/// Disabled shops are not skipped here: they are left out of "sync all" by
+/// `AppConfig::enabled_shop_ids`, but sync when requested individually.
+fn shop_skip_reason(shop: &ShopConfig) -> Option<&'static str> {
+    shop.archived.then_some("archived")
+}
+
+/// Count an order as skipped before processing, log why and publish the updated stats
+fn skip_order<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    shop: &ShopConfig,
+    stats: &mut SyncStats,
+    level: &str,
+    message: String
+) -> Result<()> {
+    stats.skipped_orders += 1;
+    info!("{}", message);
+    
+    let _ = emit_log(app_handle, LogEntry {
+        timestamp: Utc::now(),
+        message,
+        level: level.to_string(),
+        category: "sync".to_string(),
+        shop_id: Some(shop.id.clone()),
+    });
+    
+    update_sync_stats(stats.clone());
+    app_handle.emit("sync-stats-update", (shop.id.clone(), stats.clone()))
+        .map_err(|e| Error::System(format!("Failed to emit event: {}", e)))
+}
+
+/// Mark run stats as aborted with the reason of the interruption
+fn mark_interrupted(stats: &mut SyncStats, interruption: Interruption, run_timeout: Option<TokioDuration>) {
+    stats.aborted = true;
+    stats.abort_reason = Some(match interruption {
+        Interruption::Aborted => USER_ABORT_REASON.to_string(),
+        Interruption::TimedOut => format!("Run timeout of {} seconds exceeded", run_timeout.map(|t| t.as_secs()).unwrap_or_default()),
+    });
+}
+
+/// Remove orders whose external number is already known to JTL
+///
+/// Returns the remaining orders and how many were removed
+fn exclude_existing_orders(
+    orders: Vec<VirtueMartOrder>,
+    existing: &HashSet<String>,
+    shop: &ShopConfig
+) -> (Vec<VirtueMartOrder>, usize) {
+    let total = orders.len();
+    let remaining: Vec<VirtueMartOrder> = orders.into_iter()
+        .filter(|order| !existing.contains(&external_order_number(order, shop)))
+        .collect();
+    
+    let excluded = total - remaining.len();
+    (remaining, excluded)
+}
+
+/// Describe each of `ids` that is not among the `existing` JTL ids, e.g. "shipping method 10"
+fn missing_references(label: &str, ids: &[i32], existing: &[i64]) -> Vec<String> {
+    ids.iter()
+        .filter(|id| !existing.contains(&i64::from(**id)))
+        .map(|id| format!("{} {}", label, id))
+        .collect()
+}
+
+/// Run `operation` up to `attempts` times, waiting `base_delay` doubled per failed attempt in between
+///
+/// `operation` gets the attempt number starting at 1, `on_retry` the failed attempt,
+/// the wait and the error. Cancelling `cancel_token` ends the wait with the last error.
+async fn retry_with_backoff<T, F, Fut>(
+    attempts: u32,
+    base_delay: TokioDuration,
+    cancel_token: &CancellationToken,
+    mut operation: F,
+    mut on_retry: impl FnMut(u32, TokioDuration, &Error)
+) -> Result<T>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 1;
+    
+    loop {
+        let error = match operation(attempt).await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt >= attempts => return Err(e),
+            Err(e) => e,
+        };
+        
+        let delay = base_delay * (1 << (attempt - 1).min(5));
+        on_retry(attempt, delay, &error);
+        
+        tokio::select! {
+            _ = sleep(delay) => {},
+            _ = cancel_token.cancelled() => return Err(error),
+        }
+        
+        attempt += 1;
+    }
+}
 
 /// Main sync engine
 pub struct SyncEngine {
     conn_manager: ConnectionManager,
     api_client: JtlApiClient,
+    customer_cache: CustomerCache, // Reset at the start of every shop sync
+    jtl_version: Option<String>,   // Detected once per engine
+    cancel_token: CancellationToken, // Interrupts in-flight requests on abort
+    force_update: bool,            // Update existing JTL orders instead of skipping them
+    strict_preflight: bool,        // Abort a shop sync if JTL reference data is missing
+    run_timeout: Option<TokioDuration>, // Abort a shop sync that runs longer than this
+    connection_retries: u32,       // Extra attempts for the initial connection and order fetch
 }
 
 impl SyncEngine {
@@ -29,19 +160,210 @@ impl SyncEngine {
         SyncEngine {
             conn_manager: ConnectionManager::new(),
             api_client: JtlApiClient::new(api_key),
+            customer_cache: CustomerCache::new(),
+            jtl_version: None,
+            cancel_token: abort_token(),
+            force_update: false,
+            strict_preflight: false,
+            run_timeout: None,
+            connection_retries: 0,
         }
     }
     
+    /// Create a sync engine configured from the application settings
+    pub fn from_settings(api_key: &str, settings: &AppSettings) -> Self {
+        let mut engine = SyncEngine::new(api_key)
+            .with_run_timeout(settings.run_timeout_secs)
+            .with_connection_retries(settings.connection_retries)
+            .with_strict_preflight(settings.strict_preflight);
+        engine.api_client = JtlApiClient::from_settings(api_key, settings);
+        engine
+    }
+    
+    /// Update orders that already exist in JTL instead of skipping them
+    pub fn with_force_update(mut self, force_update: bool) -> Self {
+        self.force_update = force_update;
+        self
+    }
+    
+    /// Abort shop syncs when the preflight check finds missing JTL reference data
+    pub fn with_strict_preflight(mut self, strict: bool) -> Self {
+        self.strict_preflight = strict;
+        self
+    }
+    
+    /// Abort shop syncs that run longer than `timeout_secs`
+    pub fn with_run_timeout(mut self, timeout_secs: Option<u64>) -> Self {
+        self.run_timeout = timeout_secs.filter(|secs| *secs > 0).map(TokioDuration::from_secs);
+        self
+    }
+    
+    /// Retry connecting to a shop and fetching its orders up to `retries` more times
+    pub fn with_connection_retries(mut self, retries: u32) -> Self {
+        self.connection_retries = retries;
+        self
+    }
+    
+    /// Connect to the shop database and fetch its orders, retrying with backoff
+    ///
+    /// Covers a database that is briefly down at the start of a run; failures
+    /// while processing orders are handled per order instead.
+    async fn fetch_orders_with_retry<R: Runtime>(
+        &mut self,
+        app_handle: &AppHandle<R>,
+        shop: &ShopConfig,
+        since: DateTime<Utc>
+    ) -> Result<(Arc<Pool>, Vec<VirtueMartOrder>)> {
+        let attempts = self.connection_retries + 1;
+        let cancel_token = self.cancel_token.clone();
+        let conn_manager = &mut self.conn_manager;
+        
+        retry_with_backoff(
+            attempts,
+            TokioDuration::from_secs(CONNECTION_RETRY_BASE_DELAY_SECS),
+            &cancel_token,
+            |attempt| {
+                // A broken pool must not be reused by the next attempt
+                if attempt > 1 {
+                    conn_manager.remove_pool(&shop.id);
+                }
+                
+                let pool = conn_manager.get_joomla_pool_checked(shop);
+                let shop = shop.clone();
+                async move {
+                    let pool = pool?;
+                    let orders = run_blocking({
+                        let pool = pool.clone();
+                        move || get_orders_since(&pool, &shop, since)
+                    }).await?;
+                    Ok((pool, orders))
+                }
+            },
+            |attempt, delay, error| {
+                warn!("Loading orders for shop '{}' failed (attempt {}/{}), retrying in {}s: {}", 
+                      shop.name, attempt, attempts, delay.as_secs(), error);
+                
+                let _ = emit_log(app_handle, LogEntry {
+                    timestamp: Utc::now(),
+                    message: format!("Could not load orders for shop '{}' (attempt {}/{}), retrying in {}s: {}", shop.name, attempt, attempts, delay.as_secs(), error),
+                    level: "warn".to_string(),
+                    category: "sync".to_string(),
+                    shop_id: Some(shop.id.clone()),
+                });
+            },
+        ).await
+    }
+    
+    /// Mark a run as aborted because it exceeded the run timeout
+    fn abort_on_timeout<R: Runtime>(&self, app_handle: &AppHandle<R>, shop: &ShopConfig, stats: &mut SyncStats) -> Result<()> {
+        let timeout_secs = self.run_timeout.map(|t| t.as_secs()).unwrap_or_default();
+        warn!("Synchronization for shop '{}' exceeded the run timeout of {} seconds", shop.name, timeout_secs);
+        
+        let _ = emit_log(app_handle, LogEntry {
+            timestamp: Utc::now(),
+            message: format!("Synchronization for shop '{}' aborted after exceeding the run timeout of {} seconds", shop.name, timeout_secs),
+            level: "warn".to_string(),
+            category: "sync".to_string(),
+            shop_id: Some(shop.id.clone()),
+        });
+        
+        mark_interrupted(stats, Interruption::TimedOut, self.run_timeout);
+        
+        update_sync_stats(stats.clone());
+        app_handle.emit("sync-stats-update", (shop.id.clone(), stats.clone()))
+            .map_err(|e| Error::System(format!("Failed to emit event: {}", e)))?;
+        Ok(())
+    }
+    
+    /// Mark a run as aborted on user request
+    fn abort_on_user_request<R: Runtime>(&self, app_handle: &AppHandle<R>, shop: &ShopConfig, stats: &mut SyncStats, message: String) -> Result<()> {
+        let _ = emit_log(app_handle, LogEntry {
+            timestamp: Utc::now(),
+            message,
+            level: "warn".to_string(),
+            category: "sync".to_string(),
+            shop_id: Some(shop.id.clone()),
+        });
+        
+        mark_interrupted(stats, Interruption::Aborted, self.run_timeout);
+        
+        update_sync_stats(stats.clone());
+        app_handle.emit("sync-stats-update", (shop.id.clone(), stats.clone()))
+            .map_err(|e| Error::System(format!("Failed to emit event: {}", e)))?;
+        Ok(())
+    }
+    
+    /// Check that the JTL reference data used for orders exists
+    ///
+    /// Returns a description of every missing reference; lists that can't be
+    /// fetched are skipped with a warning
+    pub async fn preflight(&self, shop: &ShopConfig) -> Vec<String> {
+        let checks = [
+            ("paymentMethods", "payment method", jtl_payment_method_ids()),
+            ("shippingMethods", "shipping method", vec![shipping_method_id(shop)]),
+            ("companies", "company", vec![shop.jtl_company.company_id]),
+            ("customerGroups", "customer group", vec![JTL_CUSTOMER_GROUP_ID]),
+        ];
+        
+        let mut missing = Vec::new();
+        for (resource, label, ids) in checks {
+            match self.api_client.list_reference_ids(resource).await {
+                Ok(existing) => missing.extend(missing_references(label, &ids, &existing)),
+                Err(e) => warn!("Could not verify JTL {} IDs for shop '{}': {}", label, shop.name, e),
+            }
+        }
+        
+        missing
+    }
+    
+    /// Detect the JTL API version once and warn if it is outside the supported range
+    async fn check_jtl_version<R: Runtime>(&mut self, app_handle: &AppHandle<R>) {
+        if self.jtl_version.is_some() {
+            return;
+        }
+        
+        match self.api_client.get_api_version().await {
+            Ok(version) => {
+                if is_supported_version(&version) {
+                    info!("Detected JTL API version {}", version);
+                } else {
+                    warn!("JTL API version {} is outside the supported range {} - {}", 
+                          version, MIN_SUPPORTED_JTL_VERSION, MAX_SUPPORTED_JTL_VERSION);
+                    
+                    let _ = emit_log(app_handle, LogEntry {
+                        timestamp: Utc::now(),
+                        message: format!("JTL API version {} is not supported (supported: {} up to {}), requests may fail", 
+                                         version, MIN_SUPPORTED_JTL_VERSION, MAX_SUPPORTED_JTL_VERSION),
+                        level: "warn".to_string(),
+                        category: "system".to_string(),
+                        shop_id: None,
+                    });
+                }
+                self.jtl_version = Some(version);
+            },
+            Err(e) => warn!("Could not detect JTL API version: {}", e),
+        }
+    }
+    
+    /// Get the JTL API version detected during the last sync, if any
+    pub fn jtl_version(&self) -> Option<&str> {
+        self.jtl_version.as_deref()
+    }
+    
     /// Synchronize multiple shops sequentially
+    ///
+    /// Every listed shop is synced, disabled ones included; `sync_all_shops` only
+    /// lists enabled shops. Archived shops are always skipped.
+    /// A failing shop doesn't stop the others, its error is part of the returned summary.
     pub async fn sync_multiple_shops<R: Runtime>(
         &mut self,
         app_handle: &AppHandle<R>,
         config: &AppConfig,
         shop_ids: Vec<String>
-    ) -> Result<()> {
+    ) -> Result<MultiSyncSummary> {
         info!("Starting sequential synchronization for {} shops", shop_ids.len());
 
-        let _ = app_handle.emit("log", LogEntry {
+        let _ = emit_log(app_handle, LogEntry {
             timestamp: Utc::now(),
             message: format!("Starting sequential synchronization for {} shops", shop_ids.len()),
             level: "info".to_string(),
@@ -52,6 +374,12 @@ impl SyncEngine {
         // Reset abort flag before starting
         reset_abort_flag();
 
+        let started = std::time::Instant::now();
+        let mut summary = MultiSyncSummary {
+            total_shops: shop_ids.len(),
+            ..Default::default()
+        };
+
         // Sync each shop in sequence
         for shop_id in shop_ids {
             // Find the shop config
@@ -59,21 +387,35 @@ impl SyncEngine {
                 Some(s) => s.clone(),
                 None => {
                     let error_msg = format!("Shop with ID '{}' not found", shop_id);
-                    let _ = app_handle.emit("log", LogEntry {
+                    let _ = emit_log(app_handle, LogEntry {
                         timestamp: Utc::now(),
                         message: error_msg.clone(),
                         level: "error".to_string(),
                         category: "sync".to_string(),
                         shop_id: Some(shop_id.clone()),
                     });
+                    summary.record(shop_id, Err(error_msg));
                     continue; // Skip this shop and move to the next one
                 }
             };
             
+            if let Some(reason) = shop_skip_reason(&shop) {
+                info!("Skipping {} shop '{}'", reason, shop.name);
+                let _ = emit_log(app_handle, LogEntry {
+                    timestamp: Utc::now(),
+                    message: format!("Shop '{}' skipped: {}", shop.name, reason),
+                    level: "info".to_string(),
+                    category: "sync".to_string(),
+                    shop_id: Some(shop_id.clone()),
+                });
+                summary.skipped += 1;
+                continue;
+            }
+            
             // Get the sync hours for this shop (default to 24 if not set)
-            let sync_hours = get_shop_stats(&shop_id).sync_hours;
+            let sync_hours = shop.resolve_sync_hours(None);
             
-            let _ = app_handle.emit("log", LogEntry {
+            let _ = emit_log(app_handle, LogEntry {
                 timestamp: Utc::now(),
                 message: format!("Starting synchronization for shop '{}' with {}h timeframe", shop.name, sync_hours),
                 level: "info".to_string(),
@@ -85,11 +427,12 @@ impl SyncEngine {
             match self.sync_shop(app_handle, &shop, sync_hours).await {
                 Ok(stats) => {
                     update_sync_stats(stats.clone());
+                    send_completion_webhook(config, &stats);
                     
                     // Send events for completion
                     let _ = app_handle.emit("sync-complete", stats.clone());
                     
-                    let _ = app_handle.emit("log", LogEntry {
+                    let _ = emit_log(app_handle, LogEntry {
                         timestamp: Utc::now(),
                         message: format!("Synchronization completed for shop '{}': {} synced, {} skipped, {} errors", 
                                       shop.name, stats.synced_orders, stats.skipped_orders, stats.error_orders),
@@ -97,26 +440,29 @@ impl SyncEngine {
                         category: "sync".to_string(),
                         shop_id: Some(shop.id.clone()),
                     });
+                    
+                    summary.record(shop.id.clone(), Ok(stats));
                 },
                 Err(e) => {
                     // Log error but continue with next shop
                     let _ = app_handle.emit("sync-error", (e.to_string(), shop.id.clone()));
-                    let _ = app_handle.emit("log", LogEntry {
+                    let _ = emit_log(app_handle, LogEntry {
                         timestamp: Utc::now(),
                         message: format!("Synchronization failed for shop '{}': {}", shop.name, e),
                         level: "error".to_string(),
                         category: "sync".to_string(),
                         shop_id: Some(shop.id.clone()),
                     });
+                    summary.record(shop.id.clone(), Err(e.to_string()));
                 }
             }
             
             // Brief pause between shop syncs
-            sleep(TokioDuration::from_millis(500)).await;
+            sleep(TokioDuration::from_millis(config.settings.inter_shop_delay_ms)).await;
             
             // Check for abort between shop syncs
             if should_abort() {
-                let _ = app_handle.emit("log", LogEntry {
+                let _ = emit_log(app_handle, LogEntry {
                     timestamp: Utc::now(),
                     message: "Multi-shop synchronization aborted by user".to_string(),
                     level: "warn".to_string(),
@@ -124,20 +470,28 @@ impl SyncEngine {
                     shop_id: None,
                 });
                 
-                return Ok(());
+                summary.aborted = true;
+                break;
             }
         }
         
-        // All shops synced
-        let _ = app_handle.emit("log", LogEntry {
-            timestamp: Utc::now(),
-            message: "Sequential synchronization of all selected shops completed".to_string(),
-            level: "info".to_string(),
-            category: "sync".to_string(),
-            shop_id: None,
-        });
+        if !summary.aborted {
+            // All shops synced
+            let _ = emit_log(app_handle, LogEntry {
+                timestamp: Utc::now(),
+                message: "Sequential synchronization of all selected shops completed".to_string(),
+                level: "info".to_string(),
+                category: "sync".to_string(),
+                shop_id: None,
+            });
+        }
         
-        Ok(())
+        summary.duration_ms = started.elapsed().as_millis() as u64;
+        info!("Multi-shop summary: {} shops, {} succeeded, {} failed, {} skipped, aborted: {}, {}ms",
+              summary.total_shops, summary.succeeded, summary.failed, summary.skipped, summary.aborted, summary.duration_ms);
+        let _ = app_handle.emit("multi-sync-summary", summary.clone());
+        
+        Ok(summary)
     }
     
     /// Synchronize a single shop
@@ -149,23 +503,114 @@ impl SyncEngine {
     ) -> Result<SyncStats> {
         info!("Starting synchronization Joomla -> JTL for shop '{}' with {}h timeframe", shop.name, hours);
 
-        let _ = app_handle.emit("log", LogEntry {
+        let since = Utc::now() - ChronoDuration::hours(hours as i64);
+        self.sync_shop_since(app_handle, shop, since, hours).await
+    }
+    
+    /// Synchronize a single shop starting from an absolute timestamp
+    ///
+    /// `sync_hours` is only recorded in the stats, the order window is bounded by `since`
+    pub async fn sync_shop_since<R: Runtime>(
+        &mut self,
+        app_handle: &AppHandle<R>,
+        shop: &ShopConfig,
+        since: DateTime<Utc>,
+        sync_hours: i32
+    ) -> Result<SyncStats> {
+        // Only one sync per shop at a time, otherwise orders could be created twice
+        let _shop_guard = ShopSyncGuard::acquire(&shop.id)?;
+        
+        let started = std::time::Instant::now();
+        let result = self.run_shop_sync(app_handle, shop, since, sync_hours).await;
+        
+        match &result {
+            // Finished runs feed the sync time estimate
+            Ok(stats) => record_sync_run(&shop.id, SyncRun {
+                finished_at: Utc::now(),
+                duration_ms: started.elapsed().as_millis() as u64,
+                order_count: stats.total_orders.max(0) as usize,
+                aborted: stats.aborted,
+            }),
+            // Failed runs keep their reason in the stats for the dashboard
+            Err(e) => set_last_error(&shop.id, &e.to_string()),
+        }
+        
+        result
+    }
+    
+    /// Run the synchronization of a shop, the caller holds the shop's sync guard
+    async fn run_shop_sync<R: Runtime>(
+        &mut self,
+        app_handle: &AppHandle<R>,
+        shop: &ShopConfig,
+        since: DateTime<Utc>,
+        sync_hours: i32
+    ) -> Result<SyncStats> {
+        info!("Starting synchronization Joomla -> JTL for shop '{}' since {}", shop.name, since);
+        
+        // Pick up the token of the current run so an abort interrupts pending requests
+        self.cancel_token = abort_token();
+
+        let _ = emit_log(app_handle, LogEntry {
             timestamp: Utc::now(),
-            message: format!("Starting synchronization process for shop '{}' with {}h timeframe...", shop.name, hours),
+            message: format!("Starting synchronization process for shop '{}' with orders since {}...", shop.name, since.format("%Y-%m-%d %H:%M:%S")),
             level: "info".to_string(),
             category: "sync".to_string(),
             shop_id: Some(shop.id.clone()),
         });
 
-        // Get database connection
-        let pool = self.conn_manager.get_joomla_pool(shop)?;
+        self.check_jtl_version(app_handle).await;
+
+        // Make sure the ids used for every order exist before processing any
+        let missing = self.preflight(shop).await;
+        if !missing.is_empty() {
+            let message = format!("Missing JTL reference data for shop '{}': {}", shop.name, missing.join(", "));
+            
+            let _ = emit_log(app_handle, LogEntry {
+                timestamp: Utc::now(),
+                message: message.clone(),
+                level: if self.strict_preflight { "error" } else { "warn" }.to_string(),
+                category: "sync".to_string(),
+                shop_id: Some(shop.id.clone()),
+            });
+            
+            if self.strict_preflight {
+                error!("{}", message);
+                return Err(Error::Sync(message));
+            }
+            warn!("{}", message);
+        }
+
+        // Customer ids must not leak into later runs
+        self.customer_cache.clear();
 
-        // Get orders within timeframe
-        let orders = get_orders_within_timeframe(&pool, shop, hours)?;
+        // Get database connection and the orders since the requested time
+        let (pool, orders) = self.fetch_orders_with_retry(app_handle, shop, since).await?;
         
         let total_orders = orders.len();
         
-        let _ = app_handle.emit("log", LogEntry {
+        // Mismatches are reported per run, a clean run leaves none behind
+        clear_reconciliation_issues(&shop.id);
+        
+        // Drop orders JTL already holds up front, existing orders are still needed for forced updates
+        let (orders, already_in_jtl) = if self.force_update || orders.is_empty() {
+            (orders, 0)
+        } else {
+            match self.api_client.list_external_order_numbers(since).await {
+                Ok(existing) => exclude_existing_orders(orders, &existing, shop),
+                Err(e) => {
+                    // Orders are still checked one by one while processing
+                    warn!("Could not list existing JTL orders for shop '{}', checking each order: {}", shop.name, e);
+                    (orders, 0)
+                }
+            }
+        };
+        
+        if already_in_jtl > 0 {
+            info!("{} of {} orders for shop '{}' already exist in JTL", already_in_jtl, total_orders, shop.name);
+        }
+        
+        let _ = emit_log(app_handle, LogEntry {
             timestamp: Utc::now(),
             message: format!("Found {} orders to process for shop '{}'", total_orders, shop.name),
             level: "info".to_string(),
@@ -178,12 +623,20 @@ impl SyncEngine {
             shop_id: shop.id.clone(),
             total_orders: total_orders as i32,
             synced_orders: 0,
-            skipped_orders: 0,
+            skipped_orders: already_in_jtl as i32,
             error_orders: 0,
             last_sync_time: Some(Utc::now()),
             next_scheduled_run: None,
             aborted: false,
-            sync_hours: hours,
+            sync_hours,
+            orders_missing_billing: 0,
+            empty_orders: 0,
+            updated_orders: 0,
+            last_error: None,
+            skipped_test_orders: 0,
+            skipped_zero_total: 0,
+            failures_by_category: HashMap::new(),
+            abort_reason: None,
         };
         
         update_sync_stats(stats.clone());
@@ -191,7 +644,7 @@ impl SyncEngine {
             .map_err(|e| Error::System(format!("Failed to emit event: {}", e)))?;
             
         if orders.is_empty() {
-            info!("No new orders in the past {} hours for shop '{}'", hours, shop.name);
+            info!("No new orders since {} for shop '{}'", since, shop.name);
             
             app_handle.emit("sync-complete", stats.clone())
                 .map_err(|e| Error::System(format!("Failed to emit event: {}", e)))?;
@@ -199,28 +652,97 @@ impl SyncEngine {
             return Ok(stats);
         }
         
+        // Aborts and the deadline also interrupt the lookups of an order still being processed
+        let interrupt = RunInterrupt::new(self.cancel_token.clone(), self.run_timeout);
+        
         // Process each order
         for order in orders {
-            if should_abort() {
-                info!("Synchronization aborted, stopping after current order for shop '{}'", shop.name);
+            if should_pause() && !should_abort() {
+                info!("Synchronization paused for shop '{}'", shop.name);
                 
-                let _ = app_handle.emit("log", LogEntry {
+                let _ = emit_log(app_handle, LogEntry {
                     timestamp: Utc::now(),
-                    message: format!("Synchronization for shop '{}' aborted on user request", shop.name),
-                    level: "warn".to_string(),
+                    message: format!("Synchronization for shop '{}' paused", shop.name),
+                    level: "info".to_string(),
                     category: "sync".to_string(),
                     shop_id: Some(shop.id.clone()),
                 });
                 
-                // Set aborted flag in stats
-                stats.aborted = true;
+                // Wait for resume; an abort or the run timeout ends the wait right away
+                let interruption = interrupt.wait_while_paused(
+                    || should_pause() && !should_abort(),
+                    TokioDuration::from_millis(PAUSE_CHECK_INTERVAL_MS)
+                ).await;
                 
-                update_sync_stats(stats.clone());
-                app_handle.emit("sync-stats-update", (shop.id.clone(), stats.clone()))
-                    .map_err(|e| Error::System(format!("Failed to emit event: {}", e)))?;
+                if interruption.is_none() && !should_abort() {
+                    info!("Synchronization resumed for shop '{}'", shop.name);
                     
+                    let _ = emit_log(app_handle, LogEntry {
+                        timestamp: Utc::now(),
+                        message: format!("Synchronization for shop '{}' resumed", shop.name),
+                        level: "info".to_string(),
+                        category: "sync".to_string(),
+                        shop_id: Some(shop.id.clone()),
+                    });
+                }
+            }
+            
+            if should_abort() {
+                info!("Synchronization aborted, stopping after current order for shop '{}'", shop.name);
+                self.abort_on_user_request(app_handle, shop, &mut stats,
+                    format!("Synchronization for shop '{}' aborted on user request", shop.name))?;
                 break;
             }
+            
+            if interrupt.check() == Some(Interruption::TimedOut) {
+                self.abort_on_timeout(app_handle, shop, &mut stats)?;
+                break;
+            }
+
+            // Orders from test customers never go to JTL
+            if shop.is_test_order(&order) {
+                stats.skipped_test_orders += 1;
+                skip_order(app_handle, shop, &mut stats, "info",
+                           format!("Order {} for shop '{}' is from a test customer, skipped", order.order_number, shop.name))?;
+                continue;
+            }
+
+            // Free or refunded orders are rarely meant for JTL
+            if shop.is_non_positive_total(&order) {
+                stats.skipped_zero_total += 1;
+                skip_order(app_handle, shop, &mut stats, "info",
+                           format!("Order {} for shop '{}' has a total of {:.2}, skipped", 
+                                   order.order_number, shop.name, order.order_total))?;
+                continue;
+            }
+
+            // Orders below the shop's minimum total are skipped
+            if shop.is_below_min_total(&order) {
+                skip_order(app_handle, shop, &mut stats, "info",
+                           format!("Order {} for shop '{}' is below the minimum order total ({:.2}), skipped", 
+                                   order.order_number, shop.name, order.order_total))?;
+                continue;
+            }
+
+            // Orders without a BT row are counted and either skipped or synced with minimal data
+            if !order.has_billing_address() {
+                stats.orders_missing_billing += 1;
+                warn!("Order {} for shop '{}' has no billing address", order.order_number, shop.name);
+                
+                if shop.skip_orders_without_billing {
+                    skip_order(app_handle, shop, &mut stats, "warn",
+                               format!("Order {} for shop '{}' has no billing address, skipped", order.order_number, shop.name))?;
+                    continue;
+                }
+                
+                let _ = emit_log(app_handle, LogEntry {
+                    timestamp: Utc::now(),
+                    message: format!("Order {} for shop '{}' has no billing address, syncing with minimal data", order.order_number, shop.name),
+                    level: "warn".to_string(),
+                    category: "sync".to_string(),
+                    shop_id: Some(shop.id.clone()),
+                });
+            }
 
             info!("Processing order: ID={}, Shop={}, Customer={} {}", 
                   order.virtuemart_order_id,
@@ -228,7 +750,7 @@ impl SyncEngine {
                   order.first_name.as_deref().unwrap_or(""), 
                   order.last_name.as_deref().unwrap_or(""));
             
-            let _ = app_handle.emit("log", LogEntry {
+            let _ = emit_log(app_handle, LogEntry {
                 timestamp: Utc::now(),
                 message: format!("Processing order {} for shop '{}', customer: {} {}", 
                     order.order_number,
@@ -241,38 +763,86 @@ impl SyncEngine {
                 shop_id: Some(shop.id.clone()),
             });
 
-            match process_order(&self.api_client, &pool, &order, shop).await {
-                Ok(processed) => {
-                    if processed {
-                        stats.synced_orders += 1;
+            let result = process_order(&self.api_client, &pool, &order, shop, &mut self.customer_cache, self.force_update, &interrupt).await;
+            
+            // Interrupted orders never reached JTL, the next run picks them up again
+            match &result {
+                Ok(OrderResult::Interrupted(Interruption::Aborted)) => {
+                    info!("Synchronization aborted while processing order {} for shop '{}'", order.order_number, shop.name);
+                    self.abort_on_user_request(app_handle, shop, &mut stats,
+                        format!("Synchronization for shop '{}' aborted on user request while processing order {}", shop.name, order.order_number))?;
+                    break;
+                },
+                Ok(OrderResult::Interrupted(Interruption::TimedOut)) => {
+                    info!("Run timeout reached while processing order {} for shop '{}'", order.order_number, shop.name);
+                    self.abort_on_timeout(app_handle, shop, &mut stats)?;
+                    break;
+                },
+                _ => {}
+            }
 
-                        let _ = app_handle.emit("log", LogEntry {
-                            timestamp: Utc::now(),
-                            message: format!("Successfully synchronized order {} for shop '{}'", order.order_number, shop.name),
-                            level: "info".to_string(),
-                            category: "sync".to_string(),
-                            shop_id: Some(shop.id.clone()),
-                        });
+            // Keep failed orders for inspection until a later run processes them
+            match &result {
+                Ok(_) => clear_failed_order(&shop.id, order.virtuemart_order_id),
+                Err(e) => record_failed_order(&shop.id, &order, &e.to_string()),
+            }
 
-                        info!("Order {} successfully synchronized for shop '{}'", order.order_number, shop.name);
-                    } else {
-                        stats.skipped_orders += 1;
+            match result {
+                Ok(OrderResult::Created(jtl_order_id)) => {
+                    stats.synced_orders += 1;
 
-                        let _ = app_handle.emit("log", LogEntry {
-                            timestamp: Utc::now(),
-                            message: format!("Order {} for shop '{}' already exists, skipped", order.order_number, shop.name),
-                            level: "warn".to_string(),
-                            category: "sync".to_string(),
-                            shop_id: Some(shop.id.clone()),
-                        });
+                    let _ = app_handle.emit("order-created", OrderCreatedEvent {
+                        shop_id: shop.id.clone(),
+                        external_number: external_order_number(&order, shop),
+                        jtl_order_id,
+                    });
 
-                        info!("Order {} skipped (already exists) for shop '{}'", order.order_number, shop.name);
+                    let _ = emit_log(app_handle, LogEntry {
+                        timestamp: Utc::now(),
+                        message: format!("Successfully synchronized order {} for shop '{}'", order.order_number, shop.name),
+                        level: "info".to_string(),
+                        category: "sync".to_string(),
+                        shop_id: Some(shop.id.clone()),
+                    });
+
+                    info!("Order {} successfully synchronized for shop '{}'", order.order_number, shop.name);
+                },
+                Ok(OrderResult::Updated(jtl_order_id)) => {
+                    stats.updated_orders += 1;
+
+                    let _ = emit_log(app_handle, LogEntry {
+                        timestamp: Utc::now(),
+                        message: format!("Order {} for shop '{}' already exists, updated", order.order_number, shop.name),
+                        level: "info".to_string(),
+                        category: "sync".to_string(),
+                        shop_id: Some(shop.id.clone()),
+                    });
+
+                    info!("Order {} updated (force update, JTL ID: {}) for shop '{}'", order.order_number, jtl_order_id, shop.name);
+                },
+                Ok(OrderResult::Skipped(reason)) => {
+                    stats.skipped_orders += 1;
+                    if reason == SkipReason::NoLineItems {
+                        stats.empty_orders += 1;
                     }
+
+                    let _ = emit_log(app_handle, LogEntry {
+                        timestamp: Utc::now(),
+                        message: format!("Order {} for shop '{}' skipped: {}", order.order_number, shop.name, reason.as_str()),
+                        level: "warn".to_string(),
+                        category: "sync".to_string(),
+                        shop_id: Some(shop.id.clone()),
+                    });
+
+                    info!("Order {} skipped ({}) for shop '{}'", order.order_number, reason.as_str(), shop.name);
                 },
+                Ok(OrderResult::Interrupted(_)) => unreachable!("interrupted orders end the run above"),
                 Err(e) => {
                     stats.error_orders += 1;
+                    *stats.failures_by_category.entry(e.category().to_string()).or_insert(0) += 1;
+                    stats.last_error = Some(format!("Order {}: {}", order.order_number, e));
 
-                    let _ = app_handle.emit("log", LogEntry {
+                    let _ = emit_log(app_handle, LogEntry {
                         timestamp: Utc::now(),
                         message: format!("Error processing order {} for shop '{}': {}", order.order_number, shop.name, e),
                         level: "error".to_string(),
@@ -289,11 +859,12 @@ impl SyncEngine {
                 .map_err(|e| Error::System(format!("Failed to emit event: {}", e)))?;
 
             // Track progress
-            info!("Progress for shop '{}': {}/{} (synced: {}, skipped: {}, errors: {})", 
+            info!("Progress for shop '{}': {}/{} (synced: {}, updated: {}, skipped: {}, errors: {})", 
                 shop.name,
-                stats.synced_orders + stats.skipped_orders + stats.error_orders,
+                stats.synced_orders + stats.updated_orders + stats.skipped_orders + stats.error_orders,
                 total_orders,
                 stats.synced_orders,
+                stats.updated_orders,
                 stats.skipped_orders,
                 stats.error_orders
             );
@@ -305,7 +876,10 @@ impl SyncEngine {
             }
 
             // Brief pause between orders to prevent overwhelming the server
-            sleep(TokioDuration::from_millis(150)).await;
+            tokio::select! {
+                _ = sleep(TokioDuration::from_millis(150)) => {},
+                _ = self.cancel_token.cancelled() => {},
+            }
         }
         
         // Summarize results
@@ -320,7 +894,7 @@ impl SyncEngine {
         app_handle.emit("sync-process-complete", (shop.id.clone(), stats.clone()))
             .map_err(|e| Error::System(format!("Failed to emit process complete event: {}", e)))?;
         
-        let _ = app_handle.emit("log", LogEntry {
+        let _ = emit_log(app_handle, LogEntry {
             timestamp: Utc::now(),
             message: format!("Sync completed for shop '{}': {} synced, {} skipped, {} errors", 
                 shop.name, stats.synced_orders, stats.skipped_orders, stats.error_orders),
@@ -331,4 +905,160 @@ impl SyncEngine {
 
         Ok(stats)
 			}
-		}
\ No newline at end of file
+		}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn interruptions_mark_stats_aborted_with_reason() {
+        let mut stats = SyncStats::default();
+        mark_interrupted(&mut stats, Interruption::Aborted, None);
+        assert!(stats.aborted);
+        assert_eq!(stats.abort_reason.as_deref(), Some(USER_ABORT_REASON));
+        
+        let mut stats = SyncStats::default();
+        mark_interrupted(&mut stats, Interruption::TimedOut, Some(TokioDuration::from_secs(90)));
+        assert!(stats.aborted);
+        assert_eq!(stats.abort_reason.as_deref(), Some("Run timeout of 90 seconds exceeded"));
+    }
+    
+    #[tokio::test]
+    async fn cancelling_mid_request_returns_promptly_with_aborted_stat() {
+        let token = CancellationToken::new();
+        let interrupt = RunInterrupt::new(token.clone(), None);
+        token.cancel();
+        
+        let mut stats = SyncStats::default();
+        let result = interrupt.run(std::future::pending::<()>()).await;
+        if let Err(interruption) = result {
+            mark_interrupted(&mut stats, interruption, None);
+        }
+        
+        assert!(stats.aborted);
+        assert_eq!(stats.abort_reason.as_deref(), Some(USER_ABORT_REASON));
+    }
+    
+    #[test]
+    fn disabled_shops_are_skipped_by_sync_all_but_not_individually() {
+        let mut config = AppConfig::default();
+        let mut disabled = ShopConfig::new("Disabled Shop");
+        disabled.enabled = false;
+        config.shops.push(disabled.clone());
+        
+        assert!(!config.enabled_shop_ids().contains(&disabled.id));
+        assert_eq!(shop_skip_reason(&disabled), None);
+        
+        disabled.archived = true;
+        assert_eq!(shop_skip_reason(&disabled), Some("archived"));
+    }
+    
+    #[test]
+    fn excludes_orders_already_known_to_jtl() {
+        let shop = ShopConfig::new("Test Shop");
+        let orders: Vec<VirtueMartOrder> = (1..=3).map(|id| VirtueMartOrder {
+            virtuemart_order_id: id,
+            order_number: format!("ORD{}", id),
+            ..Default::default()
+        }).collect();
+        let existing: HashSet<String> = [external_order_number(&orders[1], &shop), "UNRELATED".to_string()].into();
+        
+        let (remaining, excluded) = exclude_existing_orders(orders, &existing, &shop);
+        
+        assert_eq!(excluded, 1);
+        let ids: Vec<i32> = remaining.iter().map(|o| o.virtuemart_order_id).collect();
+        assert_eq!(ids, vec![1, 3]);
+    }
+    
+    #[test]
+    fn preflight_reports_a_missing_shipping_method() {
+        let mut shop = ShopConfig::new("Test Shop");
+        shop.default_shipping_method_id = Some(42);
+        
+        let missing = missing_references("shipping method", &[shipping_method_id(&shop)], &[10, 11]);
+        assert_eq!(missing, vec!["shipping method 42".to_string()]);
+        
+        assert!(missing_references("shipping method", &[shipping_method_id(&shop)], &[10, 42]).is_empty());
+    }
+    
+    #[tokio::test]
+    async fn run_exceeding_the_timeout_is_aborted() {
+        let engine = SyncEngine::new("test-key").with_run_timeout(Some(1));
+        assert_eq!(engine.run_timeout, Some(TokioDuration::from_secs(1)));
+        assert_eq!(SyncEngine::new("test-key").with_run_timeout(Some(0)).run_timeout, None);
+        
+        let interrupt = RunInterrupt::new(CancellationToken::new(), Some(TokioDuration::from_millis(10)));
+        assert_eq!(interrupt.check(), None);
+        sleep(TokioDuration::from_millis(20)).await;
+        
+        let mut stats = SyncStats::default();
+        if let Some(interruption) = interrupt.check() {
+            mark_interrupted(&mut stats, interruption, engine.run_timeout);
+        }
+        
+        assert!(stats.aborted);
+        assert_eq!(stats.abort_reason.as_deref(), Some("Run timeout of 1 seconds exceeded"));
+    }
+    
+    #[tokio::test]
+    async fn fetch_is_retried_after_a_failure() {
+        let mut calls = 0;
+        let mut retries = Vec::new();
+        
+        let result = retry_with_backoff(
+            3,
+            TokioDuration::from_millis(1),
+            &CancellationToken::new(),
+            |attempt| {
+                calls += 1;
+                async move {
+                    if attempt == 1 {
+                        Err(Error::Database("Connection refused".to_string()))
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            },
+            |attempt, delay, _| retries.push((attempt, delay)),
+        ).await;
+        
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(calls, 2);
+        assert_eq!(retries, vec![(1, TokioDuration::from_millis(1))]);
+    }
+    
+    #[tokio::test]
+    async fn fetch_gives_up_after_the_last_attempt() {
+        let mut calls = 0;
+        
+        let result: Result<()> = retry_with_backoff(
+            2,
+            TokioDuration::from_millis(1),
+            &CancellationToken::new(),
+            |_| {
+                calls += 1;
+                async { Err(Error::Database("Connection refused".to_string())) }
+            },
+            |_, _, _| {},
+        ).await;
+        
+        assert!(matches!(result, Err(Error::Database(_))));
+        assert_eq!(calls, 2);
+    }
+    
+    #[test]
+    fn order_created_event_carries_the_jtl_order_id() {
+        let event = OrderCreatedEvent {
+            shop_id: "shop1".to_string(),
+            external_number: "VM-SHOP1-1001".to_string(),
+            jtl_order_id: "4711".to_string(),
+        };
+        
+        assert_eq!(serde_json::to_value(&event).unwrap(), serde_json::json!({
+            "shop_id": "shop1",
+            "external_number": "VM-SHOP1-1001",
+            "jtl_order_id": "4711",
+        }));
+    }
+}
@@ -0,0 +1,92 @@
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::db::models::VirtueMartOrder;
+use crate::utils::lock::lock_or_recover;
+
+// Number of failed orders kept per shop; the oldest is dropped first
+const MAX_FAILED_ORDERS_PER_SHOP: usize = 100;
+
+/// An order that could not be synced, kept for later inspection
+#[derive(Serialize, Clone)]
+pub struct FailedOrder {
+    pub order: VirtueMartOrder,
+    pub error: String,
+    pub failed_at: DateTime<Utc>,
+}
+
+lazy_static! {
+    // Map of shop_id -> failed orders, oldest first
+    static ref FAILED_ORDERS: Mutex<HashMap<String, Vec<FailedOrder>>> = Mutex::new(HashMap::new());
+}
+
+/// Store a failed order with its error, replacing an earlier failure of the same order
+pub fn record_failed_order(shop_id: &str, order: &VirtueMartOrder, error: &str) {
+    let mut failed = lock_or_recover(&FAILED_ORDERS, "FAILED_ORDERS");
+    let orders = failed.entry(shop_id.to_string()).or_default();
+    
+    orders.retain(|f| f.order.virtuemart_order_id != order.virtuemart_order_id);
+    orders.push(FailedOrder {
+        order: order.clone(),
+        error: error.to_string(),
+        failed_at: Utc::now(),
+    });
+    
+    if orders.len() > MAX_FAILED_ORDERS_PER_SHOP {
+        let excess = orders.len() - MAX_FAILED_ORDERS_PER_SHOP;
+        orders.drain(..excess);
+    }
+}
+
+/// Forget a failed order once it was processed successfully
+pub fn clear_failed_order(shop_id: &str, order_id: i32) {
+    if let Some(orders) = lock_or_recover(&FAILED_ORDERS, "FAILED_ORDERS").get_mut(shop_id) {
+        orders.retain(|f| f.order.virtuemart_order_id != order_id);
+    }
+}
+
+/// Get the failed orders of a shop, oldest first
+pub fn get_failed_orders(shop_id: &str) -> Vec<FailedOrder> {
+    lock_or_recover(&FAILED_ORDERS, "FAILED_ORDERS").get(shop_id).cloned().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn order(id: i32) -> VirtueMartOrder {
+        VirtueMartOrder { virtuemart_order_id: id, ..Default::default() }
+    }
+    
+    #[test]
+    fn failed_orders_are_recorded_replaced_and_cleared() {
+        record_failed_order("failures-shop", &order(1), "Timeout");
+        record_failed_order("failures-shop", &order(2), "Timeout");
+        record_failed_order("failures-shop", &order(1), "HTTP error 500");
+        
+        let failed = get_failed_orders("failures-shop");
+        assert_eq!(failed.len(), 2);
+        assert_eq!(failed[0].order.virtuemart_order_id, 2);
+        assert_eq!(failed[1].error, "HTTP error 500");
+        
+        clear_failed_order("failures-shop", 2);
+        let failed = get_failed_orders("failures-shop");
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].order.virtuemart_order_id, 1);
+        assert!(get_failed_orders("unknown-shop").is_empty());
+    }
+    
+    #[test]
+    fn keeps_only_the_newest_failures_per_shop() {
+        for id in 1..=MAX_FAILED_ORDERS_PER_SHOP as i32 + 5 {
+            record_failed_order("capped-shop", &order(id), "Timeout");
+        }
+        
+        let failed = get_failed_orders("capped-shop");
+        assert_eq!(failed.len(), MAX_FAILED_ORDERS_PER_SHOP);
+        assert_eq!(failed[0].order.virtuemart_order_id, 6);
+    }
+}
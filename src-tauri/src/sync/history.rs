@@ -0,0 +1,119 @@
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::utils::lock::lock_or_recover;
+
+// Number of finished runs kept per shop; the oldest is dropped first
+const MAX_RUNS_PER_SHOP: usize = 20;
+
+// Per-order durations assumed while a shop has no usable history, kept on the slow side
+const DEFAULT_MIN_MS_PER_ORDER: u64 = 500;
+const DEFAULT_MAX_MS_PER_ORDER: u64 = 3000;
+
+/// A finished shop synchronization run
+#[derive(Serialize, Clone)]
+pub struct SyncRun {
+    pub finished_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub order_count: usize,
+    pub aborted: bool,
+}
+
+/// Predicted duration of a sync run
+#[derive(Serialize, Clone)]
+pub struct EstimateResult {
+    pub order_count: usize,
+    pub min_ms: u64,
+    pub expected_ms: u64,
+    pub max_ms: u64,
+    pub based_on_runs: usize, // 0 means the conservative default was used
+}
+
+lazy_static! {
+    // Map of shop_id -> finished runs, oldest first
+    static ref SYNC_HISTORY: Mutex<HashMap<String, Vec<SyncRun>>> = Mutex::new(HashMap::new());
+}
+
+/// Store a finished run of a shop
+pub fn record_sync_run(shop_id: &str, run: SyncRun) {
+    let mut history = lock_or_recover(&SYNC_HISTORY, "SYNC_HISTORY");
+    let runs = history.entry(shop_id.to_string()).or_default();
+    
+    runs.push(run);
+    
+    if runs.len() > MAX_RUNS_PER_SHOP {
+        let excess = runs.len() - MAX_RUNS_PER_SHOP;
+        runs.drain(..excess);
+    }
+}
+
+/// Get the recorded runs of a shop, oldest first
+pub fn get_sync_history(shop_id: &str) -> Vec<SyncRun> {
+    lock_or_recover(&SYNC_HISTORY, "SYNC_HISTORY").get(shop_id).cloned().unwrap_or_default()
+}
+
+/// Estimate how long syncing `order_count` orders takes from earlier runs
+///
+/// Aborted runs and runs without orders say nothing about the per-order time and are ignored.
+pub fn estimate_duration(runs: &[SyncRun], order_count: usize) -> EstimateResult {
+    let per_order: Vec<f64> = runs.iter()
+        .filter(|run| !run.aborted && run.order_count > 0)
+        .map(|run| run.duration_ms as f64 / run.order_count as f64)
+        .collect();
+    
+    if per_order.is_empty() {
+        return EstimateResult {
+            order_count,
+            min_ms: DEFAULT_MIN_MS_PER_ORDER * order_count as u64,
+            expected_ms: DEFAULT_MAX_MS_PER_ORDER * order_count as u64,
+            max_ms: DEFAULT_MAX_MS_PER_ORDER * order_count as u64,
+            based_on_runs: 0,
+        };
+    }
+    
+    let average = per_order.iter().sum::<f64>() / per_order.len() as f64;
+    let fastest = per_order.iter().cloned().fold(f64::INFINITY, f64::min);
+    let slowest = per_order.iter().cloned().fold(0.0, f64::max);
+    let count = order_count as f64;
+    
+    EstimateResult {
+        order_count,
+        min_ms: (fastest * count).round() as u64,
+        expected_ms: (average * count).round() as u64,
+        max_ms: (slowest * count).round() as u64,
+        based_on_runs: per_order.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn run(duration_ms: u64, order_count: usize, aborted: bool) -> SyncRun {
+        SyncRun { finished_at: Utc::now(), duration_ms, order_count, aborted }
+    }
+    
+    #[test]
+    fn estimates_from_usable_runs() {
+        let runs = vec![run(1000, 10, false), run(3000, 10, false), run(100, 50, true), run(500, 0, false)];
+        
+        let estimate = estimate_duration(&runs, 20);
+        
+        assert_eq!(estimate.based_on_runs, 2);
+        assert_eq!(estimate.min_ms, 2000);
+        assert_eq!(estimate.expected_ms, 4000);
+        assert_eq!(estimate.max_ms, 6000);
+    }
+    
+    #[test]
+    fn falls_back_to_defaults_without_history() {
+        let estimate = estimate_duration(&[run(100, 10, true)], 4);
+        
+        assert_eq!(estimate.based_on_runs, 0);
+        assert_eq!(estimate.min_ms, 4 * DEFAULT_MIN_MS_PER_ORDER);
+        assert_eq!(estimate.max_ms, 4 * DEFAULT_MAX_MS_PER_ORDER);
+    }
+}
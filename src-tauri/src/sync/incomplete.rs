@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::Serialize;
+
+use crate::api::jtl::{jtl_id, JtlApiClient};
+use crate::config::shop::ShopConfig;
+use crate::error::Result;
+
+/// A JTL order of a shop that has no line items, e.g. after a crash between order and item creation
+#[derive(Serialize, Clone, Debug)]
+pub struct IncompleteOrder {
+    pub jtl_order_id: String,
+    pub external_number: String,
+    pub sales_order_date: Option<String>,
+}
+
+/// Whether an external order number was created by the sync for this shop
+pub fn is_shop_order_number(number: &str, shop: &ShopConfig) -> bool {
+    if shop.legacy_order_numbers {
+        return number.strip_prefix(shop.order_number_prefix.as_str())
+            .is_some_and(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()));
+    }
+    number.starts_with(&format!("{}-{}-", shop.order_number_prefix, shop.order_number_tag()))
+}
+
+/// Find orders of a shop created in JTL since `since` that have no line items
+///
+/// Orders whose line items can't be fetched are skipped with a warning rather than flagged
+pub async fn find_incomplete_orders(
+    client: &JtlApiClient,
+    shop: &ShopConfig,
+    since: DateTime<Utc>
+) -> Result<Vec<IncompleteOrder>> {
+    let orders = client.list_orders_since(since).await?;
+    
+    let mut incomplete = Vec::new();
+    for order in &orders {
+        let Some(external_number) = order["ExternalNumber"].as_str().filter(|n| is_shop_order_number(n, shop)) else {
+            continue;
+        };
+        let Some(jtl_order_id) = jtl_id(order) else {
+            continue;
+        };
+        
+        match client.get_order_line_items(&jtl_order_id).await {
+            Ok(items) if items.is_empty() => incomplete.push(IncompleteOrder {
+                jtl_order_id,
+                external_number: external_number.to_string(),
+                sales_order_date: order["SalesOrderDate"].as_str().map(str::to_string),
+            }),
+            Ok(_) => {},
+            Err(e) => warn!("Could not check line items of JTL order {} for shop '{}': {}", external_number, shop.name, e),
+        }
+    }
+    
+    info!("Found {} JTL orders without line items for shop '{}'", incomplete.len(), shop.name);
+    Ok(incomplete)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn recognizes_order_numbers_of_the_shop() {
+        let mut shop = ShopConfig::new("Test Shop");
+        shop.id = "shop1".to_string();
+        
+        assert!(is_shop_order_number("VM-SHOP1-42", &shop));
+        assert!(!is_shop_order_number("VM-SHOP2-42", &shop));
+        assert!(!is_shop_order_number("ORDER-42", &shop));
+        
+        shop.legacy_order_numbers = true;
+        assert!(is_shop_order_number("VM42", &shop));
+        assert!(!is_shop_order_number("VM", &shop));
+        assert!(!is_shop_order_number("VM42A", &shop));
+    }
+}
@@ -1,10 +1,19 @@
+pub mod diff;
 pub mod engine;
+pub mod failures;
+pub mod history;
+pub mod incomplete;
+pub mod polling;
 pub mod processor;
+pub mod reconciliation;
+pub mod responses;
+pub mod scheduler;
 pub mod stats;
 
 // Re-export key items for easier use
 pub use engine::SyncEngine;
-pub use stats::{SyncStats, get_shop_stats, update_sync_stats, get_current_stats, update_shop_sync_hours};
+pub use scheduler::ScheduledJob;
+pub use stats::{SyncStats, AggregateStats, MultiSyncSummary, aggregate_stats, get_all_shop_stats, get_shop_stats, update_sync_stats, get_current_stats, update_shop_sync_hours};
 
 // Legacy function exports for backward compatibility
 // Remove the duplicate line below
\ No newline at end of file
@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{Result, Error};
+use crate::utils::lock::lock_or_recover;
+
+/// Shortest allowed polling interval, anything faster just hammers the shop database
+pub const MIN_POLLING_INTERVAL_SECS: u64 = 10;
+
+/// The active polling loop
+#[derive(Serialize, Clone)]
+pub struct PollingJob {
+    pub shop_ids: Vec<String>,
+    pub interval_secs: u64,
+    pub started_at: DateTime<Utc>,
+    #[serde(skip)]
+    token: CancellationToken,
+}
+
+impl PollingJob {
+    /// Token cancelled when polling is stopped
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+}
+
+lazy_static! {
+    // At most one polling loop runs at a time
+    static ref POLLING_JOB: Mutex<Option<PollingJob>> = Mutex::new(None);
+}
+
+/// Register a new polling loop, failing if one is already active
+pub fn start_polling(shop_ids: Vec<String>, interval_secs: u64) -> Result<PollingJob> {
+    if interval_secs < MIN_POLLING_INTERVAL_SECS {
+        return Err(Error::ValidationError(format!("Polling interval must be at least {} seconds", MIN_POLLING_INTERVAL_SECS)));
+    }
+    
+    let mut current = lock_or_recover(&POLLING_JOB, "POLLING_JOB");
+    if current.is_some() {
+        return Err(Error::Sync("Polling is already running".to_string()));
+    }
+    
+    let job = PollingJob {
+        shop_ids,
+        interval_secs,
+        started_at: Utc::now(),
+        token: CancellationToken::new(),
+    };
+    *current = Some(job.clone());
+    Ok(job)
+}
+
+/// Stop the active polling loop; returns the stopped job, if any
+pub fn stop_polling() -> Option<PollingJob> {
+    let job = lock_or_recover(&POLLING_JOB, "POLLING_JOB").take()?;
+    job.token.cancel();
+    Some(job)
+}
+
+/// Get the active polling loop, if any
+pub fn polling_job() -> Option<PollingJob> {
+    lock_or_recover(&POLLING_JOB, "POLLING_JOB").clone()
+}
@@ -1,139 +1,321 @@
+use lazy_static::lazy_static;
 use log::{info, warn};
 use mysql::Pool;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
 
-use crate::api::jtl::JtlApiClient;
+use crate::api::jtl::{jtl_id, JtlApiClient};
 use crate::config::shop::ShopConfig;
+use crate::db::connection::run_blocking;
 use crate::db::joomla::{get_order_items, get_shipping_address};
-use crate::db::models::{VirtueMartOrder, JtlOrder, JtlAddress, JtlOrderItem, JtlCustomer, JtlCountry, JtlPaymentDetails, JtlShippingDetails};
-use crate::error::{Result, Error};
-use crate::utils::mapping::{map_payment_method, create_address_object, get_country_code};
-use crate::utils::format::format_iso_date;
+use crate::db::models::{VirtueMartOrder, VirtueMartOrderItem, JtlOrder, JtlOrderItem, JtlCustomer, JtlAddress, JtlCountry, JtlPaymentDetails, JtlShippingDetails};
+use crate::error::SyncError;
+use crate::utils::abort::{Interruption, RunInterrupt};
+use crate::sync::reconciliation::{reconcile_order, record_reconciliation_issue};
+use crate::sync::responses::record_jtl_response;
+use crate::utils::mapping::{map_payment_method, payment_method_name, create_address_object, resolve_country_code};
+use crate::utils::format::{format_iso_date_in, round_price, sanitize_item_name, truncate_with_ellipsis};
+use crate::utils::lock::lock_or_recover;
 
-/// Process a single order for synchronization
-/// 
-/// Returns Ok(true) if order was successfully synced
-/// Returns Ok(false) if order was skipped (already exists)
-/// Returns Err if there was an error during processing
-pub async fn process_order(
-    client: &JtlApiClient,
-    joomla_conn: &Pool,
-    order: &VirtueMartOrder,
-    shop: &ShopConfig
-) -> Result<bool> {
-    // Create customer number with shop ID prefix for uniqueness between shops
-    let customer_number = format!("VM{}", order.virtuemart_order_userinfo_id.unwrap_or_default().to_string());
-    
-    info!("Customer number from Joomla for shop '{}': {}", shop.name, customer_number);
-    
-    // Get shipping address
-    let shipping_address = get_shipping_address(joomla_conn, shop, order.virtuemart_order_id)?;
-    
-    // Map payment method
-    let jtl_payment_method_id = map_payment_method(order.virtuemart_paymentmethod_id);
-    
-    // Order number with shop ID prefix for uniqueness between shops
-    let order_number = format!("VM{}", order.virtuemart_order_id);
+// JTL reference data used for every order, the company follows the shop's profile
+pub const JTL_CUSTOMER_GROUP_ID: i32 = 1;
+pub const JTL_SHIPPING_METHOD_ID: i32 = 7; // Standard shipping method
 
-    
-    // Check if customer already exists
-    let customer_id = match client.get_customer_by_id(&customer_number).await? {
-        Some(customer) => {
-            info!("Customer {} already exists with ID: {} (Shop: '{}')", 
-                  customer_number, customer["Id"], shop.name);
-            customer["Id"].as_str().unwrap_or("0").to_string()
-        },
-        None => {
-            // Create new customer
-            info!("Creating new customer {} for shop '{}'", customer_number, shop.name);
-            
-            let billing_address = create_address_object(order);
-            let shipping_addr = match &shipping_address {
-                Some(addr) => create_address_object(addr),
-                None => billing_address.clone(),
-            };
-            
-            let customer_data = JtlCustomer {
-                CustomerGroupId: 1,
-                BillingAddress: billing_address,
-                InternalCompanyId: 1,
-                LanguageIso: "DE".to_string(),
-                Shipmentaddress: shipping_addr,
-                CustomerSince: format_iso_date(&order.created_on),
-                Number: customer_number.clone(),
-            };
-            
-            let response = client.create_customer(&customer_data).await?;
-            info!("Customer created with ID: {} for shop '{}'", response["Id"], shop.name);
-            response["Id"].to_string()
+/// JTL shipping method used for the orders of a shop
+pub fn shipping_method_id(shop: &ShopConfig) -> i32 {
+    shop.default_shipping_method_id.unwrap_or(JTL_SHIPPING_METHOD_ID)
+}
+
+/// Customer number -> JTL customer id, valid for a single sync run only
+pub type CustomerCache = HashMap<String, String>;
+
+lazy_static! {
+    // One lock per customer number currently being looked up or created
+    static ref CUSTOMER_LOCKS: Mutex<HashMap<String, Arc<AsyncMutex<()>>>> = Mutex::new(HashMap::new());
+}
+
+/// Holds the lock for a customer number, so concurrent syncs don't create the same customer twice
+///
+/// The entry is removed from the lock map once no other task is waiting for it
+struct CustomerLockGuard {
+    customer_number: String,
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl CustomerLockGuard {
+    async fn acquire(customer_number: &str) -> Self {
+        let lock = lock_or_recover(&CUSTOMER_LOCKS, "CUSTOMER_LOCKS")
+            .entry(customer_number.to_string())
+            .or_default()
+            .clone();
+        
+        CustomerLockGuard {
+            customer_number: customer_number.to_string(),
+            guard: Some(lock.lock_owned().await),
         }
-    };
+    }
+}
+
+impl Drop for CustomerLockGuard {
+    fn drop(&mut self) {
+        // Release the lock before checking whether anyone else still holds it
+        self.guard.take();
+        
+        let mut locks = lock_or_recover(&CUSTOMER_LOCKS, "CUSTOMER_LOCKS");
+        if locks.get(&self.customer_number).is_some_and(|lock| Arc::strong_count(lock) == 1) {
+            locks.remove(&self.customer_number);
+        }
+    }
+}
+
+/// Why an order was not sent to JTL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    AlreadyExists, // JTL already holds the order and no force update was requested
+    NoLineItems,   // The order has no line items and the shop doesn't allow empty orders
+}
+
+impl SkipReason {
+    /// Short description for logs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkipReason::AlreadyExists => "already exists",
+            SkipReason::NoLineItems => "no line items",
+        }
+    }
+}
+
+/// Result of processing a single order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderResult {
+    Created(String),     // JTL id of the new order
+    Updated(String),     // JTL id of the existing order that was updated (force update)
+    Skipped(SkipReason),
+    Interrupted(Interruption), // The run stopped before anything was written to JTL
+}
+
+// What `process_order` returns, also used to leave it from inside the customer lookup
+type OrderReturn = std::result::Result<OrderResult, SyncError>;
+
+/// What to do with an order depending on whether JTL already holds it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OrderAction {
+    Create,
+    Update, // Forced re-sync of an existing order
+    Skip,
+}
+
+fn order_action(order_exists: bool, force_update: bool) -> OrderAction {
+    match (order_exists, force_update) {
+        (false, _) => OrderAction::Create,
+        (true, true) => OrderAction::Update,
+        (true, false) => OrderAction::Skip,
+    }
+}
+
+/// External order number used in JTL for a VirtueMart order
+///
+/// Contains the shop tag, so shops sharing a VirtueMart id space don't collide in one JTL
+pub fn external_order_number(order: &VirtueMartOrder, shop: &ShopConfig) -> String {
+    if shop.legacy_order_numbers {
+        return format!("{}{}", shop.order_number_prefix, order.virtuemart_order_id);
+    }
+    format!("{}-{}-{}", shop.order_number_prefix, shop.order_number_tag(), order.virtuemart_order_id)
+}
+
+/// JTL customer number for a VirtueMart order
+///
+/// Orders without a billing row get a per-order number so they don't share one customer
+pub fn customer_number(order: &VirtueMartOrder, shop: &ShopConfig) -> String {
+    match order.virtuemart_order_userinfo_id {
+        Some(userinfo_id) => format!("{}{}", shop.customer_number_prefix, userinfo_id),
+        None => format!("{}O{}", shop.customer_number_prefix, order.virtuemart_order_id),
+    }
+}
+
+// Appended to shipping and coupon lines of orders without products
+const SERVICE_ONLY_LABEL: &str = " (service only)";
+
+/// Check whether an order without products still carries shipping or coupon charges
+pub fn is_service_only_order(order: &VirtueMartOrder, items: &[VirtueMartOrderItem]) -> bool {
+    let has_shipping = order.order_shipment.is_some_and(|cost| cost > 0.0);
+    let has_coupon = order.coupon_code.as_deref().is_some_and(|code| !code.is_empty());
+    items.is_empty() && (has_shipping || has_coupon)
+}
+
+/// JTL id of a customer from the run cache, resolved with `resolve` and cached on a miss
+///
+/// Failed lookups are not cached, the next order of the customer tries again
+async fn cached_customer_id<E, F, Fut>(
+    cache: &mut CustomerCache,
+    customer_number: &str,
+    shop: &ShopConfig,
+    resolve: F
+) -> std::result::Result<String, E>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = std::result::Result<String, E>>,
+{
+    if let Some(id) = cache.get(customer_number) {
+        info!("Customer {} found in run cache with ID: {} (Shop: '{}')", 
+              customer_number, id, shop.name);
+        return Ok(id.clone());
+    }
     
-    // Check if order already exists
-    if client.check_order_exists(&order_number, &customer_id).await? {
-        warn!("Order {} already exists for shop '{}', skipping", 
-              order_number, shop.name);
-        return Ok(false);
+    let id = resolve().await?;
+    cache.insert(customer_number.to_string(), id.clone());
+    Ok(id)
+}
+
+/// Whether an order is skipped for having no line items
+///
+/// Service-only and empty orders are synced anyway if the shop allows them
+fn skips_empty_order(order: &VirtueMartOrder, items: &[VirtueMartOrderItem], shop: &ShopConfig) -> bool {
+    if !items.is_empty() {
+        return false;
     }
     
-    // Get order items
-    let items = get_order_items(joomla_conn, shop, order.virtuemart_order_id)?;
+    if shop.allow_service_only_orders && is_service_only_order(order, items) {
+        info!("Order {} for shop '{}' has only shipping/coupon charges, syncing them as service lines", 
+              order.virtuemart_order_id, shop.name);
+        false
+    } else if shop.allow_empty_orders {
+        warn!("Order {} for shop '{}' has no line items, syncing anyway (empty orders allowed)", 
+              order.virtuemart_order_id, shop.name);
+        false
+    } else {
+        warn!("Order {} for shop '{}' has no line items, skipping", order.virtuemart_order_id, shop.name);
+        true
+    }
+}
+
+/// Tax contained in the line items, summed per tax rate in ascending rate order
+///
+/// Lines without a net price or with a 0% rate carry no tax and are left out
+pub fn tax_by_rate(items: &[JtlOrderItem]) -> Vec<(f64, f64)> {
+    // Rates are keyed in hundredths of a percent, f64 can't be a map key
+    let mut totals: BTreeMap<i64, (f64, f64)> = BTreeMap::new();
     
-    info!("Found {} order items for shop '{}'", items.len(), shop.name);
+    for item in items.iter().filter(|item| item.TaxRate > 0.0) {
+        let (Some(gross), Some(net)) = (item.SalesPriceGross, item.SalesPriceNet) else {
+            continue;
+        };
+        let entry = totals.entry((item.TaxRate * 100.0).round() as i64).or_insert((item.TaxRate, 0.0));
+        entry.1 += (gross - net) * item.Quantity as f64;
+    }
     
-    // Create JTL order
-    info!("Creating order {} in JTL for shop '{}'", order_number, shop.name);
+    totals.into_values().collect()
+}
+
+/// Zero-priced lines stating the tax amount per rate, so they don't change the order total
+fn tax_summary_lines(items: &[JtlOrderItem], shop: &ShopConfig, currency: &str) -> Vec<JtlOrderItem> {
+    tax_by_rate(items).into_iter().map(|(rate, amount)| JtlOrderItem {
+        Quantity: 1,
+        SalesPriceGross: Some(0.0),
+        TaxRate: 0.0,
+        Name: sanitize_item_name(&shop.line_item_name(&format!(
+            "VAT {}%: {:.*} {}", rate, shop.price_decimals as usize, round_price(amount, shop.price_decimals), currency
+        ))),
+        SalesUnit: "stk".to_string(),
+        SalesPriceNet: Some(0.0),
+        PurchasePriceNet: None,
+        Sku: None,
+    }).collect()
+}
+
+/// Customer note of an order, cut to the shop's comment length limit
+fn order_comment(order: &VirtueMartOrder, shop: &ShopConfig) -> String {
+    let note = order.customer_note.clone().unwrap_or_default();
     
-    let billing_address = create_address_object(order);
-    let shipping_addr = match &shipping_address {
-        Some(addr) => create_address_object(addr),
+    match truncate_with_ellipsis(&note, shop.max_comment_length) {
+        Some(truncated) => {
+            warn!("Comment of order {} truncated from {} to {} characters for shop '{}'", 
+                  order.order_number, note.chars().count(), shop.max_comment_length, shop.name);
+            truncated
+        },
+        None => note,
+    }
+}
+
+/// Currency and conversion factor of an order, the shop currency at factor 1 unless the order has its own
+pub fn order_currency(order: &VirtueMartOrder, shop: &ShopConfig) -> (String, f64) {
+    match &order.currency_iso {
+        Some(currency) => (currency.to_uppercase(), order.currency_factor.unwrap_or(1.0)),
+        None => (shop.jtl_company.currency.to_uppercase(), 1.0),
+    }
+}
+
+/// Build the JTL order and its line items for a VirtueMart order
+///
+/// Pure construction without any IO, shared by syncing and diffing
+pub fn build_jtl_order(
+    order: &VirtueMartOrder,
+    items: &[VirtueMartOrderItem],
+    shipping_address: Option<&VirtueMartOrder>,
+    shop: &ShopConfig,
+    customer_id: i32
+) -> (JtlOrder, Vec<JtlOrderItem>) {
+    let billing_address = create_address_object(order, shop);
+    let shipping_addr = match shipping_address {
+        Some(addr) => create_address_object(addr, shop),
         None => billing_address.clone(),
     };
 
-    info!("CustomerId: {} for shop '{}'", customer_id.clone(), shop.name);
-    info!("ExternalNumber: {} for shop '{}'", order_number.clone(), shop.name);
-    info!("Country: {} ID: {} for shop '{}'", 
-          get_country_code(order.virtuemart_country_id.unwrap_or_default()).unwrap_or_default(), 
-          order.virtuemart_country_id.unwrap_or_default(),
-          shop.name);
+    let order_date = shop.order_date_source.select(order);
+    let (currency, currency_factor) = order_currency(order, shop);
 
     let jtl_order = JtlOrder {
-        CustomerId: customer_id.parse::<i32>().unwrap_or_default(),
-        ExternalNumber: order_number.clone(),
-        CompanyId: 1,
+        CustomerId: customer_id,
+        ExternalNumber: external_order_number(order, shop),
+        CompanyId: shop.jtl_company.company_id,
         DepartureCountry: JtlCountry {
-            CountryISO: "DE".to_string(),
-            CurrencyIso: "EUR".to_string(),
-            CurrencyFactor: 1.0,
+            CountryISO: shop.jtl_company.departure_country_iso.to_uppercase(),
+            CurrencyIso: currency.clone(),
+            CurrencyFactor: currency_factor,
         },
         BillingAddress: billing_address,
         Shipmentaddress: shipping_addr,
-        SalesOrderDate: format_iso_date(&order.created_on),
+        SalesOrderDate: format_iso_date_in(order_date, shop.tz()),
         SalesOrderPaymentDetails: JtlPaymentDetails {
-            PaymentMethodId: jtl_payment_method_id,
-            CurrencyIso: "EUR".to_string(),
-            CurrencyFactor: 1.0,
+            PaymentMethodId: map_payment_method(order.virtuemart_paymentmethod_id),
+            CurrencyIso: currency.clone(),
+            CurrencyFactor: currency_factor,
         },
         SalesOrderShippingDetail: JtlShippingDetails {
-            ShippingMethodId: 7, // Standard shipping method
-            ShippingDate: format_iso_date(&order.created_on),
+            ShippingMethodId: shipping_method_id(shop),
+            ShippingDate: format_iso_date_in(order_date, shop.tz()),
+            TrackingId: order.tracking_number.clone(),
+            CarrierName: order.carrier_name.clone(),
         },
-        Comment: format!("Shop: {} - {}", shop.name, order.customer_note.clone().unwrap_or_default()),
+        Comment: order_comment(order, shop),
+        InternalNote: shop.internal_note(order),
         LanguageIso: "DE".to_string(),
     };
-    
+
     // Prepare order items for JTL
     let mut all_items: Vec<JtlOrderItem> = items.iter().map(|item| {
+        let tax_rate = shop.product_tax_rate_for(item.order_item_sku.as_deref());
         JtlOrderItem {
             Quantity: item.product_quantity,
             SalesPriceGross: Some(item.product_final_price),
-            TaxRate: 19.0,
-            Name: format!("[{}] {}", shop.name, item.order_item_name.clone()),
+            TaxRate: tax_rate,
+            Name: sanitize_item_name(&shop.line_item_name(&item.order_item_name)),
             SalesUnit: "stk".to_string(),
-            SalesPriceNet: Some(item.product_priceWithoutTax.unwrap_or(item.product_final_price / 1.19)),
+            SalesPriceNet: Some(item.product_priceWithoutTax.unwrap_or(item.product_final_price / (1.0 + tax_rate / 100.0))),
             PurchasePriceNet: None,
+            Sku: item.order_item_sku.as_deref()
+                .map(str::trim)
+                .filter(|sku| !sku.is_empty())
+                .map(str::to_string),
         }
     }).collect();
 
+    // Label service lines so a product-less order is recognizable in JTL
+    let service_label = if items.is_empty() { SERVICE_ONLY_LABEL } else { "" };
+
     // Add coupon if present
     if let Some(coupon_code) = &order.coupon_code {
         if !coupon_code.is_empty() {
@@ -142,10 +324,11 @@ pub async fn process_order(
                 Quantity: 1,
                 SalesPriceGross: Some(discount),
                 TaxRate: 0.0,
-                Name: format!("[{}] Coupon: {}", shop.name, coupon_code),
+                Name: sanitize_item_name(&shop.line_item_name(&format!("Coupon: {}{}", coupon_code, service_label))),
                 SalesUnit: "stk".to_string(),
                 PurchasePriceNet: None,
                 SalesPriceNet: Some(discount),
+                Sku: None,
             });
         }
     }
@@ -153,35 +336,640 @@ pub async fn process_order(
     // Add shipping if present
     if let Some(shipping_cost) = order.order_shipment {
         if shipping_cost > 0.0 {
+            // Tax follows the shipping country, so exports can be zero-rated
+            let shipping_country = shipping_address
+                .map(|addr| resolve_country_code(addr, shop))
+                .unwrap_or_else(|| resolve_country_code(order, shop));
+            let shipping_tax_rate = shop.shipping_tax_rate_for(&shipping_country);
+            
             all_items.push(JtlOrderItem {
                 Quantity: 1,
                 SalesPriceGross: Some(shipping_cost),
-                TaxRate: 19.0,
-                Name: format!("[{}] Shipping", shop.name),
+                TaxRate: shipping_tax_rate,
+                Name: sanitize_item_name(&shop.line_item_name(&format!("Shipping{}", service_label))),
                 SalesUnit: "stk".to_string(),
-                SalesPriceNet: Some(shipping_cost / 1.19),
+                SalesPriceNet: Some(shipping_cost / (1.0 + shipping_tax_rate / 100.0)),
                 PurchasePriceNet: None,
+                Sku: None,
             });
         }
     }
+
+    // Round once at the end so computed net prices don't carry float noise to JTL
+    for item in &mut all_items {
+        item.SalesPriceGross = item.SalesPriceGross.map(|price| round_price(price, shop.price_decimals));
+        item.SalesPriceNet = item.SalesPriceNet.map(|price| round_price(price, shop.price_decimals));
+    }
+
+    // Tax lines go right after the product lines and cover shipping and coupons too
+    if shop.emit_explicit_tax_line {
+        let tax_lines = tax_summary_lines(&all_items, shop, &currency);
+        all_items.splice(items.len()..items.len(), tax_lines);
+    }
+
+    (jtl_order, all_items)
+}
+
+/// Build the JTL customer for the buyer of a VirtueMart order
+///
+/// Pure construction without any IO, the shipping address falls back to the billing address
+pub fn build_jtl_customer(
+    order: &VirtueMartOrder,
+    shipping_address: Option<&VirtueMartOrder>,
+    shop: &ShopConfig,
+    customer_number: &str
+) -> JtlCustomer {
+    let billing_address = create_address_object(order, shop);
+    let shipping_addr = match shipping_address {
+        Some(addr) => create_address_object(addr, shop),
+        None => billing_address.clone(),
+    };
+
+    JtlCustomer {
+        CustomerGroupId: JTL_CUSTOMER_GROUP_ID,
+        BillingAddress: billing_address,
+        InternalCompanyId: shop.jtl_company.internal_company_id,
+        LanguageIso: "DE".to_string(),
+        Shipmentaddress: shipping_addr,
+        CustomerSince: format_iso_date_in(&order.created_on, shop.tz()),
+        Number: customer_number.to_string(),
+    }
+}
+
+// Address fields compared to decide whether a JTL customer is out of date
+const COMPARED_ADDRESS_FIELDS: [&str; 9] = [
+    "Company", "FirstName", "LastName", "Street", "Address2", "PostalCode", "City", "State", "CountryIso",
+];
+
+/// Whether an address as stored in JTL differs from the one built from VirtueMart
+///
+/// Fields missing in JTL's response count as empty
+fn address_differs(existing: &Value, address: &JtlAddress) -> bool {
+    let built = serde_json::to_value(address).unwrap_or_default();
     
-    // Create order in JTL
-    let response = client.create_order(&jtl_order, &all_items).await?;
+    COMPARED_ADDRESS_FIELDS.iter().any(|field| {
+        existing[field].as_str().unwrap_or_default().trim() != built[field].as_str().unwrap_or_default().trim()
+    })
+}
+
+/// Whether the billing or shipping address of a JTL customer differs from `customer`
+pub fn customer_address_changed(existing: &Value, customer: &JtlCustomer) -> bool {
+    address_differs(&existing["BillingAddress"], &customer.BillingAddress)
+        || address_differs(&existing["Shipmentaddress"], &customer.Shipmentaddress)
+}
+
+/// Process a single order for synchronization
+/// 
+/// Returns `Created` with the JTL order id if the order was created
+/// Returns `Updated` with the JTL order id if the order exists and `force_update` is set
+/// Returns `Skipped` with the reason if the order already exists or has no line items
+/// Returns `Interrupted` if the run was aborted or timed out during a lookup
+/// Returns Err if there was an error during processing
+///
+/// Only the lookups are interrupted; once the customer or order is being written
+/// the step runs to completion, so an abort never leaves a JTL order without its items.
+pub async fn process_order(
+    client: &JtlApiClient,
+    joomla_conn: &Pool,
+    order: &VirtueMartOrder,
+    shop: &ShopConfig,
+    customer_cache: &mut CustomerCache,
+    force_update: bool,
+    interrupt: &RunInterrupt
+) -> OrderReturn {
+    // Create customer number with shop ID prefix for uniqueness between shops
+    let customer_number = customer_number(order, shop);
+    
+    info!("Customer number from Joomla for shop '{}': {}", shop.name, customer_number);
+    
+    // Get order items first so empty orders don't create customers
+    let order_id = order.virtuemart_order_id;
+    let items = match interrupt.run(run_blocking({
+        let (pool, shop) = (joomla_conn.clone(), shop.clone());
+        move || get_order_items(&pool, &shop, order_id)
+    })).await {
+        Ok(items) => items.map_err(|e| SyncError::LoadOrder(e.to_string()))?,
+        Err(interruption) => return Ok(OrderResult::Interrupted(interruption)),
+    };
+    
+    info!("Found {} order items for shop '{}'", items.len(), shop.name);
+    
+    if skips_empty_order(order, &items, shop) {
+        return Ok(OrderResult::Skipped(SkipReason::NoLineItems));
+    }
+    
+    // Get shipping address
+    let shipping_address = match interrupt.run(run_blocking({
+        let (pool, shop) = (joomla_conn.clone(), shop.clone());
+        move || get_shipping_address(&pool, &shop, order_id)
+    })).await {
+        Ok(address) => address.map_err(|e| SyncError::LoadOrder(e.to_string()))?,
+        Err(interruption) => return Ok(OrderResult::Interrupted(interruption)),
+    };
+    
+    // Map payment method
+    let jtl_payment_method_id = map_payment_method(order.virtuemart_paymentmethod_id);
+    info!("Payment method: {} (JTL ID {}) for shop '{}'", 
+          payment_method_name(jtl_payment_method_id), jtl_payment_method_id, shop.name);
+    
+    // Order number with shop ID prefix for uniqueness between shops
+    let order_number = external_order_number(order, shop);
+
+    
+    // Only one task may look up or create a given customer at a time
+    let customer_lock = CustomerLockGuard::acquire(&customer_number).await;
+    
+    // Check if customer already exists (ids from earlier orders in this run are cached);
+    // an Err holds what this function returns instead
+    let customer_id = match cached_customer_id::<OrderReturn, _, _>(customer_cache, &customer_number, shop, || async {
+        let existing = match interrupt.run(client.get_customer_by_id(&customer_number)).await {
+            Ok(existing) => existing.map_err(|e| OrderReturn::Err(SyncError::CustomerCreate(e.to_string())))?,
+            Err(interruption) => return Err(OrderReturn::Ok(OrderResult::Interrupted(interruption))),
+        };
+        match existing {
+            Some(customer) => {
+                info!("Customer {} already exists with ID: {} (Shop: '{}')", 
+                      customer_number, customer["Id"], shop.name);
+                let id = jtl_id(&customer)
+                    .ok_or_else(|| OrderReturn::Err(SyncError::CustomerCreate(format!("JTL returned customer {} without an id", customer_number))))?;
+                
+                // Returning customers may have moved since their last order
+                if shop.update_existing_customers {
+                    let customer_data = build_jtl_customer(order, shipping_address.as_ref(), shop, &customer_number);
+                    if customer_address_changed(&customer, &customer_data) {
+                        info!("Address of customer {} changed, updating for shop '{}'", customer_number, shop.name);
+                        client.update_customer(&id, &customer_data).await
+                            .map_err(|e| OrderReturn::Err(SyncError::CustomerCreate(e.to_string())))?;
+                    }
+                }
+                Ok(id)
+            },
+            None => {
+                // Create new customer
+                info!("Creating new customer {} for shop '{}'", customer_number, shop.name);
+        
+                let customer_data = build_jtl_customer(order, shipping_address.as_ref(), shop, &customer_number);
+        
+                let response = client.create_customer(&customer_data).await
+                    .map_err(|e| OrderReturn::Err(SyncError::CustomerCreate(e.to_string())))?;
+                info!("Customer created with ID: {} for shop '{}'", response["Id"], shop.name);
+                jtl_id(&response)
+                    .ok_or_else(|| OrderReturn::Err(SyncError::CustomerCreate(format!("JTL created customer {} without returning an id", customer_number))))
+            }
+        }
+    }).await {
+        Ok(id) => id,
+        Err(result) => return result,
+    };
+    drop(customer_lock);
+    
+    // Check if order already exists
+    let order_exists = match interrupt.run(client.check_order_exists(&order_number, &customer_id)).await {
+        Ok(exists) => exists.map_err(|e| SyncError::OrderExistsCheck(e.to_string()))?,
+        Err(interruption) => return Ok(OrderResult::Interrupted(interruption)),
+    };
+    let action = order_action(order_exists, force_update);
+    if action == OrderAction::Skip {
+        warn!("Order {} already exists for shop '{}', skipping", 
+              order_number, shop.name);
+        return Ok(OrderResult::Skipped(SkipReason::AlreadyExists));
+    }
+    
+    // Create JTL order
+    info!("Creating order {} in JTL for shop '{}'", order_number, shop.name);
+    
+    info!("CustomerId: {} for shop '{}'", customer_id.clone(), shop.name);
+    info!("ExternalNumber: {} for shop '{}'", order_number.clone(), shop.name);
+    info!("Country: {} ID: {} for shop '{}'", 
+          resolve_country_code(order, shop), 
+          order.virtuemart_country_id.unwrap_or_default(),
+          shop.name);
+
+    let jtl_customer_id = customer_id.parse::<i32>()
+        .map_err(|_| SyncError::CustomerCreate(format!("Invalid JTL customer id '{}'", customer_id)))?;
+    let (jtl_order, all_items) = build_jtl_order(
+        order,
+        &items,
+        shipping_address.as_ref(),
+        shop,
+        jtl_customer_id
+    );
+    
+    // A total that doesn't add up is still synced, but reported for review
+    if let Some(issue) = reconcile_order(order, &all_items) {
+        warn!("Order {} for shop '{}' totals {:.2} in JTL but {:.2} in VirtueMart", 
+              order_number, shop.name, issue.computed, issue.expected);
+        record_reconciliation_issue(&shop.id, issue);
+    }
+    
+    // Existing orders are only updated in place when forced
+    if action == OrderAction::Update {
+        let existing_id = client.find_order_id(&order_number).await
+            .map_err(|e| SyncError::OrderCreate(e.to_string()))?
+            .ok_or_else(|| SyncError::OrderCreate(format!("Order {} exists but its JTL ID could not be found", order_number)))?;
+        
+        client.update_order(&existing_id, &jtl_order).await
+            .map_err(|e| SyncError::OrderCreate(e.to_string()))?;
+        info!("Order {} updated in JTL (ID: {}) for shop '{}'", 
+              order_number, existing_id, shop.name);
+        return Ok(OrderResult::Updated(existing_id));
+    }
+    
+    // Create order in JTL, keeping the exchange for debugging rejected orders
+    let request_payload = json!({ "order": &jtl_order, "items": &all_items });
+    let result = create_order_with_items(client, &jtl_order, &all_items).await;
+    record_jtl_response(&order_number, Some(request_payload), match &result {
+        Ok(response) => response.clone(),
+        Err(e) => json!({ "error": e.to_string() }),
+    });
+    let response = result?;
     let order_id = response["Id"].to_string();
     info!("Order {} successfully created in JTL with ID: {} for shop '{}'", 
           order_number, order_id, shop.name);
     
+    // Workflow steps are all attempted, the order exists in JTL either way
+    let mut workflow_errors = Vec::new();
+    
     // If already paid
-    if let Some(status) = &order.order_status {
-        if status == "C" && jtl_payment_method_id != 4 {
-            info!("Order {} is paid -> setting to paid for shop '{}'", 
-                  order_number, shop.name);
-            let _ = client.set_payment_paid(&order_id).await;
+    let paid = order.order_status.as_deref() == Some("C");
+    if paid && jtl_payment_method_id != 4 {
+        info!("Order {} is paid via {} -> setting to paid for shop '{}'", 
+              order_number, payment_method_name(jtl_payment_method_id), shop.name);
+        if let Err(e) = client.set_payment_paid(&order_id).await {
+            workflow_errors.push(format!("marking it paid failed ({})", e));
+        }
+    }
+
+    // Set order on hold depending on the shop's hold policy
+    if shop.hold_policy.should_hold(paid) {
+        if let Err(e) = client.set_order_hold(&order_id).await {
+            workflow_errors.push(format!("putting it on hold failed ({})", e));
         }
+    } else {
+        info!("Order {} not put on hold (policy: {:?}) for shop '{}'", 
+              order_number, shop.hold_policy, shop.name);
+    }
+    
+    if !workflow_errors.is_empty() {
+        return Err(SyncError::Workflow(format!("Order {} created in JTL with ID {}, but {}", 
+                                               order_number, order_id, workflow_errors.join(" and "))));
     }
+    
+    Ok(OrderResult::Created(order_id))
+}
+
+/// Create a JTL order and then its line items, keeping the two failures apart
+async fn create_order_with_items(
+    client: &JtlApiClient,
+    order: &JtlOrder,
+    items: &[JtlOrderItem]
+) -> std::result::Result<Value, SyncError> {
+    let response = client.create_sales_order(order).await
+        .map_err(|e| SyncError::OrderCreate(e.to_string()))?;
+    
+    let order_id = response["Id"].as_i64()
+        .ok_or_else(|| SyncError::OrderCreate("Invalid order ID".to_string()))? as i32;
+    
+    client.add_order_items(&order_id, items).await
+        .map_err(|e| SyncError::ItemsCreate(format!("Order {} was created without its items: {}", order_id, e)))?;
+    
+    Ok(response)
+}
 
-    // Set order on hold
-    let _ = client.set_order_hold(&order_id).await;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn test_shop() -> ShopConfig {
+        let mut shop = ShopConfig::new("Test Shop");
+        shop.id = "shop1".to_string();
+        shop
+    }
+    
+    fn test_order() -> VirtueMartOrder {
+        VirtueMartOrder {
+            virtuemart_order_id: 42,
+            order_number: "ORD42".to_string(),
+            created_on: "2024-03-01 10:00:00".to_string(),
+            order_total: 30.0,
+            first_name: Some("Max".to_string()),
+            last_name: Some("Mustermann".to_string()),
+            phone_1: Some("030 123".to_string()),
+            address_1: Some("Hauptstr. 1".to_string()),
+            zip: Some("10115".to_string()),
+            city: Some("Berlin".to_string()),
+            virtuemart_country_id: Some(81),
+            email: Some("max@example.com".to_string()),
+            virtuemart_paymentmethod_id: Some(9),
+            virtuemart_order_userinfo_id: Some(7),
+            customer_note: Some("Bitte klingeln".to_string()),
+            order_shipment: Some(5.0),
+            ..Default::default()
+        }
+    }
+    
+    fn test_items() -> Vec<VirtueMartOrderItem> {
+        vec![VirtueMartOrderItem {
+            virtuemart_order_item_id: 1,
+            virtuemart_order_id: 42,
+            order_item_sku: Some(" ABC-1 ".to_string()),
+            order_item_name: "Widget".to_string(),
+            product_quantity: 2,
+            product_final_price: 12.5,
+            ..Default::default()
+        }]
+    }
+    
+    #[test]
+    fn builds_order_header_from_virtuemart_order() {
+        let shop = test_shop();
+        let (order, _) = build_jtl_order(&test_order(), &test_items(), None, &shop, 99);
+        
+        assert_eq!(order.CustomerId, 99);
+        assert_eq!(order.ExternalNumber, "VM-SHOP1-42");
+        assert_eq!(order.CompanyId, 1);
+        assert_eq!(order.DepartureCountry.CountryISO, "DE");
+        assert_eq!(order.DepartureCountry.CurrencyIso, "EUR");
+        assert_eq!(order.SalesOrderDate, "2024-03-01T10:00:00+00:00");
+        assert_eq!(order.SalesOrderPaymentDetails.PaymentMethodId, 9);
+        assert_eq!(order.SalesOrderShippingDetail.ShippingMethodId, JTL_SHIPPING_METHOD_ID);
+        assert_eq!(order.Comment, "Bitte klingeln");
+        assert_eq!(order.InternalNote.as_deref(), Some("Shop: Test Shop"));
+        
+        assert_eq!(order.BillingAddress.FirstName, "Max");
+        assert_eq!(order.BillingAddress.Street, "Hauptstr. 1");
+        assert_eq!(order.BillingAddress.CountryIso, "DE");
+        assert_eq!(order.BillingAddress.PhoneNumber, "030123");
+        assert_eq!(order.Shipmentaddress.Street, order.BillingAddress.Street);
+    }
+    
+    #[test]
+    fn builds_product_and_shipping_lines() {
+        let shop = test_shop();
+        let (_, items) = build_jtl_order(&test_order(), &test_items(), None, &shop, 99);
+        
+        assert_eq!(items.len(), 2);
+        
+        let product = &items[0];
+        assert_eq!(product.Name, "[Test Shop] Widget");
+        assert_eq!(product.Quantity, 2);
+        assert_eq!(product.Sku.as_deref(), Some("ABC-1"));
+        assert_eq!(product.TaxRate, 19.0);
+        assert_eq!(product.SalesPriceGross, Some(12.5));
+        assert_eq!(product.SalesPriceNet, Some(10.5));
+        
+        let shipping = &items[1];
+        assert_eq!(shipping.Name, "[Test Shop] Shipping");
+        assert_eq!(shipping.SalesPriceGross, Some(5.0));
+        assert_eq!(shipping.SalesPriceNet, Some(4.2));
+    }
+    
+    #[test]
+    fn builds_customer_with_billing_as_shipping_address() {
+        let shop = test_shop();
+        let order = test_order();
+        let customer = build_jtl_customer(&order, None, &shop, &customer_number(&order, &shop));
+        
+        assert_eq!(customer.Number, "VM7");
+        assert_eq!(customer.CustomerGroupId, JTL_CUSTOMER_GROUP_ID);
+        assert_eq!(customer.InternalCompanyId, 1);
+        assert_eq!(customer.CustomerSince, "2024-03-01T10:00:00+00:00");
+        assert_eq!(customer.BillingAddress.EmailAddress, "max@example.com");
+        assert_eq!(
+            serde_json::to_value(&customer.Shipmentaddress).unwrap(),
+            serde_json::to_value(&customer.BillingAddress).unwrap()
+        );
+    }
+    
+    #[test]
+    fn uses_separate_shipping_address_when_present() {
+        let shop = test_shop();
+        let order = test_order();
+        let shipping = VirtueMartOrder {
+            city: Some("Hamburg".to_string()),
+            address_1: Some("Elbchaussee 5".to_string()),
+            ..test_order()
+        };
+        let customer = build_jtl_customer(&order, Some(&shipping), &shop, "VM7");
+        
+        assert_eq!(customer.BillingAddress.City, "Berlin");
+        assert_eq!(customer.Shipmentaddress.City, "Hamburg");
+        assert_eq!(customer.Shipmentaddress.Street, "Elbchaussee 5");
+    }
+    
+    fn priced_item(gross: f64, net: f64, rate: f64, quantity: i32) -> JtlOrderItem {
+        JtlOrderItem {
+            Quantity: quantity,
+            SalesPriceGross: Some(gross),
+            TaxRate: rate,
+            Name: "Item".to_string(),
+            SalesUnit: "stk".to_string(),
+            SalesPriceNet: Some(net),
+            PurchasePriceNet: None,
+            Sku: None,
+        }
+    }
+    
+    #[test]
+    fn sums_tax_per_rate_for_mixed_rate_order() {
+        let items = vec![
+            priced_item(11.9, 10.0, 19.0, 2),
+            priced_item(10.7, 10.0, 7.0, 1),
+            priced_item(5.95, 5.0, 19.0, 1),
+            priced_item(3.0, 3.0, 0.0, 1),
+        ];
+        
+        let totals = tax_by_rate(&items);
+        
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[0].0, 7.0);
+        assert!((totals[0].1 - 0.7).abs() < 1e-9);
+        assert_eq!(totals[1].0, 19.0);
+        assert!((totals[1].1 - 4.75).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn detects_changed_customer_address() {
+        let shop = test_shop();
+        let customer = build_jtl_customer(&test_order(), None, &shop, "VM7");
+        let mut existing = serde_json::to_value(&customer).unwrap();
+        
+        assert!(!customer_address_changed(&existing, &customer));
+        
+        existing["BillingAddress"]["City"] = json!("Hamburg");
+        assert!(customer_address_changed(&existing, &customer));
+        
+        let mut existing = serde_json::to_value(&customer).unwrap();
+        existing["Shipmentaddress"] = json!({});
+        assert!(customer_address_changed(&existing, &customer));
+    }
     
-    Ok(true)
-}
\ No newline at end of file
+    #[test]
+    fn empty_orders_are_skipped_unless_allowed() {
+        let mut shop = test_shop();
+        let order = VirtueMartOrder { order_shipment: None, ..test_order() };
+        
+        assert!(!skips_empty_order(&order, &test_items(), &shop));
+        assert!(skips_empty_order(&order, &[], &shop));
+        
+        shop.allow_empty_orders = true;
+        assert!(!skips_empty_order(&order, &[], &shop));
+    }
+    
+    #[test]
+    fn shipping_only_order_gets_one_service_line() {
+        let mut shop = test_shop();
+        shop.allow_service_only_orders = true;
+        let order = test_order();
+        
+        assert!(!skips_empty_order(&order, &[], &shop));
+        
+        let (_, items) = build_jtl_order(&order, &[], None, &shop, 99);
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].Name, "[Test Shop] Shipping (service only)");
+        assert_eq!(items[0].SalesPriceGross, Some(5.0));
+    }
+    
+    #[test]
+    fn lines_without_sku_omit_the_field() {
+        let items = vec![
+            VirtueMartOrderItem { order_item_sku: None, ..test_items()[0].clone() },
+            VirtueMartOrderItem { order_item_sku: Some("  ".to_string()), ..test_items()[0].clone() },
+        ];
+        let (_, lines) = build_jtl_order(&test_order(), &items, None, &test_shop(), 99);
+        
+        for line in &lines[..2] {
+            let json = serde_json::to_value(line).unwrap();
+            assert!(json.get("Sku").is_none());
+            assert_eq!(json["Name"], "[Test Shop] Widget");
+        }
+    }
+    
+    #[test]
+    fn order_number_uses_the_configured_prefix() {
+        let mut shop = test_shop();
+        shop.order_number_prefix = "AT".to_string();
+        let order = VirtueMartOrder { virtuemart_order_id: 123, ..test_order() };
+        
+        assert_eq!(external_order_number(&order, &shop), "AT-SHOP1-123");
+        
+        shop.legacy_order_numbers = true;
+        assert_eq!(external_order_number(&order, &shop), "AT123");
+    }
+    
+    #[test]
+    fn shops_sharing_an_order_id_get_distinct_numbers() {
+        let first = test_shop();
+        let mut second = ShopConfig::new("Second Shop");
+        second.id = "shop2".to_string();
+        let order = test_order();
+        
+        assert_eq!(external_order_number(&order, &first), "VM-SHOP1-42");
+        assert_eq!(external_order_number(&order, &second), "VM-SHOP2-42");
+    }
+    
+    #[test]
+    fn order_in_its_own_currency_keeps_the_factor() {
+        let shop = test_shop();
+        let order = VirtueMartOrder { currency_iso: Some("usd".to_string()), currency_factor: Some(1.08), ..test_order() };
+        
+        assert_eq!(order_currency(&order, &shop), ("USD".to_string(), 1.08));
+        assert_eq!(order_currency(&test_order(), &shop), ("EUR".to_string(), 1.0));
+        
+        let (jtl_order, _) = build_jtl_order(&order, &test_items(), None, &shop, 99);
+        assert_eq!(jtl_order.DepartureCountry.CurrencyIso, "USD");
+        assert_eq!(jtl_order.SalesOrderPaymentDetails.CurrencyFactor, 1.08);
+    }
+    
+    #[test]
+    fn shops_book_orders_under_their_own_company() {
+        let first = test_shop();
+        let mut second = ShopConfig::new("Second Shop");
+        second.id = "shop2".to_string();
+        second.jtl_company.company_id = 2;
+        second.jtl_company.internal_company_id = 3;
+        
+        let (first_order, _) = build_jtl_order(&test_order(), &test_items(), None, &first, 99);
+        let (second_order, _) = build_jtl_order(&test_order(), &test_items(), None, &second, 99);
+        assert_eq!(first_order.CompanyId, 1);
+        assert_eq!(second_order.CompanyId, 2);
+        assert_eq!(build_jtl_customer(&test_order(), None, &second, "VM7").InternalCompanyId, 3);
+    }
+    
+    #[test]
+    fn configured_shipping_method_replaces_the_default() {
+        let mut shop = test_shop();
+        assert_eq!(shipping_method_id(&shop), JTL_SHIPPING_METHOD_ID);
+        
+        shop.default_shipping_method_id = Some(12);
+        assert_eq!(shipping_method_id(&shop), 12);
+        
+        let (order, _) = build_jtl_order(&test_order(), &test_items(), None, &shop, 99);
+        assert_eq!(order.SalesOrderShippingDetail.ShippingMethodId, 12);
+    }
+    
+    #[test]
+    fn order_results_keep_outcomes_apart() {
+        let created = OrderResult::Created("4711".to_string());
+        assert_eq!(created, OrderResult::Created("4711".to_string()));
+        assert_ne!(created, OrderResult::Updated("4711".to_string()));
+        
+        assert_ne!(OrderResult::Skipped(SkipReason::AlreadyExists), OrderResult::Skipped(SkipReason::NoLineItems));
+        assert_eq!(SkipReason::AlreadyExists.as_str(), "already exists");
+        assert_eq!(SkipReason::NoLineItems.as_str(), "no line items");
+        
+        assert_ne!(OrderResult::Interrupted(Interruption::Aborted), OrderResult::Interrupted(Interruption::TimedOut));
+    }
+    
+    #[tokio::test]
+    async fn customers_are_looked_up_once_per_run() {
+        let shop = test_shop();
+        let mut cache = CustomerCache::new();
+        let mut lookups = 0;
+        
+        for number in ["VM7", "VM7", "VM8", "VM7"] {
+            let id = cached_customer_id::<(), _, _>(&mut cache, number, &shop, || {
+                lookups += 1;
+                async move { Ok(format!("id-{}", number)) }
+            }).await.unwrap();
+            assert_eq!(id, format!("id-{}", number));
+        }
+        
+        assert_eq!(lookups, 2);
+        
+        // A failed lookup is tried again
+        assert!(cached_customer_id(&mut cache, "VM9", &shop, || async { Err(()) }).await.is_err());
+        assert_eq!(cached_customer_id::<(), _, _>(&mut cache, "VM9", &shop, || async { Ok("id-VM9".to_string()) }).await.unwrap(), "id-VM9");
+    }
+    
+    #[tokio::test]
+    async fn customer_locks_serialize_and_clean_up() {
+        let first = CustomerLockGuard::acquire("lock-test").await;
+        let acquired = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        
+        let second = tokio::spawn({
+            let acquired = acquired.clone();
+            async move {
+                let guard = CustomerLockGuard::acquire("lock-test").await;
+                acquired.store(true, std::sync::atomic::Ordering::SeqCst);
+                drop(guard);
+            }
+        });
+        
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!acquired.load(std::sync::atomic::Ordering::SeqCst));
+        
+        drop(first);
+        second.await.unwrap();
+        assert!(acquired.load(std::sync::atomic::Ordering::SeqCst));
+        assert!(!lock_or_recover(&CUSTOMER_LOCKS, "CUSTOMER_LOCKS").contains_key("lock-test"));
+    }
+    
+    #[test]
+    fn force_update_updates_existing_orders_instead_of_skipping() {
+        assert_eq!(order_action(true, false), OrderAction::Skip);
+        assert_eq!(order_action(true, true), OrderAction::Update);
+        assert_eq!(order_action(false, true), OrderAction::Create);
+        assert_eq!(order_action(false, false), OrderAction::Create);
+    }
+}
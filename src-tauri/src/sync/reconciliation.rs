@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::db::models::{JtlOrderItem, VirtueMartOrder};
+use crate::utils::lock::lock_or_recover;
+
+// Differences up to this amount are rounding noise, not a mismatch
+const TOTAL_TOLERANCE: f64 = 0.01;
+
+/// An order whose line items don't add up to the VirtueMart order total
+#[derive(Serialize, Clone)]
+pub struct ReconciliationIssue {
+    pub order_number: String,
+    pub expected: f64, // VirtueMart order total
+    pub computed: f64, // Sum of the line items sent to JTL
+    pub recorded_at: DateTime<Utc>,
+}
+
+lazy_static! {
+    // Map of shop_id -> mismatches found in the shop's latest run
+    static ref RECONCILIATION_ISSUES: Mutex<HashMap<String, Vec<ReconciliationIssue>>> = Mutex::new(HashMap::new());
+}
+
+/// Gross total of the line items as JTL will see it
+pub fn computed_total(items: &[JtlOrderItem]) -> f64 {
+    items.iter()
+        .map(|item| item.SalesPriceGross.unwrap_or_default() * item.Quantity as f64)
+        .sum()
+}
+
+/// Compare the line items of an order with its total, returning the mismatch if any
+pub fn reconcile_order(order: &VirtueMartOrder, items: &[JtlOrderItem]) -> Option<ReconciliationIssue> {
+    let computed = computed_total(items);
+    
+    ((computed - order.order_total).abs() > TOTAL_TOLERANCE).then(|| ReconciliationIssue {
+        order_number: order.order_number.clone(),
+        expected: order.order_total,
+        computed,
+        recorded_at: Utc::now(),
+    })
+}
+
+/// Store a mismatch found in the current run of a shop
+pub fn record_reconciliation_issue(shop_id: &str, issue: ReconciliationIssue) {
+    let mut issues = lock_or_recover(&RECONCILIATION_ISSUES, "RECONCILIATION_ISSUES");
+    let shop_issues = issues.entry(shop_id.to_string()).or_default();
+    
+    shop_issues.retain(|i| i.order_number != issue.order_number);
+    shop_issues.push(issue);
+}
+
+/// Forget the mismatches of a shop's previous run, called when a new run starts
+pub fn clear_reconciliation_issues(shop_id: &str) {
+    lock_or_recover(&RECONCILIATION_ISSUES, "RECONCILIATION_ISSUES").remove(shop_id);
+}
+
+/// Get the mismatches found in the latest run of a shop
+pub fn get_reconciliation_issues(shop_id: &str) -> Vec<ReconciliationIssue> {
+    lock_or_recover(&RECONCILIATION_ISSUES, "RECONCILIATION_ISSUES").get(shop_id).cloned().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn line(gross: f64, quantity: i32) -> JtlOrderItem {
+        JtlOrderItem {
+            Quantity: quantity,
+            SalesPriceGross: Some(gross),
+            TaxRate: 19.0,
+            Name: "Item".to_string(),
+            SalesUnit: "stk".to_string(),
+            SalesPriceNet: None,
+            PurchasePriceNet: None,
+            Sku: None,
+        }
+    }
+    
+    #[test]
+    fn mismatched_total_is_returned_and_recorded() {
+        let order = VirtueMartOrder { order_number: "ORD1".to_string(), order_total: 30.0, ..Default::default() };
+        
+        assert!(reconcile_order(&order, &[line(12.5, 2), line(5.0, 1)]).is_none());
+        
+        let issue = reconcile_order(&order, &[line(12.5, 2)]).unwrap();
+        assert_eq!(issue.expected, 30.0);
+        assert_eq!(issue.computed, 25.0);
+        
+        record_reconciliation_issue("reconcile-shop", issue);
+        let issues = get_reconciliation_issues("reconcile-shop");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].order_number, "ORD1");
+        
+        clear_reconciliation_issues("reconcile-shop");
+        assert!(get_reconciliation_issues("reconcile-shop").is_empty());
+    }
+}
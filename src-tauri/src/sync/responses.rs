@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::utils::lock::lock_or_recover;
+
+// Number of JTL exchanges kept for debugging; the oldest is dropped first
+const MAX_STORED_RESPONSES: usize = 200;
+
+/// Request and response of the last JTL order creation for an external order number
+#[derive(Serialize, Clone)]
+pub struct JtlExchange {
+    pub external_number: String,
+    pub request: Option<Value>,
+    pub response: Value, // `{"error": ...}` if the request failed
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct ResponseCache {
+    entries: HashMap<String, JtlExchange>,
+    order: VecDeque<String>, // Insertion order for eviction
+}
+
+lazy_static! {
+    static ref JTL_RESPONSES: Mutex<ResponseCache> = Mutex::new(ResponseCache::default());
+}
+
+/// Store the latest JTL exchange for an external order number
+pub fn record_jtl_response(external_number: &str, request: Option<Value>, response: Value) {
+    let mut cache = lock_or_recover(&JTL_RESPONSES, "JTL_RESPONSES");
+    
+    cache.order.retain(|n| n != external_number);
+    cache.order.push_back(external_number.to_string());
+    cache.entries.insert(external_number.to_string(), JtlExchange {
+        external_number: external_number.to_string(),
+        request,
+        response,
+        recorded_at: Utc::now(),
+    });
+    
+    while cache.order.len() > MAX_STORED_RESPONSES {
+        if let Some(oldest) = cache.order.pop_front() {
+            cache.entries.remove(&oldest);
+        }
+    }
+}
+
+/// Get the latest JTL exchange for an external order number
+pub fn get_jtl_response(external_number: &str) -> Option<JtlExchange> {
+    lock_or_recover(&JTL_RESPONSES, "JTL_RESPONSES").entries.get(external_number).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    #[test]
+    fn responses_are_kept_until_evicted() {
+        record_jtl_response("VM-RESP-1", Some(json!({ "order": {} })), json!({ "Id": 1 }));
+        
+        let exchange = get_jtl_response("VM-RESP-1").unwrap();
+        assert_eq!(exchange.response, json!({ "Id": 1 }));
+        assert!(exchange.request.is_some());
+        
+        for i in 0..MAX_STORED_RESPONSES {
+            record_jtl_response(&format!("VM-EVICT-{}", i), None, json!({ "error": "Timeout" }));
+        }
+        
+        assert!(get_jtl_response("VM-RESP-1").is_none());
+        assert!(get_jtl_response(&format!("VM-EVICT-{}", MAX_STORED_RESPONSES - 1)).is_some());
+    }
+}
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::error::{Result, Error};
+use crate::utils::lock::lock_or_recover;
+
+/// A registered scheduled synchronization job
+#[derive(Serialize, Clone)]
+pub struct ScheduledJob {
+    pub job_id: String,
+    pub cron: String,
+    pub shop_ids: Vec<String>,
+    pub next_run: Option<DateTime<Utc>>,
+}
+
+lazy_static! {
+    // Registry of scheduled jobs: job_id -> job
+    static ref SCHEDULED_JOBS: Mutex<HashMap<String, ScheduledJob>> = Mutex::new(HashMap::new());
+}
+
+/// Parse a cron expression, accepting the classic 5-field form as well as the 6/7-field form with seconds
+pub fn parse_cron(expression: &str) -> Result<Schedule> {
+    let expression = expression.trim();
+    let normalized = if expression.split_whitespace().count() == 5 {
+        format!("0 {}", expression)
+    } else {
+        expression.to_string()
+    };
+    
+    Schedule::from_str(&normalized)
+        .map_err(|e| Error::ValidationError(format!("Invalid cron expression '{}': {}", expression, e)))
+}
+
+/// Get the next `count` run times of a cron expression
+pub fn upcoming_runs(expression: &str, count: usize) -> Result<Vec<DateTime<Utc>>> {
+    Ok(parse_cron(expression)?.upcoming(Utc).take(count).collect())
+}
+
+/// Get the next run time of a cron expression, if it can be parsed
+pub fn next_run(expression: &str) -> Option<DateTime<Utc>> {
+    parse_cron(expression).ok()?.upcoming(Utc).next()
+}
+
+/// Register a new scheduled job and return it
+pub fn add_job(shop_ids: Vec<String>, cron: &str) -> ScheduledJob {
+    let job = ScheduledJob {
+        job_id: Uuid::new_v4().to_string(),
+        cron: cron.to_string(),
+        shop_ids,
+        next_run: next_run(cron),
+    };
+    
+    lock_or_recover(&SCHEDULED_JOBS, "SCHEDULED_JOBS").insert(job.job_id.clone(), job.clone());
+    job
+}
+
+/// List all scheduled jobs ordered by their next run
+pub fn list_jobs() -> Vec<ScheduledJob> {
+    let mut jobs: Vec<ScheduledJob> = lock_or_recover(&SCHEDULED_JOBS, "SCHEDULED_JOBS")
+        .values()
+        .cloned()
+        .map(|mut job| {
+            job.next_run = next_run(&job.cron);
+            job
+        })
+        .collect();
+    
+    jobs.sort_by_key(|job| job.next_run);
+    jobs
+}
+
+/// Cancel a single job by id
+pub fn cancel_job(job_id: &str) -> Result<ScheduledJob> {
+    lock_or_recover(&SCHEDULED_JOBS, "SCHEDULED_JOBS")
+        .remove(job_id)
+        .ok_or_else(|| Error::NotFound(format!("No scheduled job found with ID '{}'", job_id)))
+}
+
+/// Cancel all jobs that include a shop; returns the number of cancelled jobs
+pub fn cancel_jobs_for_shop(shop_id: &str) -> usize {
+    let mut jobs = lock_or_recover(&SCHEDULED_JOBS, "SCHEDULED_JOBS");
+    let before = jobs.len();
+    jobs.retain(|_, job| !job.shop_ids.iter().any(|id| id == shop_id));
+    before - jobs.len()
+}
+
+/// Cancel all jobs; returns the number of cancelled jobs
+pub fn cancel_all_jobs() -> usize {
+    let mut jobs = lock_or_recover(&SCHEDULED_JOBS, "SCHEDULED_JOBS");
+    let count = jobs.len();
+    jobs.clear();
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+    
+    #[test]
+    fn upcoming_runs_accepts_five_field_expressions() {
+        let runs = upcoming_runs("30 2 * * *", 3).unwrap();
+        
+        assert_eq!(runs.len(), 3);
+        assert!(runs.windows(2).all(|pair| pair[1] - pair[0] == chrono::Duration::days(1)));
+        assert!(runs.iter().all(|run| run.hour() == 2 && run.minute() == 30 && run.second() == 0));
+    }
+    
+    #[test]
+    fn upcoming_runs_accepts_expressions_with_seconds() {
+        let runs = upcoming_runs("15 */10 * * * *", 2).unwrap();
+        
+        assert_eq!(runs[1] - runs[0], chrono::Duration::minutes(10));
+        assert_eq!(runs[0].second(), 15);
+    }
+    
+    #[test]
+    fn upcoming_runs_rejects_invalid_expressions() {
+        assert!(matches!(upcoming_runs("not a cron", 1), Err(Error::ValidationError(_))));
+        assert!(upcoming_runs("61 * * * *", 1).is_err());
+    }
+}
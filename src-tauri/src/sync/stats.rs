@@ -5,6 +5,7 @@ use std::collections::HashMap;
 use std::sync::Mutex;
 
 use crate::error::{Result, Error};
+use crate::utils::lock::lock_or_recover;
 
 /// Sync statistics structure for dashboard
 #[derive(Clone, Serialize, Deserialize)]
@@ -18,6 +19,76 @@ pub struct SyncStats {
     pub next_scheduled_run: Option<DateTime<Utc>>,
     pub aborted: bool,
     pub sync_hours: i32,
+    #[serde(default)]
+    pub orders_missing_billing: i32,
+    #[serde(default)]
+    pub empty_orders: i32, // Orders skipped because they have no line items
+    #[serde(default)]
+    pub updated_orders: i32, // Existing JTL orders updated by a forced re-sync
+    #[serde(default)]
+    pub last_error: Option<String>, // Most recent order or run error; cleared by a clean run
+    #[serde(default)]
+    pub skipped_test_orders: i32, // Orders from excluded test customers
+    #[serde(default)]
+    pub skipped_zero_total: i32, // Orders skipped for a total of zero or less
+    #[serde(default)]
+    pub failures_by_category: HashMap<String, i32>, // SyncError category -> failed orders
+    #[serde(default)]
+    pub abort_reason: Option<String>, // Why an aborted run stopped, e.g. user request or timeout
+}
+
+/// Summary of a whole multi-shop synchronization run
+///
+/// `per_shop` lists the outcome of every shop the run attempted, in sync order;
+/// skipped (archived) shops and shops not reached after an abort are not listed
+#[derive(Clone, Serialize, Deserialize, Default)]
+pub struct MultiSyncSummary {
+    pub total_shops: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,        // Archived shops
+    pub aborted: bool,
+    pub per_shop: Vec<(String, std::result::Result<SyncStats, String>)>, // shop_id -> stats or error message
+    pub duration_ms: u64,
+}
+
+impl MultiSyncSummary {
+    /// Record the outcome of a shop, keeping the counters in sync
+    pub fn record(&mut self, shop_id: String, outcome: std::result::Result<SyncStats, String>) {
+        match &outcome {
+            Ok(stats) => {
+                self.succeeded += 1;
+                self.aborted |= stats.aborted;
+            },
+            Err(_) => self.failed += 1,
+        }
+        self.per_shop.push((shop_id, outcome));
+    }
+    
+    /// IDs of the shops whose sync failed
+    pub fn failed_shop_ids(&self) -> Vec<&str> {
+        self.per_shop.iter()
+            .filter(|(_, outcome)| outcome.is_err())
+            .map(|(shop_id, _)| shop_id.as_str())
+            .collect()
+    }
+    
+    /// Number of shops synced without a run error
+    pub fn succeeded_count(&self) -> usize {
+        self.per_shop.iter().filter(|(_, outcome)| outcome.is_ok()).count()
+    }
+}
+
+/// Stats of the last sync of every shop added up
+#[derive(Clone, Serialize, Deserialize, Default)]
+pub struct AggregateStats {
+    pub shop_count: usize,
+    pub total_orders: i32,
+    pub synced_orders: i32,
+    pub skipped_orders: i32,
+    pub error_orders: i32,
+    pub updated_orders: i32,
+    pub last_sync_time: Option<DateTime<Utc>>, // Most recent sync of any shop
 }
 
 impl Default for SyncStats {
@@ -32,6 +103,14 @@ impl Default for SyncStats {
             next_scheduled_run: None,
             aborted: false,
             sync_hours: 24, // Default to 24 hours
+            orders_missing_billing: 0,
+            empty_orders: 0,
+            updated_orders: 0,
+            last_error: None,
+            skipped_test_orders: 0,
+            skipped_zero_total: 0,
+            failures_by_category: HashMap::new(),
+            abort_reason: None,
         }
     }
 }
@@ -51,18 +130,26 @@ lazy_static! {
         next_scheduled_run: None,
         aborted: false,
         sync_hours: 24, // Default to 24 hours
+        orders_missing_billing: 0,
+        empty_orders: 0,
+        updated_orders: 0,
+        last_error: None,
+        skipped_test_orders: 0,
+        skipped_zero_total: 0,
+        failures_by_category: HashMap::new(),
+        abort_reason: None,
     };
 }
 
 /// Update sync stats for a specific shop
 pub fn update_sync_stats(stats: SyncStats) {
-    let mut current_stats = SYNC_STATS.lock().unwrap();
+    let mut current_stats = lock_or_recover(&SYNC_STATS, "SYNC_STATS");
     current_stats.insert(stats.shop_id.clone(), stats);
 }
 
 /// Get sync stats for a specific shop
 pub fn get_shop_stats(shop_id: &str) -> SyncStats {
-    let stats = SYNC_STATS.lock().unwrap();
+    let stats = lock_or_recover(&SYNC_STATS, "SYNC_STATS");
     match stats.get(shop_id) {
         Some(shop_stats) => shop_stats.clone(),
         None => {
@@ -74,9 +161,28 @@ pub fn get_shop_stats(shop_id: &str) -> SyncStats {
     }
 }
 
+/// Get the stats of all shops that have synced or been configured in this session
+pub fn get_all_shop_stats() -> Vec<SyncStats> {
+    lock_or_recover(&SYNC_STATS, "SYNC_STATS").values().cloned().collect()
+}
+
+/// Add up the stats of several shops
+pub fn aggregate_stats(stats: &[SyncStats]) -> AggregateStats {
+    stats.iter().fold(AggregateStats::default(), |mut total, shop| {
+        total.shop_count += 1;
+        total.total_orders += shop.total_orders;
+        total.synced_orders += shop.synced_orders;
+        total.skipped_orders += shop.skipped_orders;
+        total.error_orders += shop.error_orders;
+        total.updated_orders += shop.updated_orders;
+        total.last_sync_time = total.last_sync_time.max(shop.last_sync_time);
+        total
+    })
+}
+
 /// Get stats for the "current" shop - used for backward compatibility
 pub fn get_current_stats() -> SyncStats {
-    let stats = SYNC_STATS.lock().unwrap();
+    let stats = lock_or_recover(&SYNC_STATS, "SYNC_STATS");
     
     // If we have any stats, return the first one
     if let Some((_, first_stats)) = stats.iter().next() {
@@ -87,13 +193,25 @@ pub fn get_current_stats() -> SyncStats {
     DEFAULT_STATS.clone()
 }
 
+/// Record the error of a failed sync run for a shop
+pub fn set_last_error(shop_id: &str, message: &str) {
+    let mut stats = lock_or_recover(&SYNC_STATS, "SYNC_STATS");
+    
+    let shop_stats = stats.entry(shop_id.to_string()).or_insert_with(|| {
+        let mut new_stats = DEFAULT_STATS.clone();
+        new_stats.shop_id = shop_id.to_string();
+        new_stats
+    });
+    shop_stats.last_error = Some(message.to_string());
+}
+
 /// Update sync time range for a shop
 pub fn update_shop_sync_hours(shop_id: &str, hours: i32) -> Result<()> {
     if hours <= 0 {
         return Err(Error::ValidationError("Sync timeframe must be greater than zero hours".to_string()));
     }
     
-    let mut stats = SYNC_STATS.lock().unwrap();
+    let mut stats = lock_or_recover(&SYNC_STATS, "SYNC_STATS");
     
     // If stats for this shop already exist, update them
     if let Some(shop_stats) = stats.get_mut(shop_id) {
@@ -112,19 +230,80 @@ pub fn update_shop_sync_hours(shop_id: &str, hours: i32) -> Result<()> {
 
 /// Reset stats for a specific shop
 pub fn reset_shop_stats(shop_id: &str) {
-    let mut stats = SYNC_STATS.lock().unwrap();
+    let mut stats = lock_or_recover(&SYNC_STATS, "SYNC_STATS");
     
     if let Some(shop_stats) = stats.get_mut(shop_id) {
         shop_stats.total_orders = 0;
         shop_stats.synced_orders = 0;
         shop_stats.skipped_orders = 0;
         shop_stats.error_orders = 0;
+        shop_stats.orders_missing_billing = 0;
+        shop_stats.empty_orders = 0;
+        shop_stats.updated_orders = 0;
         shop_stats.aborted = false;
+        shop_stats.last_error = None;
+        shop_stats.skipped_test_orders = 0;
+        shop_stats.skipped_zero_total = 0;
+        shop_stats.failures_by_category.clear();
+        shop_stats.abort_reason = None;
     }
 }
 
 /// Reset stats for all shops
 pub fn reset_all_stats() {
-    let mut stats = SYNC_STATS.lock().unwrap();
+    let mut stats = lock_or_recover(&SYNC_STATS, "SYNC_STATS");
     stats.clear();
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn summary_lists_failed_and_succeeded_shops() {
+        let mut summary = MultiSyncSummary { total_shops: 2, ..Default::default() };
+        
+        summary.record("shop-a".to_string(), Ok(SyncStats { shop_id: "shop-a".to_string(), synced_orders: 3, ..Default::default() }));
+        summary.record("shop-b".to_string(), Err("Database unreachable".to_string()));
+        
+        assert_eq!(summary.per_shop.len(), 2);
+        assert_eq!(summary.succeeded, 1);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(summary.succeeded_count(), 1);
+        assert_eq!(summary.failed_shop_ids(), vec!["shop-b"]);
+        assert!(matches!(&summary.per_shop[0].1, Ok(stats) if stats.synced_orders == 3));
+    }
+    
+    #[test]
+    fn aggregate_sums_the_stats_of_two_shops() {
+        let earlier = Utc::now() - chrono::Duration::hours(1);
+        let later = Utc::now();
+        let stats = [
+            SyncStats { shop_id: "shop-a".to_string(), total_orders: 5, synced_orders: 3, skipped_orders: 1, error_orders: 1, last_sync_time: Some(later), ..Default::default() },
+            SyncStats { shop_id: "shop-b".to_string(), total_orders: 4, synced_orders: 2, updated_orders: 2, last_sync_time: Some(earlier), ..Default::default() },
+        ];
+        
+        let total = aggregate_stats(&stats);
+        
+        assert_eq!(total.shop_count, 2);
+        assert_eq!(total.total_orders, 9);
+        assert_eq!(total.synced_orders, 5);
+        assert_eq!(total.skipped_orders, 1);
+        assert_eq!(total.error_orders, 1);
+        assert_eq!(total.updated_orders, 2);
+        assert_eq!(total.last_sync_time, Some(later));
+    }
+    
+    #[test]
+    fn run_error_is_kept_until_a_clean_run() {
+        set_last_error("last-error-shop", "Database unreachable");
+        assert_eq!(get_shop_stats("last-error-shop").last_error.as_deref(), Some("Database unreachable"));
+        
+        reset_shop_stats("last-error-shop");
+        assert_eq!(get_shop_stats("last-error-shop").last_error, None);
+        
+        set_last_error("last-error-shop", "Database unreachable");
+        update_sync_stats(SyncStats { shop_id: "last-error-shop".to_string(), ..Default::default() });
+        assert_eq!(get_shop_stats("last-error-shop").last_error, None);
+    }
+}
@@ -1,8 +1,16 @@
+use std::future::Future;
+use std::sync::Mutex;
 use std::sync::atomic::{AtomicBool, Ordering};
 use lazy_static::lazy_static;
+use tokio::time::{sleep, sleep_until, Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::utils::lock::lock_or_recover;
 
 lazy_static! {
     static ref ABORT_FLAG: AtomicBool = AtomicBool::new(false);
+    static ref PAUSE_FLAG: AtomicBool = AtomicBool::new(false);
+    static ref ABORT_TOKEN: Mutex<CancellationToken> = Mutex::new(CancellationToken::new());
 }
 
 /// Check if synchronization should be aborted
@@ -13,9 +21,174 @@ pub fn should_abort() -> bool {
 /// Reset the abort flag
 pub fn reset_abort_flag() {
     ABORT_FLAG.store(false, Ordering::SeqCst);
+    
+    // A cancelled token stays cancelled, so start a fresh one for the next run
+    let mut token = lock_or_recover(&ABORT_TOKEN, "ABORT_TOKEN");
+    if token.is_cancelled() {
+        *token = CancellationToken::new();
+    }
 }
 
 /// Set the abort flag to stop synchronization
 pub fn set_abort_flag() {
     ABORT_FLAG.store(true, Ordering::SeqCst);
-}
\ No newline at end of file
+    // An aborted sync must not stay paused
+    PAUSE_FLAG.store(false, Ordering::SeqCst);
+    lock_or_recover(&ABORT_TOKEN, "ABORT_TOKEN").cancel();
+}
+
+/// Check if synchronization should pause before the next order
+pub fn should_pause() -> bool {
+    PAUSE_FLAG.load(Ordering::SeqCst)
+}
+
+/// Pause or resume synchronization
+pub fn set_pause_flag(paused: bool) {
+    PAUSE_FLAG.store(paused, Ordering::SeqCst);
+}
+
+/// Get the cancellation token for the current run, cancelled together with the abort flag
+pub fn abort_token() -> CancellationToken {
+    lock_or_recover(&ABORT_TOKEN, "ABORT_TOKEN").clone()
+}
+
+/// Why a running sync stopped early
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interruption {
+    Aborted,  // The user requested an abort
+    TimedOut, // The run exceeded its timeout
+}
+
+/// Abort token and deadline of a sync run, used to stop waits and read-only requests early
+#[derive(Clone)]
+pub struct RunInterrupt {
+    token: CancellationToken,
+    deadline: Option<Instant>, // None means the run has no timeout
+}
+
+impl RunInterrupt {
+    /// Create an interrupt whose deadline is `timeout` from now
+    pub fn new(token: CancellationToken, timeout: Option<Duration>) -> Self {
+        RunInterrupt {
+            token,
+            deadline: timeout.map(|timeout| Instant::now() + timeout),
+        }
+    }
+    
+    /// Check without waiting whether the run was interrupted, an abort wins over the timeout
+    pub fn check(&self) -> Option<Interruption> {
+        if self.token.is_cancelled() {
+            Some(Interruption::Aborted)
+        } else if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            Some(Interruption::TimedOut)
+        } else {
+            None
+        }
+    }
+    
+    /// Wait until the run is aborted or times out, forever if neither happens
+    pub async fn interrupted(&self) -> Interruption {
+        let deadline = async {
+            match self.deadline {
+                Some(deadline) => sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+        
+        tokio::select! {
+            biased;
+            _ = self.token.cancelled() => Interruption::Aborted,
+            _ = deadline => Interruption::TimedOut,
+        }
+    }
+    
+    /// Wait while `paused` returns true, checking it every `poll_interval`
+    ///
+    /// Returns None once resumed, or the interruption if the run is aborted
+    /// or times out while paused
+    pub async fn wait_while_paused(&self, paused: impl Fn() -> bool, poll_interval: Duration) -> Option<Interruption> {
+        while paused() {
+            tokio::select! {
+                biased;
+                interruption = self.interrupted() => return Some(interruption),
+                _ = sleep(poll_interval) => {},
+            }
+        }
+        None
+    }
+    
+    /// Run a future unless the run is interrupted first, dropping the future in that case
+    ///
+    /// Only for steps that are safe to drop halfway, i.e. that don't write to JTL
+    pub async fn run<F: Future>(&self, future: F) -> std::result::Result<F::Output, Interruption> {
+        tokio::select! {
+            biased;
+            interruption = self.interrupted() => Err(interruption),
+            output = future => Ok(output),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn cancelling_stops_a_pending_request_promptly() {
+        let token = CancellationToken::new();
+        let interrupt = RunInterrupt::new(token.clone(), None);
+        
+        let canceller = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            token.cancel();
+        });
+        
+        let started = Instant::now();
+        let result = interrupt.run(std::future::pending::<()>()).await;
+        canceller.await.unwrap();
+        
+        assert_eq!(result, Err(Interruption::Aborted));
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(interrupt.check(), Some(Interruption::Aborted));
+    }
+    
+    #[tokio::test]
+    async fn uninterrupted_run_returns_the_output() {
+        let interrupt = RunInterrupt::new(CancellationToken::new(), Some(Duration::from_secs(60)));
+        
+        assert_eq!(interrupt.run(async { 42 }).await, Ok(42));
+        assert_eq!(interrupt.check(), None);
+    }
+    
+    #[tokio::test]
+    async fn pausing_halts_until_resumed() {
+        let interrupt = RunInterrupt::new(CancellationToken::new(), None);
+        let paused = std::sync::Arc::new(AtomicBool::new(true));
+        
+        let resumer = tokio::spawn({
+            let paused = paused.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                paused.store(false, Ordering::SeqCst);
+            }
+        });
+        
+        let started = Instant::now();
+        let result = interrupt.wait_while_paused(|| paused.load(Ordering::SeqCst), Duration::from_millis(5)).await;
+        resumer.await.unwrap();
+        
+        assert_eq!(result, None);
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+    
+    #[tokio::test]
+    async fn run_timeout_ends_a_pause() {
+        let interrupt = RunInterrupt::new(CancellationToken::new(), Some(Duration::from_millis(20)));
+        
+        let started = Instant::now();
+        let result = interrupt.wait_while_paused(|| true, Duration::from_millis(5)).await;
+        
+        assert_eq!(result, Some(Interruption::TimedOut));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -1,6 +1,44 @@
+use lazy_static::lazy_static;
+use log::Level;
 use serde::Serialize;
+use std::sync::Mutex;
 use tauri::{AppHandle, Runtime, Manager, Window, Emitter};
 
+use crate::models::LogEntry;
+use crate::utils::lock::lock_or_recover;
+
+lazy_static! {
+    // Most verbose level forwarded to the UI, mirrors `AppSettings::log_level`
+    static ref UI_LOG_LEVEL: Mutex<String> = Mutex::new("info".to_string());
+}
+
+/// Whether a log entry at `level` passes the `configured` minimum level
+///
+/// Unknown levels on either side are always emitted
+pub fn should_emit_log(level: &str, configured: &str) -> bool {
+    match (level.trim().parse::<Level>(), configured.trim().parse::<Level>()) {
+        (Ok(level), Ok(configured)) => level <= configured,
+        _ => true,
+    }
+}
+
+/// Set the minimum level of log entries forwarded to the UI
+pub fn set_ui_log_level(level: &str) {
+    *lock_or_recover(&UI_LOG_LEVEL, "UI_LOG_LEVEL") = level.to_string();
+}
+
+/// Emit a log entry to the UI unless it is below the configured level
+///
+/// Only the UI event is filtered; the `log` crate output keeps its own filter
+pub fn emit_log<R: Runtime>(app_handle: &AppHandle<R>, entry: LogEntry) -> tauri::Result<()> {
+    let configured = lock_or_recover(&UI_LOG_LEVEL, "UI_LOG_LEVEL").clone();
+    if !should_emit_log(&entry.level, &configured) {
+        return Ok(());
+    }
+    
+    app_handle.emit("log", entry)
+}
+
 /// Helper function to emit events to windows
 pub fn emit_to_window<R: Runtime, T: Serialize + Clone>(
     window: &Window<R>, 
@@ -22,4 +60,18 @@ pub fn emit_to_all<R: Runtime, T: Serialize + Clone>(
     app_handle
         .emit(event, payload)
         .map_err(|e| format!("Failed to emit event: {}", e))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn filters_log_entries_below_the_configured_level() {
+        assert!(!should_emit_log("info", "warn"));
+        assert!(should_emit_log("warn", "warn"));
+        assert!(should_emit_log("error", "warn"));
+        assert!(should_emit_log("debug", "debug"));
+        assert!(should_emit_log("success", "warn"));
+    }
+}
@@ -1,17 +1,59 @@
-use chrono::Utc;
+use chrono::{TimeZone, Utc};
+use chrono_tz::Tz;
 
 /// Format a date string into ISO 8601 format
 pub fn format_iso_date(date_str: &str) -> String {
+    format_iso_date_in(date_str, Tz::UTC)
+}
+
+/// Format a date string given in local time of `timezone` as UTC ISO 8601
+pub fn format_iso_date_in(date_str: &str, timezone: Tz) -> String {
     // Try to parse the input date format
     if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(date_str, "%Y-%m-%d %H:%M:%S") {
-        // Convert to DateTime<Utc> and format as ISO 8601
-        return chrono::DateTime::<Utc>::from_utc(dt, Utc).to_rfc3339();
+        // Times repeated at the end of DST take the earlier one, skipped times are read as UTC
+        return match timezone.from_local_datetime(&dt).earliest() {
+            Some(local) => local.with_timezone(&Utc).to_rfc3339(),
+            None => chrono::DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc).to_rfc3339(),
+        };
     }
     
     // If parsing fails, return the current time in ISO format
     Utc::now().to_rfc3339()
 }
 
+/// Clean a line item name for JTL: control characters become spaces, whitespace runs collapse
+pub fn sanitize_item_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Shorten text to at most `max_chars` characters, ending in an ellipsis when cut
+///
+/// Returns `None` if the text already fits
+pub fn truncate_with_ellipsis(text: &str, max_chars: usize) -> Option<String> {
+    if text.chars().count() <= max_chars {
+        return None;
+    }
+    
+    let kept: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    Some(format!("{}…", kept.trim_end()))
+}
+
+/// Round a price to `decimals` places, halves away from zero
+///
+/// Values like 1.005 are stored slightly below the half in binary, the nudge
+/// makes them round up as they would on paper.
+pub fn round_price(value: f64, decimals: u8) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    let scaled = value * factor;
+    let nudge = scaled.signum() * scaled.abs().max(1.0) * 1e-12;
+    (scaled + nudge).round() / factor
+}
+
 /// Generate a timestamp for logs
 pub fn get_timestamp() -> String {
     Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
@@ -23,4 +65,70 @@ pub fn parse_float(value: Option<&str>) -> f64 {
         Some(val) => val.parse::<f64>().unwrap_or(0.0),
         None => 0.0,
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn converts_berlin_winter_time_to_utc() {
+        // CET is UTC+1
+        assert_eq!(format_iso_date_in("2024-01-15 12:00:00", Tz::Europe__Berlin), "2024-01-15T11:00:00+00:00");
+    }
+    
+    #[test]
+    fn converts_berlin_summer_time_to_utc() {
+        // CEST is UTC+2
+        assert_eq!(format_iso_date_in("2024-07-15 12:00:00", Tz::Europe__Berlin), "2024-07-15T10:00:00+00:00");
+    }
+    
+    #[test]
+    fn repeated_hour_at_end_of_dst_takes_the_earlier_time() {
+        // 02:30 happens twice on 2024-10-27, first in CEST
+        assert_eq!(format_iso_date_in("2024-10-27 02:30:00", Tz::Europe__Berlin), "2024-10-27T00:30:00+00:00");
+    }
+    
+    #[test]
+    fn skipped_hour_at_start_of_dst_is_read_as_utc() {
+        assert_eq!(format_iso_date_in("2024-03-31 02:30:00", Tz::Europe__Berlin), "2024-03-31T02:30:00+00:00");
+    }
+    
+    #[test]
+    fn rounds_halves_up_despite_binary_representation() {
+        // 1.005 and 2.675 are stored just below the half
+        assert_eq!(round_price(1.005, 2), 1.01);
+        assert_eq!(round_price(2.675, 2), 2.68);
+    }
+    
+    #[test]
+    fn rounds_negative_halves_away_from_zero() {
+        assert_eq!(round_price(-1.005, 2), -1.01);
+    }
+    
+    #[test]
+    fn keeps_zero() {
+        assert_eq!(round_price(0.0, 2), 0.0);
+    }
+    
+    #[test]
+    fn rounds_to_whole_numbers() {
+        assert_eq!(round_price(2.5, 0), 3.0);
+        assert_eq!(round_price(2.49, 0), 2.0);
+        assert_eq!(round_price(-2.5, 0), -3.0);
+    }
+    
+    #[test]
+    fn truncates_with_ellipsis_only_when_too_long() {
+        assert_eq!(truncate_with_ellipsis("Hello", 5), None);
+        assert_eq!(truncate_with_ellipsis("Hello World", 6).as_deref(), Some("Hello…"));
+        assert_eq!(truncate_with_ellipsis("Hello World", 7).as_deref(), Some("Hello…"));
+        assert_eq!(truncate_with_ellipsis("Grüße aus Köln", 5).as_deref(), Some("Grüß…"));
+    }
+    
+    #[test]
+    fn item_names_lose_newlines_and_tabs() {
+        assert_eq!(sanitize_item_name("T-Shirt\nBlue\tXL"), "T-Shirt Blue XL");
+        assert_eq!(sanitize_item_name("  Mug \r\n\t\t large "), "Mug large");
+    }
+}
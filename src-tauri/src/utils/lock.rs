@@ -0,0 +1,34 @@
+use log::warn;
+use std::sync::{Mutex, MutexGuard};
+
+/// Lock a mutex, recovering the data if a previous holder panicked
+///
+/// The poison flag is cleared so the warning is only logged once per panic
+pub fn lock_or_recover<'a, T>(mutex: &'a Mutex<T>, name: &str) -> MutexGuard<'a, T> {
+    mutex.lock().unwrap_or_else(|poisoned| {
+        warn!("Lock '{}' was poisoned by a panicked task, recovering", name);
+        mutex.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    
+    #[test]
+    fn recovers_data_from_a_poisoned_mutex() {
+        let mutex = Mutex::new(vec![1]);
+        
+        let _ = catch_unwind(AssertUnwindSafe(|| {
+            let mut data = mutex.lock().unwrap();
+            data.push(2);
+            panic!("holder panicked");
+        }));
+        assert!(mutex.is_poisoned());
+        
+        assert_eq!(*lock_or_recover(&mutex, "test"), vec![1, 2]);
+        assert!(!mutex.is_poisoned());
+    }
+}
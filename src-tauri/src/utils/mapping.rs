@@ -1,7 +1,8 @@
 use std::collections::HashMap;
 use lazy_static::lazy_static;
-use log::info;
+use log::{info, warn};
 
+use crate::config::shop::ShopConfig;
 use crate::db::models::{VirtueMartOrder, JtlAddress};
 
 // Default value for unknown payment methods
@@ -24,6 +25,22 @@ lazy_static! {
     };
 }
 
+// Display names of the JTL payment methods the mapping produces
+lazy_static! {
+    static ref PAYMENT_METHOD_NAMES: HashMap<i32, &'static str> = {
+        let mut map = HashMap::new();
+        map.insert(2, "Bank Transfer");
+        map.insert(4, "Credit Card");
+        map.insert(9, "PayPal-Express");
+        map.insert(10, "PayPal-Plus");
+        map.insert(27, "Cash");
+        map.insert(34, "Amazon Pay Checkout");
+        map.insert(38, "Giropay");
+        map.insert(39, "Sofortüberweisung");
+        map
+    };
+}
+
 // Country code mapping
 lazy_static! {
     static ref COUNTRY_MAP: HashMap<i32, &'static str> = {
@@ -42,11 +59,58 @@ lazy_static! {
     };
 }
 
+// EU member states by ISO code
+const EU_COUNTRIES: [&str; 27] = [
+    "AT", "BE", "BG", "CY", "CZ", "DE", "DK", "EE", "ES", "FI", "FR", "GR", "HR", "HU",
+    "IE", "IT", "LT", "LU", "LV", "MT", "NL", "PL", "PT", "RO", "SE", "SI", "SK",
+];
+
+/// Check whether an ISO country code belongs to an EU member state
+pub fn is_eu_country(country_iso: &str) -> bool {
+    EU_COUNTRIES.contains(&country_iso.to_ascii_uppercase().as_str())
+}
+
 /// Get country code from country ID
 pub fn get_country_code(id: i32) -> Option<&'static str> {
     COUNTRY_MAP.get(&id).copied()
 }
 
+/// Resolve the ISO country code of an address from the direct column or the id map
+fn known_country_code(address_data: &VirtueMartOrder) -> Option<String> {
+    if let Some(code) = address_data.country_code.as_deref() {
+        if code.len() == 2 && code.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Some(code.to_ascii_uppercase());
+        }
+    }
+    
+    address_data.virtuemart_country_id
+        .filter(|id| *id > 0)
+        .and_then(get_country_code)
+        .map(str::to_string)
+}
+
+/// Resolve the ISO country code of an address, falling back to the shop's default country
+pub fn resolve_country_code(address_data: &VirtueMartOrder, shop: &ShopConfig) -> String {
+    known_country_code(address_data)
+        .unwrap_or_else(|| shop.default_country_iso.to_ascii_uppercase())
+}
+
+/// All JTL payment method IDs the mapping can produce
+pub fn jtl_payment_method_ids() -> Vec<i32> {
+    let mut ids: Vec<i32> = PAYMENT_METHOD_MAPPING.values().copied().collect();
+    ids.push(DEFAULT_PAYMENT_METHOD_ID);
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+/// Display name of a JTL payment method, "Payment method <id>" if unknown
+pub fn payment_method_name(jtl_id: i32) -> String {
+    PAYMENT_METHOD_NAMES.get(&jtl_id)
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("Payment method {}", jtl_id))
+}
+
 /// Map VirtueMart payment method to JTL payment method
 pub fn map_payment_method(payment_method_id: Option<i32>) -> i32 {
     match payment_method_id {
@@ -68,11 +132,80 @@ pub fn map_payment_method(payment_method_id: Option<i32>) -> i32 {
     }
 }
 
+/// Check whether an email address looks deliverable (local@domain.tld)
+pub fn is_valid_email(email: &str) -> bool {
+    let email = email.trim();
+    if email.is_empty() || email.chars().any(|c| c.is_whitespace()) {
+        return false;
+    }
+
+    let mut parts = email.split('@');
+    let (local, domain) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(local), Some(domain), None) => (local, domain),
+        _ => return false,
+    };
+
+    !local.is_empty()
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !domain.contains("..")
+}
+
+/// Build a placeholder email for orders without a usable address
+pub fn placeholder_email(order_id: i32, shop: &ShopConfig) -> String {
+    let domain = shop.placeholder_email_domain.clone()
+        .filter(|d| !d.trim().is_empty())
+        .unwrap_or_else(|| shop.name.trim().to_lowercase().replace(' ', "-"));
+
+    format!("noreply+{}@{}", order_id, domain.trim())
+}
+
+/// Return the order's email if valid, otherwise a shop-specific placeholder
+pub fn resolve_email(address_data: &VirtueMartOrder, shop: &ShopConfig) -> String {
+    let email = address_data.email.clone().unwrap_or_default();
+    if is_valid_email(&email) {
+        return email.trim().to_string();
+    }
+
+    let placeholder = placeholder_email(address_data.virtuemart_order_id, shop);
+    warn!("Invalid or missing email '{}' for order {} in shop '{}', using placeholder: {}",
+          email, address_data.virtuemart_order_id, shop.name, placeholder);
+    placeholder
+}
+
+/// Strip everything but digits from a phone number, keeping a leading `+`
+pub fn normalize_phone(phone: &str) -> String {
+    let phone = phone.trim();
+    let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+
+    if phone.starts_with('+') && !digits.is_empty() {
+        format!("+{}", digits)
+    } else {
+        digits
+    }
+}
+
+/// Normalize an optional phone field, logging when the value changes
+fn resolve_phone(phone: &Option<String>, order_id: i32) -> String {
+    let raw = phone.clone().unwrap_or_default();
+    let normalized = normalize_phone(&raw);
+    if normalized != raw.trim() {
+        info!("Normalized phone number '{}' to '{}' for order {}", raw, normalized, order_id);
+    }
+    normalized
+}
+
 /// Create a JTL address object from a VirtueMart address
-pub fn create_address_object(address_data: &VirtueMartOrder) -> JtlAddress {
+pub fn create_address_object(address_data: &VirtueMartOrder, shop: &ShopConfig) -> JtlAddress {
+    if known_country_code(address_data).is_none() {
+        warn!("Order {} has no known country (ID: {:?}), using default country {} for shop '{}'", 
+              address_data.virtuemart_order_id, address_data.virtuemart_country_id, shop.default_country_iso, shop.name);
+    }
+    
     JtlAddress {
         City: address_data.city.clone().unwrap_or_default(),
-        CountryIso: get_country_code(address_data.virtuemart_country_id.unwrap_or_default()).unwrap_or("DE").to_string(),
+        CountryIso: resolve_country_code(address_data, shop),
         Company: address_data.company.clone().unwrap_or_default(),
         FormOfAddress: String::new(),
         Title: String::new(),
@@ -85,9 +218,96 @@ pub fn create_address_object(address_data: &VirtueMartOrder) -> JtlAddress {
         Address2: String::new(),
         PostalCode: address_data.zip.clone().unwrap_or_default(),
         State: String::new(),
-        PhoneNumber: address_data.phone_1.clone().unwrap_or_default(),
-        MobilePhoneNumber: address_data.phone_2.clone().unwrap_or_default(),
-        EmailAddress: address_data.email.clone().unwrap_or_default(),
+        PhoneNumber: resolve_phone(&address_data.phone_1, address_data.virtuemart_order_id),
+        MobilePhoneNumber: resolve_phone(&address_data.phone_2, address_data.virtuemart_order_id),
+        EmailAddress: resolve_email(address_data, shop),
         Fax: String::new(),
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn accepts_plain_email() {
+        assert!(is_valid_email("a@b.c"));
+        assert!(is_valid_email("  a@b.c  "));
+    }
+    
+    #[test]
+    fn rejects_malformed_emails() {
+        assert!(!is_valid_email("ab.c"));
+        assert!(!is_valid_email("a@b@c.d"));
+        assert!(!is_valid_email("a@.c"));
+        assert!(!is_valid_email("a@b..c"));
+        assert!(!is_valid_email("a@b.c."));
+        assert!(!is_valid_email("a b@c.d"));
+        assert!(!is_valid_email("@b.c"));
+        assert!(!is_valid_email(""));
+    }
+    
+    #[test]
+    fn placeholder_uses_shop_name_without_spaces() {
+        let shop = ShopConfig::new(" My Test Shop ");
+        assert_eq!(placeholder_email(42, &shop), "noreply+42@my-test-shop");
+    }
+    
+    #[test]
+    fn placeholder_prefers_configured_domain() {
+        let mut shop = ShopConfig::new("My Test Shop");
+        shop.placeholder_email_domain = Some(" example.com ".to_string());
+        assert_eq!(placeholder_email(42, &shop), "noreply+42@example.com");
+    }
+    
+    #[test]
+    fn normalizes_formatted_phone_numbers() {
+        assert_eq!(normalize_phone("+49 (0) 30-123"), "+49030123");
+        assert_eq!(normalize_phone(" +49 30 123 "), "+4930123");
+        assert_eq!(normalize_phone("0049 30/123"), "004930123");
+    }
+    
+    #[test]
+    fn normalizes_empty_phone_numbers() {
+        assert_eq!(normalize_phone(""), "");
+        assert_eq!(normalize_phone("+"), "");
+        assert_eq!(normalize_phone(" - "), "");
+    }
+    
+    #[test]
+    fn names_known_and_unknown_payment_methods() {
+        assert_eq!(payment_method_name(map_payment_method(Some(9))), "PayPal-Express");
+        assert_eq!(payment_method_name(2), "Bank Transfer");
+        assert_eq!(payment_method_name(999), "Payment method 999");
+    }
+    
+    #[test]
+    fn missing_country_falls_back_to_the_shop_default() {
+        let mut shop = ShopConfig::new("Test Shop");
+        shop.default_country_iso = "at".to_string();
+        
+        for country_id in [None, Some(0)] {
+            let order = VirtueMartOrder { virtuemart_country_id: country_id, ..Default::default() };
+            assert_eq!(known_country_code(&order), None);
+            assert_eq!(resolve_country_code(&order, &shop), "AT");
+        }
+        
+        let order = VirtueMartOrder { virtuemart_country_id: Some(204), ..Default::default() };
+        assert_eq!(resolve_country_code(&order, &shop), "CH");
+    }
+    
+    #[test]
+    fn direct_country_code_bypasses_the_id_lookup() {
+        let shop = ShopConfig::new("Test Shop");
+        let order = VirtueMartOrder {
+            country_code: Some("nl".to_string()),
+            virtuemart_country_id: Some(81),
+            ..Default::default()
+        };
+        assert_eq!(resolve_country_code(&order, &shop), "NL");
+        
+        // Anything but a 2-letter code falls back to the id
+        let order = VirtueMartOrder { country_code: Some("NLD".to_string()), ..order };
+        assert_eq!(resolve_country_code(&order, &shop), "DE");
+    }
+}
@@ -1,10 +1,14 @@
 pub mod abort;
 pub mod format;
+pub mod lock;
 pub mod mapping;
 pub mod emit;
+pub mod tasks;
 
 // Re-export key items for easier use
-pub use abort::{should_abort, reset_abort_flag, set_abort_flag};
-pub use format::{format_iso_date, get_timestamp};
+pub use abort::{should_abort, reset_abort_flag, set_abort_flag, abort_token, should_pause, set_pause_flag};
+pub use format::{format_iso_date, format_iso_date_in, get_timestamp, sanitize_item_name};
+pub use lock::lock_or_recover;
 pub use mapping::{map_payment_method, create_address_object, get_country_code};
-pub use emit::{emit_to_window, emit_to_all};
\ No newline at end of file
+pub use emit::{emit_to_window, emit_to_all, emit_log, should_emit_log, set_ui_log_level};
+pub use tasks::{SyncTaskGuard, ShopSyncGuard, is_shop_syncing, running_sync_count, wait_for_running_syncs};
\ No newline at end of file
@@ -0,0 +1,138 @@
+use lazy_static::lazy_static;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+use crate::error::{Result, Error};
+use crate::utils::lock::lock_or_recover;
+
+// Number of sync tasks currently running in the background
+static RUNNING_SYNCS: AtomicUsize = AtomicUsize::new(0);
+
+// Set once the app has started shutting down
+static SHUTDOWN_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    // IDs of shops with a sync in progress
+    static ref SYNCING_SHOPS: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+// How often to re-check the running task counter while waiting
+const POLL_INTERVAL_MS: u64 = 100;
+
+/// Marks a sync task as running for as long as the guard is alive
+pub struct SyncTaskGuard;
+
+impl SyncTaskGuard {
+    /// Register a new running sync task
+    pub fn acquire() -> Self {
+        RUNNING_SYNCS.fetch_add(1, Ordering::SeqCst);
+        SyncTaskGuard
+    }
+    
+    /// Register a new running sync task unless `max_running` tasks are already running
+    pub fn try_acquire(max_running: usize) -> Result<Self> {
+        RUNNING_SYNCS.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |running| (running < max_running).then_some(running + 1))
+            .map(|_| SyncTaskGuard)
+            .map_err(|running| Error::Sync(format!(
+                "{} synchronizations are already running, the configured maximum is {}", running, max_running
+            )))
+    }
+}
+
+impl Drop for SyncTaskGuard {
+    fn drop(&mut self) {
+        RUNNING_SYNCS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Marks a shop as syncing for as long as the guard is alive
+///
+/// Released on drop, so the shop is freed again after errors, aborts and panics
+pub struct ShopSyncGuard {
+    shop_id: String,
+}
+
+impl ShopSyncGuard {
+    /// Mark a shop as syncing, failing if a sync of it is already running
+    pub fn acquire(shop_id: &str) -> Result<Self> {
+        if !lock_or_recover(&SYNCING_SHOPS, "SYNCING_SHOPS").insert(shop_id.to_string()) {
+            return Err(Error::Sync(format!("Synchronization for shop '{}' is already running", shop_id)));
+        }
+        
+        Ok(ShopSyncGuard { shop_id: shop_id.to_string() })
+    }
+}
+
+impl Drop for ShopSyncGuard {
+    fn drop(&mut self) {
+        lock_or_recover(&SYNCING_SHOPS, "SYNCING_SHOPS").remove(&self.shop_id);
+    }
+}
+
+/// Check whether a sync of a shop is currently running
+pub fn is_shop_syncing(shop_id: &str) -> bool {
+    lock_or_recover(&SYNCING_SHOPS, "SYNCING_SHOPS").contains(shop_id)
+}
+
+/// Get the number of running sync tasks
+pub fn running_sync_count() -> usize {
+    RUNNING_SYNCS.load(Ordering::SeqCst)
+}
+
+/// Mark the app as shutting down; returns false if shutdown was already requested
+pub fn request_shutdown() -> bool {
+    !SHUTDOWN_REQUESTED.swap(true, Ordering::SeqCst)
+}
+
+/// Wait until all running sync tasks have finished or the timeout elapses
+///
+/// Returns true if all tasks finished in time
+pub async fn wait_for_running_syncs(timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    
+    while running_sync_count() > 0 {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        sleep(Duration::from_millis(POLL_INTERVAL_MS)).await;
+    }
+    
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn second_sync_of_a_shop_is_rejected_while_others_proceed() {
+        let first = ShopSyncGuard::acquire("guard-test-a").unwrap();
+        
+        assert!(matches!(ShopSyncGuard::acquire("guard-test-a"), Err(Error::Sync(_))));
+        assert!(is_shop_syncing("guard-test-a"));
+        
+        let other = ShopSyncGuard::acquire("guard-test-b").unwrap();
+        drop(other);
+        
+        drop(first);
+        assert!(!is_shop_syncing("guard-test-a"));
+        assert!(ShopSyncGuard::acquire("guard-test-a").is_ok());
+    }
+    
+    #[tokio::test]
+    async fn waiting_ends_once_running_syncs_finish() {
+        let guard = SyncTaskGuard::acquire();
+        assert!(!wait_for_running_syncs(Duration::from_millis(50)).await);
+        
+        let finisher = tokio::spawn(async move {
+            sleep(Duration::from_millis(50)).await;
+            drop(guard);
+        });
+        
+        assert!(wait_for_running_syncs(Duration::from_secs(5)).await);
+        finisher.await.unwrap();
+    }
+}
@@ -222,10 +222,17 @@ export class TauriApiService {
   }
 
   /**
-   * Remove a shop
+   * Remove a shop, or only archive it when `archive` is set
    */
-  static async removeShop(shopId: string): Promise<AppConfig> {
-    return this.invoke<AppConfig>("remove_shop_command", { shop_id: shopId });
+  static async removeShop(shopId: string, archive = false): Promise<AppConfig> {
+    return this.invoke<AppConfig>("remove_shop_command", { shop_id: shopId, archive });
+  }
+
+  /**
+   * Restore an archived shop
+   */
+  static async unarchiveShop(shopId: string): Promise<AppConfig> {
+    return this.invoke<AppConfig>("unarchive_shop_command", { shop_id: shopId });
   }
 
   /**
@@ -4,6 +4,7 @@ export interface DatabaseConfig {
   user: string;
   password: string;
   database: string;
+  port?: number;
 }
 
 export interface ScheduledJob {
@@ -21,6 +22,20 @@ export interface TablesConfig {
   orders: string;
   orderItems: string;
   customers: string;
+  country_code_column?: string;
+  tracking_number_column?: string;
+  carrier_column?: string;
+  currency_code_column?: string;
+  currency_factor_column?: string;
+  column_map?: Record<string, string>;
+}
+
+// JTL company a shop is booked under
+export interface JtlCompanyProfile {
+  company_id: number;
+  internal_company_id: number;
+  departure_country_iso: string;
+  currency: string;
 }
 
 // Shop configuration
@@ -30,6 +45,54 @@ export interface ShopConfig {
   joomla: DatabaseConfig;
   jtl: DatabaseConfig;
   tables: TablesConfig;
+  placeholder_email_domain?: string;
+  hold_policy?: "Always" | "OnlyUnpaid" | "Never";
+  enabled?: boolean;
+  line_item_name_prefix?: string | null;
+  order_date_source?: "Created" | "Paid" | "Modified";
+  skip_orders_without_billing?: boolean;
+  min_order_total?: number;
+  allow_non_positive_totals?: boolean;
+  shipping_tax_rate?: number;
+  zero_rate_exports?: boolean;
+  emit_explicit_tax_line?: boolean;
+  allow_empty_orders?: boolean;
+  allow_service_only_orders?: boolean;
+  customer_number_prefix?: string;
+  order_number_prefix?: string;
+  order_time_column?: 'CreatedOn' | 'ModifiedOn' | 'Either';
+  legacy_order_numbers?: boolean;
+  default_country_iso?: string;
+  internal_note_template?: string | null;
+  sync_hours?: number | null;
+  timezone?: string;
+  excluded_email_domains?: string[];
+  excluded_user_ids?: number[];
+  tax_rate_by_sku_prefix?: [string, number][];
+  price_decimals?: number;
+  jtl_company?: JtlCompanyProfile;
+  max_comment_length?: number;
+  default_shipping_method_id?: number | null;
+  update_existing_customers?: boolean;
+  archived?: boolean;
+}
+
+// Global application settings
+export interface AppSettings {
+  inter_shop_delay_ms: number;
+  max_concurrent_syncs: number;
+  notify_on_complete: boolean;
+  notify_on_error: boolean;
+  proxy?: string;
+  compress_requests?: boolean;
+  log_level?: string;
+  strict_preflight?: boolean;
+  line_item_batch_size?: number | null;
+  pool_max_idle_per_host?: number | null;
+  pool_idle_timeout_secs?: number | null;
+  extra_headers?: Record<string, string>;
+  run_timeout_secs?: number | null;
+  connection_retries?: number;
 }
 
 // Application configuration
@@ -38,6 +101,10 @@ export interface AppConfig {
   current_shop_index: number;
   logFile: string;
   jtlApiPath: string;
+  settings?: AppSettings;
+  completion_webhook_url?: string;
+  webhook_secret?: string;
+  max_sync_hours?: number | null;
 }
 
 // Table column configuration
@@ -68,6 +135,26 @@ export interface SyncStats {
   last_sync_time: string | null;
   next_scheduled_run: string | null;
   aborted: boolean;
+  sync_hours?: number;
+  orders_missing_billing?: number;
+  empty_orders?: number;
+  updated_orders?: number;
+  last_error?: string | null;
+  skipped_test_orders?: number;
+  skipped_zero_total?: number;
+  failures_by_category?: Record<string, number>;
+  abort_reason?: string | null;
+}
+
+// Stats of all shops added up
+export interface AggregateStats {
+  shop_count: number;
+  total_orders: number;
+  synced_orders: number;
+  skipped_orders: number;
+  error_orders: number;
+  updated_orders: number;
+  last_sync_time: string | null;
 }
 
 // VirtueMart Order interface
@@ -162,6 +249,7 @@ export interface JtlOrder {
   SalesOrderPaymentDetails: JtlPaymentDetails;
   SalesOrderShippingDetail: JtlShippingDetails;
   Comment: string;
+  InternalNote?: string;
   LanguageIso: string;
 }
 
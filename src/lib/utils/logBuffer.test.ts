@@ -0,0 +1,33 @@
+import { describe, expect, it } from "vitest";
+import { addLog, emptyLogBuffers, mergeLogs, MAX_OTHER_LOGS, type LogBuffers } from "./logBuffer";
+
+function log(level: string, message: string, seconds: number) {
+  return { timestamp: new Date(seconds * 1000), message, level, category: "sync" };
+}
+
+describe("log buffers", () => {
+  it("keeps earlier errors when info entries overflow their buffer", () => {
+    let buffers: LogBuffers = emptyLogBuffers();
+    buffers = addLog(buffers, log("error", "Order failed", 0));
+
+    for (let i = 1; i <= MAX_OTHER_LOGS + 50; i++) {
+      buffers = addLog(buffers, log("info", `Info ${i}`, i));
+    }
+
+    const logs = mergeLogs(buffers.important, buffers.other);
+    expect(buffers.other).toHaveLength(MAX_OTHER_LOGS);
+    expect(logs.some((entry) => entry.message === "Order failed")).toBe(true);
+    expect(logs[logs.length - 1].message).toBe("Order failed");
+  });
+
+  it("merges both buffers newest first", () => {
+    let buffers: LogBuffers = emptyLogBuffers();
+    buffers = addLog(buffers, log("info", "first", 1));
+    buffers = addLog(buffers, log("warn", "second", 2));
+    buffers = addLog(buffers, { ...log("info", "third", 3), timestamp: "1970-01-01T00:00:03Z" as unknown as Date });
+
+    const logs = mergeLogs(buffers.important, buffers.other);
+    expect(logs.map((entry) => entry.message)).toEqual(["third", "second", "first"]);
+    expect(logs[0].time).toBe(3000);
+  });
+});
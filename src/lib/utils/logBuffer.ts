@@ -0,0 +1,63 @@
+import type { LogEntry } from "$lib/types";
+
+// Errors and warnings get their own buffer so a flood of info entries can't evict them
+export const MAX_IMPORTANT_LOGS = 500;
+export const MAX_OTHER_LOGS = 1000;
+
+// Log entry with its timestamp in milliseconds, computed once when the entry is added
+export interface BufferedLog extends LogEntry {
+  time: number;
+}
+
+// Both log buffers, each newest first
+export interface LogBuffers {
+  important: BufferedLog[];
+  other: BufferedLog[];
+}
+
+export function emptyLogBuffers(): LogBuffers {
+  return { important: [], other: [] };
+}
+
+// Whether a log level goes to the error/warning buffer
+export function isImportant(level: string): boolean {
+  return level === "error" || level === "warn" || level === "warning";
+}
+
+// Add an entry to its buffer, dropping the oldest entries beyond the buffer limit
+export function addLog(buffers: LogBuffers, log: LogEntry): LogBuffers {
+  // Timestamps arriving as JSON are strings
+  const timestamp = typeof log.timestamp === "string"
+    ? new Date(log.timestamp)
+    : log.timestamp;
+
+  const entry: BufferedLog = {
+    timestamp,
+    time: timestamp.getTime(),
+    message: log.message,
+    level: log.level,
+    category: log.category,
+  };
+
+  if (isImportant(entry.level)) {
+    return { ...buffers, important: [entry, ...buffers.important].slice(0, MAX_IMPORTANT_LOGS) };
+  }
+  return { ...buffers, other: [entry, ...buffers.other].slice(0, MAX_OTHER_LOGS) };
+}
+
+// Merge two newest-first buffers into one newest-first list
+export function mergeLogs(a: BufferedLog[], b: BufferedLog[]): BufferedLog[] {
+  const merged: BufferedLog[] = [];
+  let i = 0;
+  let j = 0;
+
+  while (i < a.length && j < b.length) {
+    if (a[i].time >= b[j].time) {
+      merged.push(a[i++]);
+    } else {
+      merged.push(b[j++]);
+    }
+  }
+
+  return merged.concat(a.slice(i), b.slice(j));
+}